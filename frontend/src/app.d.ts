@@ -4,16 +4,18 @@ declare global {
 	namespace App {
 		interface AuthUser {
 			id: number;
-			github_id: number;
+			github_id: number | null;
 			name: string | null;
 			avatar_url: string | null;
-			admin_level: number;
+			role: string;
+			permissions: import('$lib/types').Permission[];
 		}
 
 		interface ImpersonationInfo {
 			admin_id: number;
 			admin_name: string | null;
 			admin_avatar_url: string | null;
+			write: boolean;
 		}
 
 		interface Locals {
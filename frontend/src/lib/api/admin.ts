@@ -1,14 +1,14 @@
 import { Api } from '$lib/api/api';
 
-export async function impersonateUser(api: Api, userId: number) {
-	await api.get(`/admin/impersonate/${userId}`);
+export async function impersonateUser(api: Api, userId: number, write = false) {
+	await api.post(`/admin/impersonate/${userId}${write ? '?write=true' : ''}`);
 	window.location.href = '/';
 }
 
-export async function changeAdminLevel(api: Api, userId: number, targetLevel: number) {
-	if (targetLevel < 0) {
-		return;
-	}
+export async function changeRole(api: Api, userId: number, role: string) {
+	await api.put(`/admin/role/${userId}/${encodeURIComponent(role)}`);
+}
 
-	await api.put(`/admin/admin_level/${userId}/${targetLevel}`);
+export async function setBanned(api: Api, userId: number, banned: boolean) {
+	await api.put(`/admin/ban/${userId}/${banned}`);
 }
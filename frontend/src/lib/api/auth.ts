@@ -0,0 +1,40 @@
+import type {
+	IdentitiesResponse,
+	LocalCredentials,
+	ProvidersResponse,
+	RevokeSessionsResponse,
+	SessionsResponse
+} from '$lib/types/auth';
+import type { Api } from './api';
+
+export async function getProviders(api: Api) {
+	return api.get<ProvidersResponse>('/auth/providers');
+}
+
+export async function localLogin(api: Api, credentials: LocalCredentials) {
+	await api.post<void>('/auth/local/login', credentials);
+}
+
+export async function localRegister(api: Api, credentials: LocalCredentials) {
+	await api.post<void>('/auth/local/register', credentials);
+}
+
+export async function getIdentities(api: Api) {
+	return api.get<IdentitiesResponse>('/data/identities');
+}
+
+export async function deleteIdentity(api: Api, identityId: number) {
+	await api.delete<void>(`/data/identities/${identityId}`);
+}
+
+export async function getSessions(api: Api) {
+	return api.get<SessionsResponse>('/data/sessions');
+}
+
+export async function revokeSession(api: Api, sessionId: string) {
+	await api.delete<void>(`/data/sessions/${sessionId}`);
+}
+
+export async function revokeOtherSessions(api: Api) {
+	return api.post<RevokeSessionsResponse>('/data/sessions/revoke-others');
+}
@@ -0,0 +1,22 @@
+import type { GoalPeriod, GoalsResponse } from '$lib/types/goals';
+import type { Api } from './api';
+
+export interface CreateGoalRequest {
+	period: GoalPeriod;
+	target_seconds: number;
+	project?: string;
+	language?: string;
+	ignored_weekdays: string[];
+}
+
+export async function getGoals(api: Api) {
+	return api.get<GoalsResponse>('/data/goals');
+}
+
+export async function createGoal(api: Api, goal: CreateGoalRequest) {
+	return api.post<GoalsResponse>('/data/goals', goal);
+}
+
+export async function deleteGoal(api: Api, goalId: number) {
+	await api.delete<void>(`/data/goals/${goalId}`);
+}
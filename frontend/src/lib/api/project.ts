@@ -1,15 +1,31 @@
-import type { FullProjectListResponse, ProjectAliasesResponse } from '$lib/types/projects';
+import type {
+	FullProjectListResponse,
+	ProjectAliasesResponse,
+	ProjectRule,
+	ProjectRuleField,
+	ProjectRulesResponse
+} from '$lib/types/projects';
+import type { EntityPrivacy } from '$lib/types/settings';
 import type { Api } from './api';
 
 export interface UpdateProjectRequest {
 	project_url?: string;
 	hidden?: boolean;
+	entity_privacy?: EntityPrivacy | null;
 }
 
 export async function updateProject(api: Api, id: number, updates: UpdateProjectRequest) {
 	await api.put<void>(`/data/projects/${id}`, updates);
 }
 
+export async function renameProject(api: Api, id: number, name: string) {
+	return api.post<{ message: string }>(`/data/projects/${id}/rename`, { name });
+}
+
+export async function mergeProject(api: Api, id: number, targetId: number) {
+	return api.post<{ message: string }>(`/data/projects/${id}/merge`, { target_id: targetId });
+}
+
 export async function getProjectAliases(api: Api) {
 	return api.get<ProjectAliasesResponse>('/data/project_aliases');
 }
@@ -22,6 +38,29 @@ export async function deleteProjectAlias(api: Api, aliasId: number) {
 	await api.delete<void>(`/data/project_aliases/${aliasId}`);
 }
 
+export interface CreateProjectRuleRequest {
+	match_field: ProjectRuleField;
+	pattern: string;
+	target_project: string;
+	priority?: number;
+}
+
+export async function getProjectRules(api: Api) {
+	return api.get<ProjectRulesResponse>('/data/project_rules');
+}
+
+export async function createProjectRule(api: Api, rule: CreateProjectRuleRequest) {
+	return api.post<ProjectRule>('/data/project_rules', rule);
+}
+
+export async function deleteProjectRule(api: Api, ruleId: number) {
+	await api.delete<void>(`/data/project_rules/${ruleId}`);
+}
+
+export async function applyProjectRules(api: Api) {
+	return api.post<{ message: string }>('/data/project_rules/apply');
+}
+
 export async function getProjects(api: Api) {
 	return api.get<FullProjectListResponse>('/data/projects');
 }
@@ -1,7 +1,18 @@
 import type { Api } from './api';
+import type {
+	EntityPrivacy,
+	LanguageMapping,
+	LanguageMappingKind,
+	LanguageMappingsResponse
+} from '$lib/types/settings';
 
 export interface UpdateSettingsRequest {
 	timezone?: string;
+	public_line_stats?: boolean;
+	entity_privacy?: EntityPrivacy;
+	private_profile?: boolean;
+	email?: string;
+	weekly_digest?: boolean;
 }
 
 export interface UpdateSettingsResponse {
@@ -14,3 +25,25 @@ export async function updateSettings(
 ): Promise<UpdateSettingsResponse> {
 	return api.put<UpdateSettingsResponse>('/data/settings', settings);
 }
+
+export interface CreateLanguageMappingRequest {
+	kind: LanguageMappingKind;
+	source: string;
+	target: string;
+}
+
+export async function getLanguageMappings(api: Api) {
+	return api.get<LanguageMappingsResponse>('/data/language_mappings');
+}
+
+export async function createLanguageMapping(api: Api, mapping: CreateLanguageMappingRequest) {
+	return api.post<LanguageMapping>('/data/language_mappings', mapping);
+}
+
+export async function deleteLanguageMapping(api: Api, mappingId: number) {
+	await api.delete<void>(`/data/language_mappings/${mappingId}`);
+}
+
+export async function applyLanguageMappings(api: Api) {
+	return api.post<{ message: string }>('/data/language_mappings/apply');
+}
@@ -0,0 +1,24 @@
+import type { CreatedWebhookResponse, WebhookEvent, WebhooksResponse } from '$lib/types/webhooks';
+import type { Api } from './api';
+
+export interface CreateWebhookRequest {
+	url: string;
+	events: WebhookEvent[];
+	global: boolean;
+}
+
+export async function getWebhooks(api: Api) {
+	return api.get<WebhooksResponse>('/data/webhooks');
+}
+
+export async function createWebhook(api: Api, webhook: CreateWebhookRequest) {
+	return api.post<CreatedWebhookResponse>('/data/webhooks', webhook);
+}
+
+export async function deleteWebhook(api: Api, webhookId: number) {
+	await api.delete<void>(`/data/webhooks/${webhookId}`);
+}
+
+export async function testWebhook(api: Api, webhookId: number) {
+	await api.post<void>(`/data/webhooks/${webhookId}/test`);
+}
@@ -1,20 +1,25 @@
 import { browser } from '$app/environment';
-import { invalidateAll } from '$app/navigation';
+import { goto, invalidateAll } from '$app/navigation';
+import { resolve } from '$app/paths';
 import { writable } from 'svelte/store';
 import { createApi } from '$lib/api/api';
+import { getProviders } from '$lib/api/auth';
+import type { Permission } from '$lib/types';
 
 export interface User {
 	id: number;
-	github_id: number;
+	github_id: number | null;
 	name: string | null;
 	avatar_url: string | null;
-	admin_level: number;
+	role: string;
+	permissions: Permission[];
 }
 
 export type AuthErrorType =
 	| 'unauthorized' // 401/403 - session invalid, should clear
 	| 'network' // Network connectivity issues
 	| 'server' // 5xx server errors
+	| 'registration_closed' // New account refused by the server
 	| 'unknown' // Other unexpected errors
 	| null; // No error
 
@@ -45,12 +50,25 @@ const createAuthStore = () => {
 
 		clear: () => set({ error: null }),
 
-		login: async () => {
+		// signs in with the given provider, or picks one when there is a choice
+		// also used to link another account while signed in
+		login: async (provider?: string) => {
 			if (!browser) return;
 
 			try {
 				const api = createApi(fetch);
-				const { auth_url } = await api.get<{ auth_url: string }>('/auth/github/login');
+				if (!provider) {
+					const { providers, local_login } = await getProviders(api);
+					if (providers.length !== 1 || local_login) {
+						await goto(resolve('/login'));
+						return;
+					}
+					provider = providers[0].id;
+				}
+
+				const { auth_url } = await api.get<{ auth_url: string }>(
+					`/auth/${encodeURIComponent(provider)}/login`
+				);
 				if (auth_url) window.location.href = auth_url;
 			} catch (e) {
 				setError('server');
@@ -63,7 +81,7 @@ const createAuthStore = () => {
 
 			try {
 				const api = createApi(fetch);
-				await api.get('/auth/github/logout');
+				await api.post('/auth/logout');
 			} catch (e) {
 				console.log('Logout error:', e);
 			} finally {
@@ -1,4 +1,6 @@
-import type { PartialUser } from '.';
+import type { PartialUser, Role } from '.';
+
+export type AdminUserSort = 'role' | 'newest' | 'oldest' | 'total_time';
 
 export interface AdminResponse {
 	total_users: number;
@@ -7,7 +9,44 @@ export interface AdminResponse {
 	heartbeats_last_24h: number;
 	requests_per_second: number;
 	daily_activity: Array<{ date: string; count: number }>;
+	matching_users: number;
 	all_users: Array<PartialUser>;
+	roles: Role[];
 	limit: number;
 	offset: number;
 }
+
+export interface AdminUserSession {
+	device: string | null;
+	ip_address: string | null;
+	created_at: string;
+	last_seen_at: string;
+	expires_at: string;
+	impersonating: string | null;
+}
+
+export interface AdminUserImportJob {
+	id: number;
+	status: string;
+	imported_count: number | null;
+	processed_count: number | null;
+	error_message: string | null;
+	created_at: string;
+}
+
+export interface AdminUserDetailResponse {
+	user: PartialUser;
+	timezone: string;
+	heartbeats: {
+		today: number;
+		this_week: number;
+		this_month: number;
+		all_time: number;
+	};
+	last_heartbeat_at: string | null;
+	daily_activity: Array<{ date: string; count: number }>;
+	sessions: AdminUserSession[];
+	import_jobs: AdminUserImportJob[];
+	projects: Array<{ id: number; name: string; hidden: boolean; total_seconds: number }>;
+	source_types: Array<{ source_type: string; count: number }>;
+}
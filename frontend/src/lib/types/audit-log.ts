@@ -0,0 +1,18 @@
+export interface AuditLogRecord {
+	id: number;
+	actor_id: number;
+	actor_name: string | null;
+	effective_user_id: number;
+	effective_user_name: string | null;
+	action: string;
+	target: string | null;
+	metadata: Record<string, unknown>;
+	created_at: string;
+}
+
+export interface AuditLogResponse {
+	entries: AuditLogRecord[];
+	total: number;
+	limit: number;
+	offset: number;
+}
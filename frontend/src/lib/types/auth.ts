@@ -0,0 +1,50 @@
+export type ProviderKind = 'github' | 'gitlab' | 'gitea' | 'oidc';
+
+export interface LoginProvider {
+	id: string;
+	kind: ProviderKind;
+	name: string;
+}
+
+export interface ProvidersResponse {
+	providers: LoginProvider[];
+	local_login: boolean;
+	local_registration: boolean;
+}
+
+export interface LocalCredentials {
+	username: string;
+	password: string;
+}
+
+export interface Identity {
+	id: number;
+	provider: string;
+	username: string;
+	email: string | null;
+	created_at: string;
+}
+
+export interface IdentitiesResponse {
+	identities: Identity[];
+	providers: LoginProvider[];
+}
+
+export interface Session {
+	id: string;
+	device: string | null;
+	ip_address: string | null;
+	created_at: string;
+	last_seen_at: string;
+	expires_at: string;
+	current: boolean;
+	impersonating: string | null;
+}
+
+export interface SessionsResponse {
+	sessions: Session[];
+}
+
+export interface RevokeSessionsResponse {
+	revoked: number;
+}
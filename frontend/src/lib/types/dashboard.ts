@@ -13,4 +13,40 @@ export interface DashboardResponse {
 	editors: UsageStat[];
 	operating_systems: UsageStat[];
 	languages: UsageStat[];
+	machines: UsageStat[];
+	categories: UsageStat[];
+	dependencies: UsageStat[];
+	line_changes: LineChangeStats;
+}
+
+export interface LineChangeStat {
+	name: string;
+	line_additions: number;
+	line_deletions: number;
+	files_written: number;
+	write_seconds: number;
+	total_seconds: number;
+	write_ratio: number;
+	ai_line_changes: number;
+	human_line_changes: number;
+}
+
+export interface LineChangeStats {
+	total: LineChangeStat;
+	daily: LineChangeStat[];
+	projects: LineChangeStat[];
+}
+
+export interface DependencyGroup {
+	name: string;
+	dependencies: UsageStat[];
+}
+
+export interface DependencyStatsResponse {
+	range: string;
+	total_seconds: number;
+	human_readable_total: string;
+	dependencies: UsageStat[];
+	projects: DependencyGroup[];
+	languages: DependencyGroup[];
 }
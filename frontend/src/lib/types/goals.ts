@@ -0,0 +1,29 @@
+export type GoalPeriod = 'day' | 'week';
+
+export type RangeStatus = 'success' | 'fail' | 'ignored' | 'pending';
+
+export interface GoalPeriodProgress {
+	start: string;
+	end: string;
+	actual_seconds: number;
+	goal_seconds: number;
+	status: RangeStatus;
+}
+
+export interface GoalProgress {
+	id: number;
+	title: string;
+	period: GoalPeriod;
+	target_seconds: number;
+	project: string | null;
+	language: string | null;
+	ignored_weekdays: string[];
+	status: RangeStatus;
+	percent: number;
+	current_streak: number;
+	history: GoalPeriodProgress[];
+}
+
+export interface GoalsResponse {
+	goals: GoalProgress[];
+}
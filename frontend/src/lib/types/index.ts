@@ -1,10 +1,28 @@
+export type Permission =
+	| 'view_users'
+	| 'impersonate'
+	| 'impersonate_write'
+	| 'ban'
+	| 'manage_imports'
+	| 'view_api_keys'
+	| 'manage_roles'
+	| 'view_audit_log'
+	| 'global_webhooks';
+
+export interface Role {
+	name: string;
+	rank: number;
+	permissions: string[];
+	created_at: string;
+}
+
 export interface User {
 	id: number;
-	github_id: number;
+	github_id: number | null;
 	name: string;
 	avatar_url: string;
 	api_key: string; // UUID string
-	admin_level: number;
+	role: string;
 	is_banned: boolean;
 	created_at: string; // ISO date string
 	updated_at: string; // ISO date string
@@ -12,12 +30,13 @@ export interface User {
 
 export interface PartialUser {
 	id: number;
-	github_id: number;
+	github_id: number | null;
 	name: string;
 	avatar_url: string;
 	api_key?: string; // UUID string
-	admin_level: number;
+	role: string;
 	is_banned: boolean;
+	total_seconds: number;
 	created_at: string; // ISO date string
 	updated_at: string; // ISO date string
 }
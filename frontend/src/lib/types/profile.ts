@@ -10,14 +10,45 @@ export type Project = {
 	total_seconds: number;
 };
 
+export type Category = {
+	name: string;
+	total_seconds: number;
+};
+
+export type ProfileLineChanges = {
+	line_additions: number;
+	line_deletions: number;
+	files_written: number;
+	write_seconds: number;
+	total_seconds: number;
+	write_ratio: number;
+	ai_line_changes: number;
+	human_line_changes: number;
+};
+
 export type ProfileUser = {
 	username: string;
 	avatar_url: string;
-	admin_level: number;
+	role: string;
+};
+
+export type DailyTotal = {
+	date: string;
+	total_seconds: number;
+};
+
+export type ProfileActivity = {
+	days: DailyTotal[];
+	current_streak: number;
+	longest_streak: number;
+	best_day: DailyTotal | null;
 };
 
 export type ProfileResponse = {
 	user: ProfileUser;
 	projects: Project[];
+	categories: Category[];
+	line_changes?: ProfileLineChanges;
 	time: TimeInfo;
+	activity: ProfileActivity;
 };
@@ -1,14 +1,43 @@
+import type { LineChangeStats, UsageStat } from './dashboard';
+import type { EntityPrivacy } from './settings';
+
 export interface Project {
 	id: number;
 	name: string;
 	total_seconds: number;
 	project_url: string | null;
 	hidden: boolean;
+	entity_privacy: EntityPrivacy | null;
 	human_readable_total: string;
 	created_at: string | null;
 	updated_at: string | null;
 }
 
+export interface DailyTotal {
+	date: string;
+	total_seconds: number;
+}
+
+export interface ProjectDetailResponse {
+	id: number;
+	name: string;
+	project_url: string | null;
+	hidden: boolean;
+	entity_privacy: EntityPrivacy | null;
+	range: string;
+	total_seconds: number;
+	human_readable_total: string;
+	daily: DailyTotal[];
+	weekdays: number[];
+	hours: number[];
+	branches: UsageStat[];
+	entities: UsageStat[];
+	languages: UsageStat[];
+	editors: UsageStat[];
+	categories: UsageStat[];
+	line_changes: LineChangeStats;
+}
+
 export interface ProjectsResponse {
 	projects: Project[];
 }
@@ -44,3 +73,18 @@ export interface SimpleProject {
 export interface FullProjectListResponse {
 	projects: SimpleProject[];
 }
+
+export type ProjectRuleField = 'project' | 'entity';
+
+export interface ProjectRule {
+	id: number;
+	match_field: ProjectRuleField;
+	pattern: string;
+	target_project: string;
+	priority: number;
+	created_at: string;
+}
+
+export interface ProjectRulesResponse {
+	rules: ProjectRule[];
+}
@@ -1,6 +1,28 @@
 export interface SettingsResponse {
 	api_key?: string;
 	timezone: string;
+	public_line_stats: boolean;
+	entity_privacy: EntityPrivacy;
+	private_profile: boolean;
+	email: string | null;
+	email_editable: boolean;
+	weekly_digest: boolean;
+}
+
+export type EntityPrivacy = 'full' | 'basename' | 'hashed' | 'hidden';
+
+export type LanguageMappingKind = 'extension' | 'language';
+
+export interface LanguageMapping {
+	id: number;
+	kind: LanguageMappingKind;
+	source: string;
+	target: string;
+	created_at: string;
+}
+
+export interface LanguageMappingsResponse {
+	mappings: LanguageMapping[];
 }
 
 export interface ImportStartResponse {
@@ -0,0 +1,41 @@
+export type WebhookEvent =
+	| 'import.completed'
+	| 'import.failed'
+	| 'goal.reached'
+	| 'leaderboard.rank_changed'
+	| 'summary.daily'
+	| 'ping';
+
+export interface Webhook {
+	id: number;
+	url: string;
+	events: WebhookEvent[];
+	global: boolean;
+	enabled: boolean;
+	secret_hint: string;
+	created_at: string;
+}
+
+export interface WebhookDelivery {
+	id: number;
+	webhook_id: number;
+	event: WebhookEvent;
+	attempt: number;
+	status_code: number | null;
+	success: boolean;
+	error: string | null;
+	duration_ms: number;
+	created_at: string;
+}
+
+export interface WebhooksResponse {
+	webhooks: Webhook[];
+	deliveries: WebhookDelivery[];
+	events: WebhookEvent[];
+	can_create_global: boolean;
+}
+
+export interface CreatedWebhookResponse {
+	webhook: Webhook;
+	secret: string;
+}
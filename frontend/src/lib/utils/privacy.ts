@@ -0,0 +1,8 @@
+import type { EntityPrivacy } from '$lib/types/settings';
+
+export const entityPrivacyOptions: { value: EntityPrivacy; label: string }[] = [
+	{ value: 'full', label: 'Store full paths' },
+	{ value: 'basename', label: 'Store file names only' },
+	{ value: 'hashed', label: 'Store hashed paths' },
+	{ value: 'hidden', label: 'Do not store paths' }
+];
@@ -7,11 +7,12 @@ interface RawVerifyResponse {
 	valid: boolean;
 	user?: {
 		id: number;
-		github_id: number;
+		github_id: number | null;
 		username?: string | null;
 		name?: string | null;
 		avatar_url: string | null;
-		admin_level: number;
+		role: string;
+		permissions: App.AuthUser['permissions'];
 	};
 	impersonation?: App.ImpersonationInfo | null;
 }
@@ -29,7 +30,7 @@ export const load: ServerLoad = async ({ fetch, cookies, locals }) => {
 
 	if (sessionId) {
 		try {
-			const response = await fetch(`${BACKEND_API_URL}/auth/github/verify`, {
+			const response = await fetch(`${BACKEND_API_URL}/auth/verify`, {
 				headers: {
 					Cookie: `rustytime_session=${sessionId}`
 				}
@@ -48,7 +49,8 @@ export const load: ServerLoad = async ({ fetch, cookies, locals }) => {
 							github_id: data.user.github_id,
 							name,
 							avatar_url: data.user.avatar_url,
-							admin_level: data.user.admin_level
+							role: data.user.role,
+							permissions: data.user.permissions ?? []
 						},
 						impersonation: data.impersonation ?? null
 					};
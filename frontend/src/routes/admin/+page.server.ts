@@ -3,16 +3,24 @@ import type { AdminResponse } from '$lib/types/admin';
 import { createApi, ApiError } from '$lib/api/api';
 import { redirect, error } from '@sveltejs/kit';
 
+const FILTERS = ['search', 'banned', 'admin', 'has_imports', 'active_days', 'sort'] as const;
+
 export const load: PageServerLoad = async ({ fetch, depends, request, url }) => {
 	depends('app:admin');
 
-	const limit = parseInt(url.searchParams.get('limit') || '25', 10);
-	const offset = parseInt(url.searchParams.get('offset') || '0', 10);
+	const params = new URLSearchParams({
+		limit: url.searchParams.get('limit') || '25',
+		offset: url.searchParams.get('offset') || '0'
+	});
+	for (const key of FILTERS) {
+		const value = url.searchParams.get(key);
+		if (value) params.set(key, value);
+	}
 
 	try {
 		const cookieHeader = request.headers.get('cookie') || undefined;
 		const api = createApi(fetch, cookieHeader);
-		return await api.get<AdminResponse>(`/page/admin?limit=${limit}&offset=${offset}`);
+		return await api.get<AdminResponse>(`/page/admin?${params.toString()}`);
 	} catch (e) {
 		console.error('Error loading admin page data:', e);
 		const err = e as ApiError;
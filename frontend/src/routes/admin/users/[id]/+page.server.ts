@@ -0,0 +1,24 @@
+import type { PageServerLoad } from './$types';
+import type { AdminUserDetailResponse } from '$lib/types/admin';
+import { createApi, ApiError } from '$lib/api/api';
+import { redirect, error } from '@sveltejs/kit';
+
+export const load: PageServerLoad = async ({ fetch, depends, request, url, params }) => {
+	depends('app:admin-user');
+
+	try {
+		const cookieHeader = request.headers.get('cookie') || undefined;
+		const api = createApi(fetch, cookieHeader);
+		return await api.get<AdminUserDetailResponse>(`/page/admin/users/${params.id}`);
+	} catch (e) {
+		console.error('Error loading admin user data:', e);
+		const err = e as ApiError;
+		if (err.status === 401 || err.status === 403) {
+			throw redirect(
+				302,
+				`/?auth_error=unauthorized&redirect=${Buffer.from(url.pathname + url.search).toString('base64url')}`
+			);
+		}
+		throw error(err.status || 500, err.message);
+	}
+};
@@ -0,0 +1,35 @@
+import type { PageServerLoad } from './$types';
+import type { AuditLogResponse } from '$lib/types/audit-log';
+import { createApi, ApiError } from '$lib/api/api';
+import { redirect, error } from '@sveltejs/kit';
+
+const FILTERS = ['action', 'actor_id', 'effective_user_id', 'target'] as const;
+
+export const load: PageServerLoad = async ({ fetch, depends, request, url }) => {
+	depends('app:audit-log');
+
+	const params = new URLSearchParams({
+		limit: url.searchParams.get('limit') || '25',
+		offset: url.searchParams.get('offset') || '0'
+	});
+	for (const key of FILTERS) {
+		const value = url.searchParams.get(key);
+		if (value) params.set(key, value);
+	}
+
+	try {
+		const cookieHeader = request.headers.get('cookie') || undefined;
+		const api = createApi(fetch, cookieHeader);
+		return await api.get<AuditLogResponse>(`/page/audit_log?${params.toString()}`);
+	} catch (e) {
+		console.error('Error loading audit log page data:', e);
+		const err = e as ApiError;
+		if (err.status === 401 || err.status === 403) {
+			throw redirect(
+				302,
+				`/?auth_error=unauthorized&redirect=${Buffer.from(url.pathname + url.search).toString('base64url')}`
+			);
+		}
+		throw error(err.status || 500, err.message);
+	}
+};
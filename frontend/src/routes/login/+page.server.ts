@@ -0,0 +1,15 @@
+import type { PageServerLoad } from './$types';
+import type { ProvidersResponse } from '$lib/types/auth';
+import { createApi, ApiError } from '$lib/api/api';
+import { error } from '@sveltejs/kit';
+
+export const load: PageServerLoad = async ({ fetch }) => {
+	try {
+		const api = createApi(fetch);
+		return await api.get<ProvidersResponse>('/auth/providers');
+	} catch (e) {
+		console.error('Error loading login providers:', e);
+		const err = e as ApiError;
+		throw error(err.status || 500, err.message);
+	}
+};
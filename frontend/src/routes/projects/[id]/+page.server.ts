@@ -0,0 +1,35 @@
+import type { PageServerLoad } from './$types';
+import { createApi, ApiError } from '$lib/api/api';
+import { redirect, error } from '@sveltejs/kit';
+import type { ProjectDetailResponse } from '$lib/types/projects';
+
+export const load: PageServerLoad = async ({ fetch, depends, params, url, request }) => {
+	depends('app:project');
+
+	const range = url.searchParams.get('range') || 'month';
+	const cookieHeader = request.headers.get('cookie') || undefined;
+
+	const loadProject = async (): Promise<ProjectDetailResponse> => {
+		try {
+			const api = createApi(fetch, cookieHeader);
+			return await api.get<ProjectDetailResponse>(
+				`/page/projects/${encodeURIComponent(params.id)}?range=${range}`
+			);
+		} catch (e) {
+			console.error('Error loading project page data:', e);
+			const err = e as ApiError;
+			if (err.status === 401 || err.status === 403) {
+				throw redirect(
+					302,
+					`/?auth_error=unauthorized&redirect=${Buffer.from(url.pathname + url.search).toString('base64url')}`
+				);
+			}
+			throw error(err.status || 500, err.message);
+		}
+	};
+
+	return {
+		project: await loadProject(),
+		range
+	};
+};
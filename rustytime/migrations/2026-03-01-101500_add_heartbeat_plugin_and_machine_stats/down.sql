@@ -0,0 +1,176 @@
+-- Restore dashboard functions without the machine breakdown
+CREATE OR REPLACE FUNCTION calculate_dashboard_stats_by_range(
+    p_user_id INT,
+    p_start_time TIMESTAMPTZ,
+    p_timeout_seconds INT,
+    p_limit_count INT DEFAULT 10
+) RETURNS TABLE (
+    metric_type TEXT,
+    name TEXT,
+    total_seconds BIGINT,
+    total_time BIGINT
+) AS $$
+WITH 
+base_heartbeats AS (
+    SELECT h.time, par.resolved_project_id, h.language, h.editor, h.operating_system
+    FROM heartbeats h
+    LEFT JOIN project_alias_resolutions par 
+        ON par.user_id = h.user_id AND par.project_id = h.project_id
+    WHERE h.user_id = p_user_id
+      AND h.time >= p_start_time
+      AND h.time IS NOT NULL
+),
+total_time_calc AS (
+    SELECT CAST(COALESCE(SUM(diff), 0) AS BIGINT) as total
+    FROM (
+        SELECT CASE
+            WHEN LAG(time) OVER (ORDER BY time) IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (ORDER BY time))), p_timeout_seconds)
+        END as diff
+        FROM base_heartbeats
+    ) capped_diffs
+),
+projects AS (
+    SELECT 
+        'project' as metric_type,
+        p.name as name,
+        CAST(COALESCE(SUM(diff), 0) AS BIGINT) as total_seconds,
+        (SELECT total FROM total_time_calc) as total_time,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC) as rn
+    FROM (
+        SELECT
+            resolved_project_id,
+            CASE
+                WHEN LAG(time) OVER (PARTITION BY resolved_project_id ORDER BY time) IS NULL THEN 0
+                ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (PARTITION BY resolved_project_id ORDER BY time))), p_timeout_seconds)
+            END as diff
+        FROM base_heartbeats
+        WHERE resolved_project_id IS NOT NULL
+    ) capped_diffs
+    JOIN projects p ON p.id = capped_diffs.resolved_project_id
+    GROUP BY p.id, p.name
+    HAVING SUM(diff) > 0
+),
+editors AS (
+    SELECT 
+        'editor' as metric_type,
+        editor as name,
+        CAST(COALESCE(SUM(diff), 0) AS BIGINT) as total_seconds,
+        (SELECT total FROM total_time_calc) as total_time,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC) as rn
+    FROM (
+        SELECT
+            editor,
+            CASE
+                WHEN LAG(time) OVER (PARTITION BY editor ORDER BY time) IS NULL THEN 0
+                ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (PARTITION BY editor ORDER BY time))), p_timeout_seconds)
+            END as diff
+        FROM base_heartbeats
+        WHERE editor IS NOT NULL
+    ) capped_diffs
+    GROUP BY editor
+    HAVING SUM(diff) > 0
+),
+oses AS (
+    SELECT 
+        'operating_system' as metric_type,
+        operating_system as name,
+        CAST(COALESCE(SUM(diff), 0) AS BIGINT) as total_seconds,
+        (SELECT total FROM total_time_calc) as total_time,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC) as rn
+    FROM (
+        SELECT
+            operating_system,
+            CASE
+                WHEN LAG(time) OVER (PARTITION BY operating_system ORDER BY time) IS NULL THEN 0
+                ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (PARTITION BY operating_system ORDER BY time))), p_timeout_seconds)
+            END as diff
+        FROM base_heartbeats
+        WHERE operating_system IS NOT NULL
+    ) capped_diffs
+    GROUP BY operating_system
+    HAVING SUM(diff) > 0
+),
+languages AS (
+    SELECT 
+        'language' as metric_type,
+        language as name,
+        CAST(COALESCE(SUM(diff), 0) AS BIGINT) as total_seconds,
+        (SELECT total FROM total_time_calc) as total_time,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC) as rn
+    FROM (
+        SELECT
+            language,
+            CASE
+                WHEN LAG(time) OVER (PARTITION BY language ORDER BY time) IS NULL THEN 0
+                ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (PARTITION BY language ORDER BY time))), p_timeout_seconds)
+            END as diff
+        FROM base_heartbeats
+        WHERE language IS NOT NULL
+    ) capped_diffs
+    GROUP BY language
+    HAVING SUM(diff) > 0
+)
+SELECT 'total_time'::text, NULL::text, 0::bigint, (SELECT total FROM total_time_calc)
+UNION ALL
+SELECT metric_type, name, total_seconds, total_time FROM projects WHERE rn <= p_limit_count
+UNION ALL
+SELECT metric_type, name, total_seconds, total_time FROM editors WHERE rn <= p_limit_count
+UNION ALL
+SELECT metric_type, name, total_seconds, total_time FROM oses WHERE rn <= p_limit_count
+UNION ALL
+SELECT metric_type, name, total_seconds, total_time FROM languages WHERE rn <= p_limit_count;
+$$ LANGUAGE SQL STABLE;
+
+-- Update calculate_dashboard_stats to use resolution table
+CREATE OR REPLACE FUNCTION calculate_dashboard_stats(
+    p_user_id INT,
+    p_timeout_seconds INT,
+    p_limit INT DEFAULT 10
+) RETURNS TABLE (
+    metric_type TEXT,
+    name TEXT,
+    total_seconds BIGINT,
+    total_time BIGINT
+) AS $$
+DECLARE
+    v_total BIGINT;
+BEGIN
+    SELECT COALESCE(
+        calculate_user_duration(
+            p_user_id,
+            NULL,
+            NULL,
+            NULL,
+            NULL,
+            NULL,
+            NULL,
+            p_timeout_seconds
+        ),
+        0
+    )
+    INTO v_total;
+
+    RETURN QUERY
+    SELECT 'project'::TEXT, stats.name, stats.total_seconds, v_total
+    FROM calculate_project_stats_with_aliases(p_user_id, p_timeout_seconds, p_limit) AS stats(name, total_seconds);
+
+    RETURN QUERY
+    SELECT 'editor'::TEXT, stats.name, stats.total_seconds, v_total
+    FROM calculate_field_stats(p_user_id, 'editor', p_timeout_seconds, p_limit) AS stats(name, total_seconds);
+
+    RETURN QUERY
+    SELECT 'operating_system'::TEXT, stats.name, stats.total_seconds, v_total
+    FROM calculate_field_stats(p_user_id, 'operating_system', p_timeout_seconds, p_limit) AS stats(name, total_seconds);
+
+    RETURN QUERY
+    SELECT 'language'::TEXT, stats.name, stats.total_seconds, v_total
+    FROM calculate_field_stats(p_user_id, 'language', p_timeout_seconds, p_limit) AS stats(name, total_seconds);
+
+    RETURN QUERY
+    SELECT 'total_time'::TEXT, NULL::TEXT, v_total, v_total;
+END;
+$$ LANGUAGE plpgsql STABLE;
+
+ALTER TABLE heartbeats DROP COLUMN IF EXISTS plugin_version;
+ALTER TABLE heartbeats DROP COLUMN IF EXISTS plugin;
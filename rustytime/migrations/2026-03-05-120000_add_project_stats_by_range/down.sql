@@ -0,0 +1 @@
+DROP FUNCTION IF EXISTS calculate_project_stats_by_range(INT, INT, TIMESTAMPTZ, TEXT, INT, INT);
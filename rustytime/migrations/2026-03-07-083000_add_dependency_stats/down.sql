@@ -0,0 +1 @@
+DROP FUNCTION IF EXISTS calculate_dependency_stats_by_range(INT, TIMESTAMPTZ, INT, INT, INT);
@@ -0,0 +1,119 @@
+-- Dependency usage weighted by coded time, overall and grouped by project and language
+CREATE OR REPLACE FUNCTION calculate_dependency_stats_by_range(
+    p_user_id INT,
+    p_start_time TIMESTAMPTZ,
+    p_timeout_seconds INT,
+    p_limit_count INT DEFAULT 10,
+    p_group_limit INT DEFAULT 0
+) RETURNS TABLE (
+    scope TEXT,
+    scope_name TEXT,
+    name TEXT,
+    total_seconds BIGINT,
+    total_time BIGINT
+) AS $$
+WITH
+base_heartbeats AS (
+    SELECT
+        h.dependencies,
+        h.language,
+        par.resolved_project_id,
+        CASE
+            WHEN LAG(h.time) OVER (ORDER BY h.time) IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (h.time - LAG(h.time) OVER (ORDER BY h.time))), p_timeout_seconds)
+        END AS diff
+    FROM heartbeats h
+    LEFT JOIN project_alias_resolutions par
+        ON par.user_id = h.user_id AND par.project_id = h.project_id
+    WHERE h.user_id = p_user_id
+      AND (p_start_time IS NULL OR h.time >= p_start_time)
+      AND h.time IS NOT NULL
+),
+total_time_calc AS (
+    SELECT CAST(COALESCE(SUM(diff), 0) AS BIGINT) AS total
+    FROM base_heartbeats
+),
+-- each heartbeat's time is credited to every dependency it lists
+dependency_time AS (
+    SELECT b.diff, b.language, b.resolved_project_id, deps.dependency
+    FROM base_heartbeats b
+    CROSS JOIN LATERAL (
+        SELECT DISTINCT d AS dependency
+        FROM unnest(b.dependencies) d
+        WHERE d IS NOT NULL AND d <> ''
+    ) deps
+    WHERE b.dependencies IS NOT NULL
+      AND b.diff > 0
+),
+user_totals AS (
+    SELECT
+        1 AS scope_order,
+        'user'::TEXT AS scope,
+        NULL::TEXT AS scope_name,
+        0::BIGINT AS group_rank,
+        dependency AS name,
+        CAST(SUM(diff) AS BIGINT) AS total_seconds,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC, dependency) AS rn
+    FROM dependency_time
+    GROUP BY dependency
+),
+project_groups AS (
+    SELECT
+        resolved_project_id,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC, resolved_project_id) AS group_rank
+    FROM base_heartbeats
+    WHERE p_group_limit > 0
+      AND cardinality(dependencies) > 0
+      AND resolved_project_id IS NOT NULL
+    GROUP BY resolved_project_id
+),
+project_totals AS (
+    SELECT
+        2 AS scope_order,
+        'project'::TEXT AS scope,
+        p.name AS scope_name,
+        pg.group_rank,
+        dt.dependency AS name,
+        CAST(SUM(dt.diff) AS BIGINT) AS total_seconds,
+        ROW_NUMBER() OVER (PARTITION BY p.id ORDER BY SUM(dt.diff) DESC, dt.dependency) AS rn
+    FROM dependency_time dt
+    JOIN project_groups pg
+        ON pg.resolved_project_id = dt.resolved_project_id AND pg.group_rank <= p_group_limit
+    JOIN projects p ON p.id = dt.resolved_project_id
+    GROUP BY p.id, p.name, pg.group_rank, dt.dependency
+),
+language_groups AS (
+    SELECT
+        language,
+        ROW_NUMBER() OVER (ORDER BY SUM(diff) DESC, language) AS group_rank
+    FROM base_heartbeats
+    WHERE p_group_limit > 0
+      AND cardinality(dependencies) > 0
+      AND language IS NOT NULL
+    GROUP BY language
+),
+language_totals AS (
+    SELECT
+        3 AS scope_order,
+        'language'::TEXT AS scope,
+        dt.language AS scope_name,
+        lg.group_rank,
+        dt.dependency AS name,
+        CAST(SUM(dt.diff) AS BIGINT) AS total_seconds,
+        ROW_NUMBER() OVER (PARTITION BY dt.language ORDER BY SUM(dt.diff) DESC, dt.dependency) AS rn
+    FROM dependency_time dt
+    JOIN language_groups lg
+        ON lg.language = dt.language AND lg.group_rank <= p_group_limit
+    GROUP BY dt.language, lg.group_rank, dt.dependency
+),
+combined AS (
+    SELECT * FROM user_totals WHERE rn <= p_limit_count
+    UNION ALL
+    SELECT * FROM project_totals WHERE rn <= p_limit_count
+    UNION ALL
+    SELECT * FROM language_totals WHERE rn <= p_limit_count
+)
+SELECT c.scope, c.scope_name, c.name, c.total_seconds, (SELECT total FROM total_time_calc)
+FROM combined c
+ORDER BY c.scope_order, c.group_rank, c.rn;
+$$ LANGUAGE SQL STABLE;
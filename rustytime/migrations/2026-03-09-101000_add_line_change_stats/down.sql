@@ -0,0 +1,3 @@
+DROP FUNCTION IF EXISTS calculate_line_stats_by_range(INT, TIMESTAMPTZ, TEXT, INT, INT, INT);
+
+ALTER TABLE users DROP COLUMN IF EXISTS public_line_stats;
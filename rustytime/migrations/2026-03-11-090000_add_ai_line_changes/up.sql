@@ -0,0 +1,115 @@
+-- Store line changes reported by AI assistants and by humans
+ALTER TABLE heartbeats ADD COLUMN ai_line_changes INT;
+ALTER TABLE heartbeats ADD COLUMN human_line_changes INT;
+
+-- The return type changes, so the function has to be recreated
+DROP FUNCTION IF EXISTS calculate_line_stats_by_range(INT, TIMESTAMPTZ, TEXT, INT, INT, INT);
+
+-- Line changes split by AI and human authorship, in total and per day and project
+CREATE FUNCTION calculate_line_stats_by_range(
+    p_user_id INT,
+    p_start_time TIMESTAMPTZ,
+    p_timezone TEXT,
+    p_timeout_seconds INT,
+    p_project_id INT DEFAULT NULL,
+    p_limit_count INT DEFAULT 10
+) RETURNS TABLE (
+    metric_type TEXT,
+    name TEXT,
+    line_additions BIGINT,
+    line_deletions BIGINT,
+    files_written BIGINT,
+    write_seconds BIGINT,
+    total_seconds BIGINT,
+    ai_line_changes BIGINT,
+    human_line_changes BIGINT
+) AS $$
+WITH
+base_heartbeats AS (
+    SELECT
+        h.time,
+        h.entity,
+        h.is_write,
+        h.line_additions,
+        h.line_deletions,
+        h.ai_line_changes,
+        h.human_line_changes,
+        par.resolved_project_id
+    FROM heartbeats h
+    LEFT JOIN project_alias_resolutions par
+        ON par.user_id = h.user_id AND par.project_id = h.project_id
+    WHERE h.user_id = p_user_id
+      AND (p_start_time IS NULL OR h.time >= p_start_time)
+      AND (p_project_id IS NULL OR par.resolved_project_id = p_project_id)
+      AND h.time IS NOT NULL
+),
+capped_diffs AS (
+    SELECT
+        entity,
+        COALESCE(is_write, false) AS is_write,
+        COALESCE(line_additions, 0) AS line_additions,
+        COALESCE(line_deletions, 0) AS line_deletions,
+        COALESCE(ai_line_changes, 0) AS ai_line_changes,
+        COALESCE(human_line_changes, 0) AS human_line_changes,
+        resolved_project_id,
+        to_char(time AT TIME ZONE p_timezone, 'YYYY-MM-DD') AS local_day,
+        CASE
+            WHEN LAG(time) OVER (ORDER BY time) IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (ORDER BY time))), p_timeout_seconds)
+        END AS diff
+    FROM base_heartbeats
+),
+totals AS (
+    SELECT
+        'total'::TEXT AS metric_type,
+        NULL::TEXT AS name,
+        COALESCE(SUM(line_additions), 0)::BIGINT AS line_additions,
+        COALESCE(SUM(line_deletions), 0)::BIGINT AS line_deletions,
+        COUNT(DISTINCT entity) FILTER (WHERE is_write)::BIGINT AS files_written,
+        COALESCE(SUM(diff) FILTER (WHERE is_write), 0)::BIGINT AS write_seconds,
+        COALESCE(SUM(diff), 0)::BIGINT AS total_seconds,
+        COALESCE(SUM(ai_line_changes), 0)::BIGINT AS ai_line_changes,
+        COALESCE(SUM(human_line_changes), 0)::BIGINT AS human_line_changes
+    FROM capped_diffs
+),
+days AS (
+    SELECT
+        'day'::TEXT AS metric_type,
+        local_day AS name,
+        COALESCE(SUM(line_additions), 0)::BIGINT AS line_additions,
+        COALESCE(SUM(line_deletions), 0)::BIGINT AS line_deletions,
+        COUNT(DISTINCT entity) FILTER (WHERE is_write)::BIGINT AS files_written,
+        COALESCE(SUM(diff) FILTER (WHERE is_write), 0)::BIGINT AS write_seconds,
+        COALESCE(SUM(diff), 0)::BIGINT AS total_seconds,
+        COALESCE(SUM(ai_line_changes), 0)::BIGINT AS ai_line_changes,
+        COALESCE(SUM(human_line_changes), 0)::BIGINT AS human_line_changes
+    FROM capped_diffs
+    GROUP BY local_day
+),
+project_totals AS (
+    SELECT
+        'project'::TEXT AS metric_type,
+        p.name AS name,
+        COALESCE(SUM(cd.line_additions), 0)::BIGINT AS line_additions,
+        COALESCE(SUM(cd.line_deletions), 0)::BIGINT AS line_deletions,
+        COUNT(DISTINCT cd.entity) FILTER (WHERE cd.is_write)::BIGINT AS files_written,
+        COALESCE(SUM(cd.diff) FILTER (WHERE cd.is_write), 0)::BIGINT AS write_seconds,
+        COALESCE(SUM(cd.diff), 0)::BIGINT AS total_seconds,
+        COALESCE(SUM(cd.ai_line_changes), 0)::BIGINT AS ai_line_changes,
+        COALESCE(SUM(cd.human_line_changes), 0)::BIGINT AS human_line_changes,
+        ROW_NUMBER() OVER (
+            ORDER BY SUM(cd.line_additions + cd.line_deletions) DESC, SUM(cd.diff) DESC, p.name
+        ) AS rn
+    FROM capped_diffs cd
+    JOIN projects p ON p.id = cd.resolved_project_id
+    WHERE p_project_id IS NULL
+    GROUP BY p.id, p.name
+)
+SELECT * FROM totals
+UNION ALL
+SELECT * FROM (SELECT * FROM days ORDER BY name) ordered_days
+UNION ALL
+SELECT metric_type, name, line_additions, line_deletions, files_written, write_seconds, total_seconds,
+    ai_line_changes, human_line_changes
+FROM (SELECT * FROM project_totals WHERE rn <= p_limit_count ORDER BY rn) ordered_projects;
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,13 @@
+-- Per-user rules mapping project names or entity paths onto a target project
+CREATE TABLE project_rules (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    match_field VARCHAR(20) NOT NULL, -- 'project', 'entity'
+    pattern TEXT NOT NULL,
+    target_project TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Rules are always loaded per user in evaluation order
+CREATE INDEX idx_project_rules_user ON project_rules(user_id, priority, id);
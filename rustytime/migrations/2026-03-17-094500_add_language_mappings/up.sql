@@ -0,0 +1,10 @@
+-- Per-user overrides for the language of a heartbeat
+CREATE TABLE language_mappings (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL, -- 'extension', 'language'
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, kind, source)
+);
@@ -0,0 +1,13 @@
+-- Per-user coding goals of N seconds per day or week
+CREATE TABLE goals (
+    id SERIAL PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    period VARCHAR(10) NOT NULL, -- 'day', 'week'
+    target_seconds INTEGER NOT NULL CHECK (target_seconds > 0),
+    project TEXT,
+    language TEXT,
+    ignored_weekdays SMALLINT NOT NULL DEFAULT 0, -- bit 0 is monday
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_goals_user ON goals(user_id, id);
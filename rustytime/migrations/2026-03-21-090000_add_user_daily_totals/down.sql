@@ -0,0 +1,2 @@
+DROP FUNCTION IF EXISTS refresh_user_daily_totals(INT, TEXT, DATE, DATE, INT);
+DROP TABLE IF EXISTS user_daily_totals;
@@ -0,0 +1,47 @@
+-- Per-day coding totals in each user's timezone, backing profile heatmaps and streaks
+CREATE TABLE user_daily_totals (
+    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    total_seconds BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, day)
+);
+
+-- Recompute the totals of every local day between p_start_day and p_end_day (inclusive),
+-- storing a row for days without activity too so the latest stored day marks how far the
+-- rollup is up to date
+CREATE OR REPLACE FUNCTION refresh_user_daily_totals(
+    p_user_id INT,
+    p_timezone TEXT,
+    p_start_day DATE,
+    p_end_day DATE,
+    p_timeout_seconds INT
+) RETURNS VOID AS $$
+    INSERT INTO user_daily_totals (user_id, day, total_seconds, updated_at)
+    SELECT
+        p_user_id,
+        days.day::date,
+        COALESCE(totals.total_seconds, 0),
+        NOW()
+    FROM generate_series(p_start_day, p_end_day, INTERVAL '1 day') AS days(day)
+    LEFT JOIN (
+        SELECT local_day, SUM(diff)::bigint AS total_seconds
+        FROM (
+            SELECT
+                (time AT TIME ZONE p_timezone)::date AS local_day,
+                CASE
+                    WHEN LAG(time) OVER w IS NULL THEN 0
+                    ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER w)), p_timeout_seconds)
+                END AS diff
+            FROM heartbeats
+            WHERE user_id = p_user_id
+              AND time >= (p_start_day::timestamp AT TIME ZONE p_timezone)
+              AND time < ((p_end_day + 1)::timestamp AT TIME ZONE p_timezone)
+            WINDOW w AS (PARTITION BY (time AT TIME ZONE p_timezone)::date ORDER BY time)
+        ) capped_diffs
+        GROUP BY local_day
+    ) totals ON totals.local_day = days.day::date
+    ON CONFLICT (user_id, day) DO UPDATE
+    SET total_seconds = EXCLUDED.total_seconds,
+        updated_at = EXCLUDED.updated_at;
+$$ LANGUAGE SQL;
@@ -0,0 +1,2 @@
+-- Let users hide their public profile and badges
+ALTER TABLE users ADD COLUMN private_profile BOOLEAN NOT NULL DEFAULT false;
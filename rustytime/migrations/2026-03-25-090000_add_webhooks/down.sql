@@ -0,0 +1,3 @@
+ALTER TABLE goals DROP COLUMN IF EXISTS last_reached_on;
+DROP TABLE IF EXISTS webhook_deliveries;
+DROP TABLE IF EXISTS webhooks;
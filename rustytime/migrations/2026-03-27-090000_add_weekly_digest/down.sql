@@ -0,0 +1,5 @@
+DROP FUNCTION IF EXISTS top_languages_by_range(INT, INT, TIMESTAMPTZ, TIMESTAMPTZ, INT);
+
+ALTER TABLE users DROP COLUMN IF EXISTS last_digest_sent_on;
+ALTER TABLE users DROP COLUMN IF EXISTS weekly_digest;
+ALTER TABLE users DROP COLUMN IF EXISTS email;
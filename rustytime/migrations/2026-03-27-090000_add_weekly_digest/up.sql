@@ -0,0 +1,48 @@
+-- Email address from the OAuth provider, used for the opt-in weekly digest
+ALTER TABLE users ADD COLUMN email VARCHAR(255);
+ALTER TABLE users ADD COLUMN weekly_digest BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE users ADD COLUMN last_digest_sent_on DATE;
+
+CREATE OR REPLACE FUNCTION top_languages_by_range(
+    p_user_id INT,
+    p_timeout_seconds INT,
+    p_start_time TIMESTAMPTZ,
+    p_end_time TIMESTAMPTZ,
+    p_limit INT
+) RETURNS TABLE (
+    name TEXT,
+    total_seconds BIGINT
+) AS $$
+    SELECT
+        language AS name,
+        SUM(
+            CASE
+                WHEN prev_time IS NULL THEN 0
+                ELSE LEAST(EXTRACT(EPOCH FROM (time - prev_time)), p_timeout_seconds)
+            END
+        )::bigint AS total_seconds
+    FROM (
+        SELECT
+            h.time,
+            h.language,
+            LAG(h.time) OVER (
+                PARTITION BY h.language
+                ORDER BY h.time
+            ) AS prev_time
+        FROM heartbeats h
+        WHERE h.user_id = p_user_id
+          AND h.language IS NOT NULL
+          AND h.language <> ''
+          AND h.time >= p_start_time
+          AND h.time < p_end_time
+    ) languages_with_lag
+    GROUP BY language
+    HAVING SUM(
+        CASE
+            WHEN prev_time IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (time - prev_time)), p_timeout_seconds)
+        END
+    ) > 0
+    ORDER BY total_seconds DESC, name ASC
+    LIMIT p_limit;
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,10 @@
+ALTER TABLE sessions ADD COLUMN github_user_id BIGINT NOT NULL DEFAULT 0;
+UPDATE sessions s SET github_user_id = COALESCE(u.github_id, 0) FROM users u WHERE u.id = s.user_id;
+ALTER TABLE sessions ALTER COLUMN github_user_id DROP DEFAULT;
+CREATE INDEX idx_sessions_github_user_id ON sessions(github_user_id);
+
+-- Users without a GitHub account cannot be represented anymore
+DELETE FROM users WHERE github_id IS NULL;
+ALTER TABLE users ALTER COLUMN github_id SET NOT NULL;
+
+DROP TABLE IF EXISTS user_identities;
@@ -0,0 +1,28 @@
+-- Accounts from login providers, several of which can be linked to one user
+CREATE TABLE user_identities (
+    id          SERIAL       PRIMARY KEY,
+    user_id     INTEGER      NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    provider    VARCHAR(50)  NOT NULL,
+    subject     VARCHAR(255) NOT NULL,
+    username    VARCHAR(100) NOT NULL,
+    email       VARCHAR(255),
+    created_at  TIMESTAMPTZ  NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ  NOT NULL DEFAULT now(),
+    CONSTRAINT user_identities_provider_subject_key UNIQUE (provider, subject)
+);
+
+CREATE INDEX idx_user_identities_user_id ON user_identities(user_id);
+
+SELECT diesel_manage_updated_at('user_identities');
+
+-- Every existing account signed in with GitHub
+INSERT INTO user_identities (user_id, provider, subject, username, email, created_at)
+SELECT id, 'github', github_id::text, name, email, created_at
+FROM users
+WHERE github_id > 0;
+
+-- Users from other providers have no GitHub id
+ALTER TABLE users ALTER COLUMN github_id DROP NOT NULL;
+
+DROP INDEX IF EXISTS idx_sessions_github_user_id;
+ALTER TABLE sessions DROP COLUMN github_user_id;
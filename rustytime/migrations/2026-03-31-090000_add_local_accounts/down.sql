@@ -0,0 +1,2 @@
+DELETE FROM user_identities WHERE provider = 'local';
+ALTER TABLE user_identities DROP COLUMN password_hash;
@@ -0,0 +1,2 @@
+-- Argon2 hash of the password for local accounts, identities with provider 'local'
+ALTER TABLE user_identities ADD COLUMN password_hash VARCHAR(255);
@@ -0,0 +1,2 @@
+ALTER TABLE sessions ADD COLUMN github_access_token TEXT NOT NULL DEFAULT '';
+ALTER TABLE sessions ALTER COLUMN github_access_token DROP DEFAULT;
@@ -0,0 +1,2 @@
+-- provider access tokens are only needed during the login callback, never keep them
+ALTER TABLE sessions DROP COLUMN github_access_token;
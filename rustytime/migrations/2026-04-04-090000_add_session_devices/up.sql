@@ -0,0 +1,6 @@
+ALTER TABLE sessions
+    ADD COLUMN device       VARCHAR(255),
+    ADD COLUMN ip_address   VARCHAR(45),
+    ADD COLUMN last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+UPDATE sessions SET last_seen_at = updated_at;
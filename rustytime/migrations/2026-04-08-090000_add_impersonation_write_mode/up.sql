@@ -0,0 +1,2 @@
+-- Impersonation is read-only unless an owner explicitly asks for write access
+ALTER TABLE sessions ADD COLUMN impersonation_write BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,7 @@
+ALTER TABLE users ADD COLUMN admin_level SMALLINT NOT NULL DEFAULT 0;
+
+UPDATE users SET admin_level = roles.rank FROM roles WHERE roles.name = users.role;
+
+DROP INDEX IF EXISTS idx_users_role;
+ALTER TABLE users DROP COLUMN role;
+DROP TABLE roles;
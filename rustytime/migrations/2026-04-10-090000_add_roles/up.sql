@@ -0,0 +1,27 @@
+-- Named roles with permission sets, replacing users.admin_level
+CREATE TABLE roles (
+    name        VARCHAR(50)  PRIMARY KEY,
+    -- a role can only manage users and roles ranked below it
+    rank        SMALLINT     NOT NULL DEFAULT 0,
+    permissions TEXT[]       NOT NULL DEFAULT '{}',
+    created_at  TIMESTAMPTZ  NOT NULL DEFAULT now()
+);
+
+INSERT INTO roles (name, rank, permissions) VALUES
+    ('user', 0, '{}'),
+    ('admin', 1, '{view_users,impersonate,ban}'),
+    ('owner', 2, '{view_users,impersonate,impersonate_write,ban,manage_imports,view_api_keys,manage_roles,view_audit_log,global_webhooks}');
+
+ALTER TABLE users
+    ADD COLUMN role VARCHAR(50) NOT NULL DEFAULT 'user'
+        REFERENCES roles(name) ON UPDATE CASCADE;
+
+UPDATE users SET role = CASE
+    WHEN admin_level >= 2 THEN 'owner'
+    WHEN admin_level = 1 THEN 'admin'
+    ELSE 'user'
+END;
+
+CREATE INDEX idx_users_role ON users(role);
+
+ALTER TABLE users DROP COLUMN admin_level;
@@ -0,0 +1,84 @@
+-- Hidden entities are stored as empty strings, leave them out of the entity breakdown
+CREATE OR REPLACE FUNCTION calculate_project_stats_by_range(
+    p_user_id INT,
+    p_project_id INT,
+    p_start_time TIMESTAMPTZ,
+    p_timezone TEXT,
+    p_timeout_seconds INT,
+    p_limit_count INT DEFAULT 10
+) RETURNS TABLE (
+    metric_type TEXT,
+    name TEXT,
+    total_seconds BIGINT,
+    total_time BIGINT
+) AS $$
+WITH
+base_heartbeats AS (
+    SELECT h.time, h.branch, h.entity, h.language, h.editor, h.category
+    FROM heartbeats h
+    JOIN project_alias_resolutions par
+        ON par.user_id = h.user_id AND par.project_id = h.project_id
+    WHERE h.user_id = p_user_id
+      AND par.resolved_project_id = p_project_id
+      AND (p_start_time IS NULL OR h.time >= p_start_time)
+      AND h.time IS NOT NULL
+),
+capped_diffs AS (
+    SELECT
+        branch,
+        entity,
+        language,
+        editor,
+        category,
+        time AT TIME ZONE p_timezone AS local_time,
+        CASE
+            WHEN LAG(time) OVER (ORDER BY time) IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER (ORDER BY time))), p_timeout_seconds)
+        END AS diff
+    FROM base_heartbeats
+),
+total_time_calc AS (
+    SELECT CAST(COALESCE(SUM(diff), 0) AS BIGINT) AS total
+    FROM capped_diffs
+),
+buckets AS (
+    SELECT 'day' AS metric_type, to_char(local_time, 'YYYY-MM-DD') AS name, diff FROM capped_diffs
+    UNION ALL
+    SELECT 'weekday', (EXTRACT(ISODOW FROM local_time)::INT - 1)::TEXT, diff FROM capped_diffs
+    UNION ALL
+    SELECT 'hour', EXTRACT(HOUR FROM local_time)::INT::TEXT, diff FROM capped_diffs
+),
+bucket_totals AS (
+    SELECT metric_type, name, CAST(SUM(diff) AS BIGINT) AS total_seconds
+    FROM buckets
+    GROUP BY metric_type, name
+    HAVING SUM(diff) > 0
+),
+-- each gap is credited to the heartbeat that closes it so breakdowns add up to the total
+dimensions AS (
+    SELECT 'branch' AS metric_type, branch AS name, diff FROM capped_diffs WHERE branch IS NOT NULL
+    UNION ALL
+    SELECT 'entity', entity, diff FROM capped_diffs WHERE entity <> ''
+    UNION ALL
+    SELECT 'language', language, diff FROM capped_diffs WHERE language IS NOT NULL
+    UNION ALL
+    SELECT 'editor', editor, diff FROM capped_diffs WHERE editor IS NOT NULL
+    UNION ALL
+    SELECT 'category', category, diff FROM capped_diffs WHERE category IS NOT NULL
+),
+dimension_totals AS (
+    SELECT
+        metric_type,
+        name,
+        CAST(SUM(diff) AS BIGINT) AS total_seconds,
+        ROW_NUMBER() OVER (PARTITION BY metric_type ORDER BY SUM(diff) DESC, name) AS rn
+    FROM dimensions
+    GROUP BY metric_type, name
+    HAVING SUM(diff) > 0
+)
+SELECT 'total_time'::TEXT, NULL::TEXT, 0::BIGINT, (SELECT total FROM total_time_calc)
+UNION ALL
+SELECT metric_type, name, total_seconds, (SELECT total FROM total_time_calc) FROM bucket_totals
+UNION ALL
+SELECT metric_type, name, total_seconds, (SELECT total FROM total_time_calc) FROM dimension_totals WHERE rn <= p_limit_count;
+$$ LANGUAGE SQL STABLE;
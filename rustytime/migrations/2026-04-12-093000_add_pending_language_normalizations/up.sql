@@ -0,0 +1,8 @@
+-- Users whose stored languages still have to be normalized by the history worker
+CREATE TABLE pending_language_normalizations (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO pending_language_normalizations (user_id)
+SELECT id FROM users;
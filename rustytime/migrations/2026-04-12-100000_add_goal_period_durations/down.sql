@@ -0,0 +1 @@
+DROP FUNCTION IF EXISTS calculate_goal_period_durations(INT, DATE[], TIMESTAMPTZ, TIMESTAMPTZ, TEXT, TEXT, TEXT, INT);
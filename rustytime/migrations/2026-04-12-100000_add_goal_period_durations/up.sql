@@ -0,0 +1,55 @@
+-- Coded time per goal period, each heartbeat belongs to the latest period start on or before its local day
+CREATE OR REPLACE FUNCTION calculate_goal_period_durations(
+    p_user_id INT,
+    p_period_starts DATE[],
+    p_start_date TIMESTAMPTZ,
+    p_end_date TIMESTAMPTZ,
+    p_timezone TEXT,
+    p_project TEXT,
+    p_language TEXT,
+    p_timeout_seconds INT
+) RETURNS TABLE (
+    period_start DATE,
+    total_seconds BIGINT
+) AS $$
+WITH
+target_project AS (
+    SELECT par.resolved_project_id
+    FROM projects p
+    JOIN project_alias_resolutions par ON par.user_id = p.user_id AND par.project_id = p.id
+    WHERE p.user_id = p_user_id AND p.name = p_project
+    LIMIT 1
+),
+bucketed AS (
+    SELECT
+        h.time,
+        (
+            SELECT MAX(s)
+            FROM unnest(p_period_starts) s
+            WHERE s <= (h.time AT TIME ZONE p_timezone)::DATE
+        ) AS period_start
+    FROM heartbeats h
+    LEFT JOIN project_alias_resolutions par
+        ON par.user_id = h.user_id AND par.project_id = h.project_id
+    WHERE h.user_id = p_user_id
+      AND h.time >= p_start_date
+      AND h.time <= p_end_date
+      AND (p_language IS NULL OR h.language = p_language)
+      AND (p_project IS NULL OR par.resolved_project_id = (SELECT resolved_project_id FROM target_project))
+),
+-- gaps are only counted within a period, the same as a duration query per period
+capped_diffs AS (
+    SELECT
+        period_start,
+        CASE
+            WHEN LAG(time) OVER w IS NULL THEN 0
+            ELSE LEAST(EXTRACT(EPOCH FROM (time - LAG(time) OVER w)), p_timeout_seconds)
+        END AS diff
+    FROM bucketed
+    WHERE period_start IS NOT NULL
+    WINDOW w AS (PARTITION BY period_start ORDER BY time)
+)
+SELECT period_start, CAST(COALESCE(SUM(diff), 0) AS BIGINT)
+FROM capped_diffs
+GROUP BY period_start;
+$$ LANGUAGE SQL STABLE;
@@ -0,0 +1,5 @@
+-- Rank last sent in a leaderboard.rank_changed webhook, so swaps between two
+-- notification runs don't produce a delivery each
+ALTER TABLE leaderboards ADD COLUMN notified_rank INT4;
+UPDATE leaderboards SET notified_rank = rank;
+ALTER TABLE leaderboards ALTER COLUMN notified_rank SET NOT NULL;
@@ -3,7 +3,8 @@
 use crate::db::connection::DbPool;
 use crate::handlers::api::user::store_heartbeats_in_db_count_only;
 use crate::models::heartbeat::{NewHeartbeat, SourceType};
-use crate::models::user::User;
+use crate::models::role::Role;
+use crate::models::user::{NewUser, User};
 use chrono::Utc;
 use ipnetwork::{IpNetwork, Ipv4Network};
 use rand::prelude::IndexedRandom;
@@ -43,11 +44,17 @@ pub async fn seed_database(pool: &DbPool) -> Result<(), Box<dyn std::error::Erro
             return Ok(());
         }
 
-        User::create_or_update(
+        User::create(
             &mut conn,
-            -1,
-            "Test User",
-            "https://avatars.githubusercontent.com/u/999999",
+            &NewUser {
+                github_id: Some(-1),
+                name: "Test User".to_string(),
+                avatar_url: "https://avatars.githubusercontent.com/u/999999".to_string(),
+                role: Role::USER.to_string(),
+                is_banned: false,
+                timezone: "UTC".to_string(),
+                email: None,
+            },
         )?
     };
 
@@ -155,5 +162,9 @@ fn generate_random_heartbeat<R: Rng>(
         cursorpos: Some(rng.random_range(0..500)),
         source_type: Some(SourceType::Seeding as i16),
         project_id: None,
+        plugin: Some("vscode-wakatime".to_string()),
+        plugin_version: Some("25.0.3".to_string()),
+        ai_line_changes: None,
+        human_line_changes: None,
     }
 }
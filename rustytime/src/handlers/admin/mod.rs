@@ -1,25 +1,30 @@
 use aide::NoApi;
+use axum::Extension;
 use axum::extract::Path;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 
 use crate::db_transaction;
+use crate::models::audit_log::{AuditAction, AuditLogEntry, NewAuditLogEntry};
+use crate::models::role::{Permission, Role};
 use crate::models::user::User;
 use crate::tx_bail;
-use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use crate::utils::extractors::{AuthenticatedUser, CurrentRole, DbConnection};
+use crate::utils::session::ImpersonationContext;
 use crate::utils::transaction::{TxOptionExt, TxResultExt};
 
-pub async fn change_user_admin_level(
-    Path((user_id, new_level)): Path<(i32, i16)>,
+pub async fn change_user_role(
+    Path((user_id, role_name)): Path<(i32, String)>,
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
 ) -> Result<StatusCode, Response> {
-    if !current_user.is_owner() {
-        return Err((StatusCode::FORBIDDEN, "No permission").into_response());
-    }
+    // the admin behind an impersonated session is the one acting
+    let actor_id = impersonation.map_or(current_user.id, |Extension(ctx)| ctx.admin.id);
 
-    if new_level < 0 {
-        return Err((StatusCode::BAD_REQUEST, "Invalid admin level").into_response());
+    if !current_role.has(Permission::ManageRoles) {
+        return Err((StatusCode::FORBIDDEN, "No permission").into_response());
     }
 
     db_transaction!(conn, |conn| {
@@ -27,22 +32,92 @@ pub async fn change_user_admin_level(
             .db_err("Failed to fetch target user")?
             .or_not_found("User not found")?;
 
-        if target_user.id == current_user.id {
-            tx_bail!(StatusCode::BAD_REQUEST, "Cannot change own admin level");
+        if target_user.id == actor_id {
+            tx_bail!(StatusCode::BAD_REQUEST, "Cannot change own role");
         }
 
-        if target_user.is_owner() {
-            tx_bail!(StatusCode::BAD_REQUEST, "Cannot change owner admin level");
+        let target_role = target_user
+            .role(conn)
+            .db_err("Failed to fetch target role")?;
+        if !current_role.outranks(&target_role) {
+            tx_bail!(
+                StatusCode::BAD_REQUEST,
+                "Cannot change role of equal or higher ranked user"
+            );
         }
 
-        if target_user.admin_level >= current_user.admin_level {
+        let new_role = Role::find(conn, &role_name)
+            .db_err("Failed to fetch role")?
+            .or_not_found("Role not found")?;
+        if !current_role.outranks(&new_role) {
+            tx_bail!(
+                StatusCode::BAD_REQUEST,
+                "Cannot assign a role of equal or higher rank"
+            );
+        }
+
+        User::set_role(conn, user_id, &new_role.name).db_err("Failed to update role")?;
+        AuditLogEntry::record(
+            conn,
+            &NewAuditLogEntry::new(actor_id, current_user.id, AuditAction::RoleChange)
+                .target_user(user_id)
+                .metadata(serde_json::json!({
+                    "from": target_user.role,
+                    "to": new_role.name,
+                })),
+        )
+        .db_err("Failed to write audit log")?;
+
+        Ok(())
+    });
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn set_user_banned(
+    Path((user_id, banned)): Path<(i32, bool)>,
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<StatusCode, Response> {
+    // the admin behind an impersonated session is the one acting
+    let actor_id = impersonation.map_or(current_user.id, |Extension(ctx)| ctx.admin.id);
+
+    if !current_role.has(Permission::Ban) {
+        return Err((StatusCode::FORBIDDEN, "No permission").into_response());
+    }
+
+    db_transaction!(conn, |conn| {
+        let target_user = User::get_by_id(conn, user_id)
+            .db_err("Failed to fetch target user")?
+            .or_not_found("User not found")?;
+
+        let target_role = target_user
+            .role(conn)
+            .db_err("Failed to fetch target role")?;
+        if !current_role.outranks(&target_role) {
             tx_bail!(
                 StatusCode::BAD_REQUEST,
-                "Cannot change admin level of equal or higher admin"
+                "Cannot ban equal or higher ranked user"
             );
         }
 
-        User::set_admin_level(conn, user_id, new_level).db_err("Failed to update admin level")?;
+        User::set_banned(conn, user_id, banned).db_err("Failed to update ban")?;
+        AuditLogEntry::record(
+            conn,
+            &NewAuditLogEntry::new(
+                actor_id,
+                current_user.id,
+                if banned {
+                    AuditAction::UserBan
+                } else {
+                    AuditAction::UserUnban
+                },
+            )
+            .target_user(user_id),
+        )
+        .db_err("Failed to write audit log")?;
 
         Ok(())
     });
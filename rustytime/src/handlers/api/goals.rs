@@ -0,0 +1,166 @@
+use aide::NoApi;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::db_query;
+use crate::handlers::data::goals::cached_goal_progress;
+use crate::models::goal::{GoalPeriodProgress, GoalProgress, RangeStatus};
+use crate::state::AppState;
+use crate::utils::auth::{get_user_from_api_key, get_valid_api_key};
+use crate::utils::extractors::DbConnection;
+use crate::utils::time::{
+    TimeFormat, get_day_end_utc, get_day_start_utc, human_readable_duration, parse_timezone,
+};
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalsResponse {
+    data: Vec<GoalData>,
+    total: usize,
+    total_pages: u32,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalData {
+    id: String,
+    title: String,
+    status: RangeStatus,
+    is_enabled: bool,
+    delta: String,
+    seconds: i64,
+    languages: Vec<String>,
+    projects: Vec<String>,
+    ignore_days: Vec<String>,
+    status_percent_calculated: f64,
+    chart_data: Vec<GoalChartData>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalChartData {
+    range: GoalChartRange,
+    actual_seconds: i64,
+    actual_seconds_text: String,
+    goal_seconds: i64,
+    goal_seconds_text: String,
+    range_status: RangeStatus,
+    range_status_reason: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalChartRange {
+    start: String,
+    end: String,
+    date: String,
+    text: String,
+    timezone: String,
+}
+
+fn range_status_reason(period: &GoalPeriodProgress) -> String {
+    let actual = human_readable_duration(period.actual_seconds, TimeFormat::HourMinute);
+    let goal = human_readable_duration(period.goal_seconds, TimeFormat::HourMinute);
+    match period.status {
+        RangeStatus::Success => format!(
+            "Coded {} which meets the goal of {}",
+            actual.human_readable, goal.human_readable
+        ),
+        RangeStatus::Fail => format!(
+            "Coded {} which is less than the goal of {}",
+            actual.human_readable, goal.human_readable
+        ),
+        RangeStatus::Ignored => "This day is ignored by the goal".to_string(),
+        RangeStatus::Pending => format!(
+            "Coded {} so far out of {}",
+            actual.human_readable, goal.human_readable
+        ),
+    }
+}
+
+fn to_goal_data(goal: GoalProgress, timezone: &str) -> GoalData {
+    let tz = parse_timezone(timezone);
+
+    let chart_data = goal
+        .history
+        .iter()
+        .map(|period| GoalChartData {
+            range: GoalChartRange {
+                start: get_day_start_utc(period.start, tz).to_rfc3339(),
+                end: get_day_end_utc(period.end, tz).to_rfc3339(),
+                date: period.start.format("%Y-%m-%d").to_string(),
+                text: if period.start == period.end {
+                    period.start.format("%a %b %-d").to_string()
+                } else {
+                    format!(
+                        "{} until {}",
+                        period.start.format("%b %-d"),
+                        period.end.format("%b %-d")
+                    )
+                },
+                timezone: timezone.to_string(),
+            },
+            actual_seconds: period.actual_seconds,
+            actual_seconds_text: human_readable_duration(
+                period.actual_seconds,
+                TimeFormat::HourMinute,
+            )
+            .human_readable,
+            goal_seconds: period.goal_seconds,
+            goal_seconds_text: human_readable_duration(period.goal_seconds, TimeFormat::HourMinute)
+                .human_readable,
+            range_status: period.status,
+            range_status_reason: range_status_reason(period),
+        })
+        .collect();
+
+    GoalData {
+        id: goal.id.to_string(),
+        title: goal.title,
+        status: goal.status,
+        is_enabled: true,
+        delta: goal.period.as_str().to_string(),
+        seconds: goal.target_seconds,
+        languages: goal.language.into_iter().collect(),
+        projects: goal.project.into_iter().collect(),
+        ignore_days: goal.ignored_weekdays,
+        status_percent_calculated: goal.percent,
+        chart_data,
+    }
+}
+
+pub async fn get_goals(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    uri: Uri,
+) -> Result<Json<GoalsResponse>, Response> {
+    if id != "current" {
+        return Err((StatusCode::BAD_REQUEST, "Bad request").into_response());
+    }
+
+    let Some(api_key) = get_valid_api_key(&headers, &uri).await else {
+        return Err((StatusCode::BAD_REQUEST, "Bad request").into_response());
+    };
+
+    let Some(user) = get_user_from_api_key(&app_state.db_pool, &api_key).await else {
+        return Err((StatusCode::BAD_REQUEST, "Bad request").into_response());
+    };
+
+    let goals = db_query!(
+        cached_goal_progress(&app_state, &mut conn, &user),
+        "Failed to fetch goals"
+    );
+
+    let data: Vec<GoalData> = goals
+        .into_iter()
+        .map(|goal| to_goal_data(goal, &user.timezone))
+        .collect();
+
+    Ok(Json(GoalsResponse {
+        total: data.len(),
+        total_pages: 1,
+        data,
+    }))
+}
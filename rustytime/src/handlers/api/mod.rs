@@ -1 +1,2 @@
+pub mod goals;
 pub mod user;
@@ -15,6 +15,7 @@ use std::net::SocketAddr;
 
 use crate::db::connection::DbPool;
 use crate::db_transaction_result;
+use crate::models::daily_total::DailyTotalRow;
 use crate::models::heartbeat::Heartbeat;
 use crate::models::heartbeat::*;
 use crate::models::project::get_or_create_project_id;
@@ -62,6 +63,23 @@ pub struct StatusBarResponseDataRange {
     timezone: String,
 }
 
+/// Get the user's ingest settings, loading them into the cache if needed
+fn get_ingest_settings(app_state: &AppState, user_id: i32) -> Result<IngestSettings, String> {
+    if let Some(cached) = app_state.cache.ingest_settings.get(&user_id) {
+        return Ok(cached);
+    }
+
+    let mut conn = app_state.db_pool.get().map_err(|e| e.to_string())?;
+    let settings = IngestSettings::load(&mut conn, user_id).map_err(|e| e.to_string())?;
+
+    app_state
+        .cache
+        .ingest_settings
+        .insert(user_id, settings.clone());
+
+    Ok(settings)
+}
+
 /// Process heartbeat request and store in the database
 async fn process_heartbeat_request(
     app_state: &AppState,
@@ -92,6 +110,10 @@ async fn process_heartbeat_request(
         None => return Err((StatusCode::UNAUTHORIZED, "Unauthorized").into_response()),
     };
 
+    let settings = get_ingest_settings(app_state, user_id).map_err(|e| {
+        eprintln!("❌ Error loading ingest settings: {}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response()
+    })?;
     let ip_network = IpNetwork::from(client_ip);
 
     if heartbeat_requests.len() == 1 {
@@ -100,6 +122,7 @@ async fn process_heartbeat_request(
             user_id,
             ip_network,
             &headers,
+            &settings,
         );
 
         match store_heartbeats_in_db(&app_state.db_pool, vec![new_heartbeat]).await {
@@ -122,7 +145,7 @@ async fn process_heartbeat_request(
     } else {
         let new_heartbeats: Vec<NewHeartbeat> = heartbeat_requests
             .into_iter()
-            .map(|req| NewHeartbeat::from_request(req, user_id, ip_network, &headers))
+            .map(|req| NewHeartbeat::from_request(req, user_id, ip_network, &headers, &settings))
             .collect();
 
         match store_heartbeats_in_db(&app_state.db_pool, new_heartbeats).await {
@@ -324,6 +347,19 @@ async fn store_heartbeats_in_db_internal(
                 }
             }
 
+            // heartbeats synced late can land on days the activity rollup already covers
+            let late_cutoff = Utc::now() - chrono::Duration::days(1);
+            let mut earliest_late: HashMap<i32, chrono::DateTime<Utc>> = HashMap::new();
+            for hb in new_heartbeats.iter().filter(|hb| hb.time < late_cutoff) {
+                earliest_late
+                    .entry(hb.user_id)
+                    .and_modify(|time| *time = (*time).min(hb.time))
+                    .or_insert(hb.time);
+            }
+            for (user_id, since) in earliest_late {
+                DailyTotalRow::invalidate_since(conn, user_id, since)?;
+            }
+
             let responses = if include_responses {
                 keys.iter()
                     .map(|key| HeartbeatResponse::from(inserted_map[key].clone()))
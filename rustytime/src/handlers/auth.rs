@@ -0,0 +1,476 @@
+use aide::NoApi;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::env;
+use tower_cookies::Cookies;
+
+use crate::db_query;
+use crate::db_transaction;
+use crate::models::role::{Permission, Role};
+use crate::models::session::{NewSession, Session};
+use crate::models::user_identity::{SignInOutcome, UserIdentity};
+use crate::state::AppState;
+use crate::tx_bail;
+use crate::utils::extractors::{ClientInfo, DbConnection};
+use crate::utils::local_auth::{
+    hash_password, validate_password, validate_username, verify_password,
+};
+use crate::utils::oauth::ProviderInfo;
+use crate::utils::session::SessionManager;
+use crate::utils::transaction::{TxError, TxOptionExt, TxResultExt};
+use crate::{models::user::User, utils::env::is_production_env};
+use axum::Json;
+use tracing::error;
+
+const OAUTH_STATE_COOKIE: &str = "rustytime_oauth_state";
+
+#[derive(Deserialize, JsonSchema)]
+pub struct AuthRequest {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct AuthUrlResponse {
+    pub auth_url: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ProvidersResponse {
+    pub providers: Vec<ProviderInfo>,
+    /// Whether username and password login is available
+    pub local_login: bool,
+    /// Whether new local accounts can be created
+    pub local_registration: bool,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct LocalCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct UserResponse {
+    pub id: i32,
+    pub github_id: Option<i64>,
+    pub username: String,
+    pub avatar_url: String,
+    pub role: String,
+    pub permissions: Vec<Permission>,
+}
+
+impl From<(User, Role)> for UserResponse {
+    fn from((user, role): (User, Role)) -> Self {
+        Self {
+            id: user.id,
+            github_id: user.github_id,
+            username: user.name,
+            avatar_url: user.avatar_url,
+            permissions: role.permission_set(),
+            role: role.name,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ImpersonationResponse {
+    pub admin_id: i32,
+    pub admin_name: String,
+    pub admin_avatar_url: String,
+    /// Whether changes can be made as the user, otherwise impersonation is read-only
+    pub write: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct VerifySessionResponse {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub impersonation: Option<ImpersonationResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Handler to list the configured login providers
+pub async fn providers(State(app_state): State<AppState>) -> Json<ProvidersResponse> {
+    Json(ProvidersResponse {
+        providers: app_state.auth_providers.list(),
+        local_login: app_state.local_auth.enabled,
+        local_registration: app_state.local_auth.registration,
+    })
+}
+
+/// Create a session for the user and set its cookie
+fn start_session(
+    conn: &mut PgConnection,
+    cookies: &Cookies,
+    user_id: i32,
+    client: ClientInfo,
+) -> QueryResult<()> {
+    let session = Session::create(
+        conn,
+        &NewSession {
+            user_id,
+            impersonated_by: None,
+            device: client.device,
+            ip_address: client.ip_address,
+        },
+    )?;
+
+    cookies.add(SessionManager::create_session_cookie(
+        session.id,
+        session.expires_at,
+    ));
+    Ok(())
+}
+
+/// Handler to log in with a local account
+pub async fn local_login(
+    State(app_state): State<AppState>,
+    cookies: NoApi<Cookies>,
+    NoApi(client): NoApi<ClientInfo>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(credentials): Json<LocalCredentials>,
+) -> Result<Json<UserResponse>, Response> {
+    if !app_state.local_auth.enabled {
+        return Err((StatusCode::NOT_FOUND, "Local accounts are disabled").into_response());
+    }
+
+    let identity = db_query!(
+        UserIdentity::find_local(&mut conn, credentials.username.trim()),
+        "Failed to fetch account"
+    );
+
+    // hashing is slow on purpose, keep it off the async workers
+    let password_hash = identity.as_ref().and_then(|i| i.password_hash.clone());
+    let valid = tokio::task::spawn_blocking(move || {
+        verify_password(&credentials.password, password_hash.as_deref())
+    })
+    .await
+    .unwrap_or(false);
+
+    let Some(identity) = identity.filter(|_| valid) else {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid username or password").into_response());
+    };
+
+    let user = db_query!(
+        User::get_by_id(&mut conn, identity.user_id),
+        "Failed to fetch user"
+    )
+    .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid username or password").into_response())?;
+
+    let role = db_query!(user.role(&mut conn), "Failed to fetch role");
+    db_query!(
+        start_session(&mut conn, &cookies.0, user.id, client),
+        "Failed to create session"
+    );
+    Ok(Json((user, role).into()))
+}
+
+/// Handler to create a local account and log in with it
+pub async fn local_register(
+    State(app_state): State<AppState>,
+    cookies: NoApi<Cookies>,
+    NoApi(client): NoApi<ClientInfo>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(credentials): Json<LocalCredentials>,
+) -> Result<Json<UserResponse>, Response> {
+    if !app_state.local_auth.registration {
+        return Err((StatusCode::FORBIDDEN, "Registration is closed").into_response());
+    }
+
+    let username = credentials.username.trim().to_string();
+    validate_username(&username)
+        .and(validate_password(&credentials.password))
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+
+    let password_hash = tokio::task::spawn_blocking(move || hash_password(&credentials.password))
+        .await
+        .map_err(|_| {
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to hash password").into_response()
+        })?
+        .map_err(|_| {
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to hash password").into_response()
+        })?;
+
+    let user = db_query!(
+        UserIdentity::register_local(&mut conn, &username, &password_hash),
+        "Failed to create account"
+    )
+    .ok_or_else(|| (StatusCode::CONFLICT, "Username is already taken").into_response())?;
+
+    let role = db_query!(user.role(&mut conn), "Failed to fetch role");
+    db_query!(
+        start_session(&mut conn, &cookies.0, user.id, client),
+        "Failed to create session"
+    );
+    Ok(Json((user, role).into()))
+}
+
+/// Handler to initiate an OAuth login with one of the providers
+pub async fn login(
+    State(app_state): State<AppState>,
+    Path(provider): Path<String>,
+    cookies: NoApi<Cookies>,
+) -> Result<Json<AuthUrlResponse>, Response> {
+    let cookies = cookies.0;
+    let provider = app_state
+        .auth_providers
+        .get(&provider)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Unknown login provider").into_response())?;
+
+    let (auth_url, csrf_token_secret) = provider.authorize_url();
+
+    // check if in production for cookie security settings
+    let is_production = is_production_env();
+
+    let cookie = tower_cookies::Cookie::build((OAUTH_STATE_COOKIE, csrf_token_secret))
+        .path("/")
+        .expires(time::OffsetDateTime::now_utc() + time::Duration::minutes(10))
+        .http_only(true)
+        .secure(is_production) // only secure in production
+        .same_site(tower_cookies::cookie::SameSite::Lax)
+        .build();
+
+    cookies.add(cookie);
+
+    Ok(Json(AuthUrlResponse { auth_url }))
+}
+
+/// Handler for the OAuth callback of a provider
+///
+/// Signs in with the provider account, or links it to the current user when already signed in
+pub async fn callback(
+    State(app_state): State<AppState>,
+    Path(provider): Path<String>,
+    cookies: NoApi<Cookies>,
+    NoApi(client): NoApi<ClientInfo>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Query(params): Query<AuthRequest>,
+) -> Result<Redirect, Response> {
+    let cookies = cookies.0;
+    // get frontend URL from environment variable
+    let frontend_url =
+        env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+
+    let Some(provider) = app_state.auth_providers.get(&provider) else {
+        return Ok(Redirect::to(&format!(
+            "{}/?error=unknown_provider",
+            frontend_url
+        )));
+    };
+
+    // validate state parameter against cookie
+    let state_cookie = cookies.get(OAUTH_STATE_COOKIE);
+    let state_valid = state_cookie
+        .as_ref()
+        .map(|c| c.value() == params.state)
+        .unwrap_or(false);
+
+    // remove the oauth state cookie after checking
+    let remove_state_cookie = tower_cookies::Cookie::build((OAUTH_STATE_COOKIE, ""))
+        .expires(time::OffsetDateTime::UNIX_EPOCH)
+        .path("/")
+        .build();
+    cookies.add(remove_state_cookie);
+
+    if !state_valid {
+        return Ok(Redirect::to(&format!(
+            "{}/?error=invalid_state",
+            frontend_url
+        )));
+    }
+
+    // exchange code for access token
+    let access_token = match provider.exchange_code(params.code).await {
+        Ok(token) => token,
+        Err(err) => {
+            error!("Failed to exchange code for token: {}", err);
+            return Ok(Redirect::to(&format!(
+                "{}/?error=token_exchange",
+                frontend_url
+            )));
+        }
+    };
+
+    // fetch user info from the provider
+    let account = match provider
+        .fetch_user(&app_state.http_client, &access_token)
+        .await
+    {
+        Ok(account) => account,
+        Err(err) => {
+            return Ok({
+                error!("Failed to fetch user info from {}: {}", provider.name, err);
+                Redirect::to(&format!("{}/?error=provider_api", frontend_url))
+            });
+        }
+    };
+
+    // an existing session links the account instead of signing in, unless it is impersonating
+    let current_user_id = match SessionManager::resolve_session(&cookies, &app_state.db_pool).await
+    {
+        Ok(Some(resolved)) if resolved.impersonator.is_none() => Some(resolved.user.id),
+        _ => None,
+    };
+
+    // single-user mode only lets the owner link accounts
+    let allow_sign_up = !app_state.local_auth.is_single_user();
+    let user = match UserIdentity::sign_in(
+        &mut conn,
+        provider.kind,
+        &account,
+        current_user_id,
+        allow_sign_up,
+    ) {
+        Ok(SignInOutcome::SignedIn(user)) => *user,
+        Ok(SignInOutcome::Linked) => {
+            return Ok(Redirect::to(&format!(
+                "{}/settings?linked={}",
+                frontend_url,
+                provider.id()
+            )));
+        }
+        Ok(SignInOutcome::AlreadyLinked) => {
+            return Ok(Redirect::to(&format!(
+                "{}/settings?error=identity_in_use",
+                frontend_url
+            )));
+        }
+        Ok(SignInOutcome::SignUpClosed) => {
+            return Ok(Redirect::to(&format!(
+                "{}/?auth_error=registration_closed",
+                frontend_url
+            )));
+        }
+        Err(err) => {
+            error!("Database error during sign in: {}", err);
+            return Ok(Redirect::to(&format!("{}/?error=database", frontend_url)));
+        }
+    };
+
+    let session = db_transaction!(conn, |conn| {
+        // create new session for authentication
+        let new_session = NewSession {
+            user_id: user.id,
+            impersonated_by: None,
+            device: client.device,
+            ip_address: client.ip_address,
+        };
+        Session::create(conn, &new_session)
+            .db_err("Failed to create session")
+            .map_err(|_| {
+                error!("Database error during session creation");
+                TxError::Response(Box::new(
+                    Redirect::to(&format!("{}/?error=database", frontend_url)).into_response(),
+                ))
+            })
+    });
+
+    // set session cookie
+    let session_cookie = SessionManager::create_session_cookie(session.id, session.expires_at);
+    cookies.add(session_cookie);
+
+    Ok(Redirect::to(&frontend_url))
+}
+
+/// Handler to verify a session token
+pub async fn verify_session(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    cookies: NoApi<Cookies>,
+) -> Result<Json<VerifySessionResponse>, Response> {
+    let cookies = cookies.0;
+    let session_id = SessionManager::get_session_from_cookies(&cookies).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, "Missing or invalid session_id").into_response()
+    })?;
+
+    match SessionManager::validate_session(&app_state.db_pool, session_id).await {
+        Ok(Some(session_data)) => {
+            let (user, impersonation) = db_transaction!(conn, |conn| {
+                // get user details
+                let user = crate::schema::users::table
+                    .find(session_data.user_id)
+                    .first::<User>(conn)
+                    .optional()
+                    .db_err("Failed to fetch user")?
+                    .or_not_found("User not found")?;
+                let role = user.role(conn).db_err("Failed to fetch role")?;
+
+                let impersonation = if let Some(admin_id) = session_data.impersonated_by {
+                    let admin = crate::schema::users::table
+                        .find(admin_id)
+                        .first::<User>(conn)
+                        .optional()
+                        .db_err("Failed to fetch impersonating admin")?;
+
+                    let Some(admin) = admin else {
+                        tx_bail!(StatusCode::NOT_FOUND, "Impersonating admin not found");
+                    };
+
+                    let admin_role = admin.role(conn).db_err("Failed to fetch admin role")?;
+
+                    Some(ImpersonationResponse {
+                        write: session_data.impersonation_write
+                            && admin_role.has(Permission::ImpersonateWrite),
+                        admin_id: admin.id,
+                        admin_name: admin.name,
+                        admin_avatar_url: admin.avatar_url,
+                    })
+                } else {
+                    None
+                };
+
+                Ok(((user, role), impersonation))
+            });
+
+            Ok(Json(VerifySessionResponse {
+                valid: true,
+                user: Some(user.into()),
+                impersonation,
+                expires_at: Some(session_data.expires_at),
+                message: None,
+            }))
+        }
+        Ok(None) => Ok(Json(VerifySessionResponse {
+            valid: false,
+            user: None,
+            impersonation: None,
+            expires_at: None,
+            message: Some("Session not found or expired".to_string()),
+        })),
+        Err(err) => {
+            eprintln!("Database error validating session: {}", err);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response())
+        }
+    }
+}
+
+/// Handler to log out the user
+pub async fn logout(
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    cookies: NoApi<Cookies>,
+) -> Result<Response, Response> {
+    let cookies = cookies.0;
+    // get session from cookie
+    if let Some(session_id) = SessionManager::get_session_from_cookies(&cookies) {
+        db_query!(SessionManager::delete_session(&mut conn, session_id));
+    }
+
+    // remove session cookie
+    let remove_cookie = SessionManager::remove_session_cookie();
+    cookies.add(remove_cookie);
+
+    Ok(StatusCode::OK.into_response())
+}
@@ -0,0 +1,386 @@
+use aide::NoApi;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::Utc;
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::db_query;
+use crate::models::heartbeat::{
+    DurationInput, Heartbeat, MAX_PROJECT_LENGTH, TimeRange, UsageStat,
+};
+use crate::models::project::Project;
+use crate::models::user::User;
+use crate::state::AppState;
+use crate::utils::badge::{DEFAULT_COLOR, INACTIVE_COLOR, parse_color, render_badge};
+use crate::utils::cache::{BadgeCacheKey, CardCacheKey};
+use crate::utils::card::{CardData, CardLayout, CardOptions, CardStat, CardTheme, render_card};
+use crate::utils::extractors::DbConnection;
+use crate::utils::time::{format_hour_minute, parse_timezone};
+
+/// How long clients and proxies may cache a badge or card, matching the server side cache
+const BADGE_MAX_AGE_SECONDS: u32 = 300;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct BadgeQuery {
+    /// Time range to cover, all time by default
+    pub range: Option<TimeRange>,
+    /// Text shown on the left side of the badge
+    pub label: Option<String>,
+    /// Named or hex color of the right side of the badge
+    pub color: Option<String>,
+}
+
+impl BadgeQuery {
+    fn range(&self) -> TimeRange {
+        self.range.unwrap_or(TimeRange::All)
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CardQuery {
+    /// Time range to cover, all time by default
+    pub range: Option<TimeRange>,
+    pub theme: Option<CardTheme>,
+    pub layout: Option<CardLayout>,
+    /// Comma separated languages to leave out of the card
+    pub hide: Option<String>,
+    /// Whether to list the top projects below the languages
+    pub projects: Option<bool>,
+}
+
+impl CardQuery {
+    fn hidden_languages(&self) -> Vec<String> {
+        self.hide
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .take(20)
+            .collect()
+    }
+}
+
+fn range_label(range: TimeRange) -> &'static str {
+    match range {
+        TimeRange::Day => "today",
+        TimeRange::Week => "this week",
+        TimeRange::Month => "this month",
+        TimeRange::All => "coding time",
+    }
+}
+
+fn card_range_label(range: TimeRange) -> &'static str {
+    match range {
+        TimeRange::Day => "Today",
+        TimeRange::Week => "This week",
+        TimeRange::Month => "This month",
+        TimeRange::All => "All time",
+    }
+}
+
+/// Get a badge's message from the cache, computing it if needed
+fn cached_message(
+    app_state: &AppState,
+    key: BadgeCacheKey,
+    compute: impl FnOnce() -> QueryResult<Option<String>>,
+) -> QueryResult<Option<String>> {
+    if let Some(cached) = app_state.cache.badges.get(&key) {
+        return Ok(cached);
+    }
+
+    let message = compute()?;
+    app_state.cache.badges.insert(key, message.clone());
+
+    Ok(message)
+}
+
+fn user_seconds(
+    conn: &mut PgConnection,
+    user: &User,
+    range: TimeRange,
+    project: Option<String>,
+) -> QueryResult<i64> {
+    let tz = parse_timezone(&user.timezone);
+    let now = Utc::now();
+
+    Heartbeat::get_user_duration_seconds(
+        conn,
+        DurationInput {
+            user_id: Some(user.id),
+            start_date: Heartbeat::start_boundary_utc(range, tz, now),
+            end_date: Some(now),
+            project,
+            language: None,
+            entity: None,
+            type_filter: None,
+        },
+    )
+}
+
+/// Render a badge for the given message
+fn badge_response(
+    headers: &HeaderMap,
+    query: &BadgeQuery,
+    default_label: &str,
+    message: Option<String>,
+) -> Response {
+    let label = query
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .unwrap_or(default_label);
+
+    // missing users, private profiles and hidden projects all look the same
+    let svg = match message {
+        Some(message) => {
+            let color = query
+                .color
+                .as_deref()
+                .and_then(parse_color)
+                .unwrap_or_else(|| DEFAULT_COLOR.to_string());
+            render_badge(label, &message, &color)
+        }
+        None => render_badge(label, "not found", INACTIVE_COLOR),
+    };
+
+    svg_response(headers, svg)
+}
+
+/// Serve an SVG with caching headers, answering conditional requests with 304 Not Modified
+fn svg_response(headers: &HeaderMap, svg: String) -> Response {
+    let digest = Sha256::digest(svg.as_bytes());
+    let etag = format!(
+        "\"{}\"",
+        digest[..8]
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    );
+    let cache_control = format!("public, max-age={BADGE_MAX_AGE_SECONDS}");
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+
+    let mut response = if not_modified {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        (
+            [(header::CONTENT_TYPE, "image/svg+xml; charset=utf-8")],
+            svg,
+        )
+            .into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&cache_control) {
+        response_headers.insert(header::CACHE_CONTROL, value);
+    }
+
+    response
+}
+
+/// Badge showing a user's total coding time
+pub async fn time_badge(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(username): Path<String>,
+    Query(query): Query<BadgeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    let username = username.chars().take(100).collect::<String>();
+    let range = query.range();
+
+    let key = BadgeCacheKey {
+        username: username.to_lowercase(),
+        kind: "time".to_string(),
+        range,
+    };
+    let message = db_query!(
+        cached_message(&app_state, key, || {
+            let Some(user) = User::find_public_by_name(&mut conn, &username)? else {
+                return Ok(None);
+            };
+            user_seconds(&mut conn, &user, range, None).map(|s| Some(format_hour_minute(s)))
+        }),
+        "Failed to render badge"
+    );
+
+    Ok(badge_response(
+        &headers,
+        &query,
+        range_label(range),
+        message,
+    ))
+}
+
+/// Badge showing a user's most used language
+pub async fn language_badge(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(username): Path<String>,
+    Query(query): Query<BadgeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    let username = username.chars().take(100).collect::<String>();
+    let range = query.range();
+
+    let key = BadgeCacheKey {
+        username: username.to_lowercase(),
+        kind: "language".to_string(),
+        range,
+    };
+    let message = db_query!(
+        cached_message(&app_state, key, || {
+            let Some(user) = User::find_public_by_name(&mut conn, &username)? else {
+                return Ok(None);
+            };
+            let stats =
+                Heartbeat::get_dashboard_stats_by_range(&mut conn, user.id, range, &user.timezone)?;
+            let language = stats
+                .top_languages
+                .into_iter()
+                .map(|l| l.name)
+                .find(|name| !name.is_empty() && name != "Unknown")
+                .unwrap_or_else(|| "none".to_string());
+            Ok(Some(language))
+        }),
+        "Failed to render badge"
+    );
+
+    Ok(badge_response(&headers, &query, "top language", message))
+}
+
+/// Badge showing the coding time of one of a user's projects
+pub async fn project_badge(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path((username, project_name)): Path<(String, String)>,
+    Query(query): Query<BadgeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    let username = username.chars().take(100).collect::<String>();
+    let project_name = project_name
+        .chars()
+        .take(MAX_PROJECT_LENGTH)
+        .collect::<String>();
+    let range = query.range();
+
+    let key = BadgeCacheKey {
+        username: username.to_lowercase(),
+        kind: format!("project:{project_name}"),
+        range,
+    };
+    let message = db_query!(
+        cached_message(&app_state, key, || {
+            let Some(user) = User::find_public_by_name(&mut conn, &username)? else {
+                return Ok(None);
+            };
+            let Some(project) = Project::find_by_name(&mut conn, user.id, &project_name)? else {
+                return Ok(None);
+            };
+            // an alias must not reveal the time of a hidden project it resolves to
+            let resolved = Project::find_resolved_user_project(&mut conn, user.id, project.id)?;
+            if project.hidden || resolved.is_none_or(|p| p.hidden) {
+                return Ok(None);
+            }
+            user_seconds(&mut conn, &user, range, Some(project.name))
+                .map(|s| Some(format_hour_minute(s)))
+        }),
+        "Failed to render badge"
+    );
+
+    Ok(badge_response(&headers, &query, &project_name, message))
+}
+
+fn card_data(conn: &mut PgConnection, user: &User, range: TimeRange) -> QueryResult<CardData> {
+    let stats = Heartbeat::get_dashboard_stats_by_range(conn, user.id, range, &user.timezone)?;
+    let hidden_projects: Vec<String> = Project::list_user_projects(conn, user.id)?
+        .into_iter()
+        .filter(|p| p.hidden)
+        .map(|p| p.name)
+        .collect();
+
+    let to_stat = |s: UsageStat| CardStat {
+        name: s.name,
+        total_seconds: s.total_seconds,
+    };
+
+    Ok(CardData {
+        username: user.name.clone(),
+        total_seconds: stats.total_time,
+        languages: stats
+            .top_languages
+            .into_iter()
+            .filter(|l| !l.name.is_empty() && l.name != "Unknown")
+            .map(to_stat)
+            .collect(),
+        projects: stats
+            .top_projects
+            .into_iter()
+            .filter(|p| !p.name.is_empty() && !hidden_projects.contains(&p.name))
+            .map(to_stat)
+            .collect(),
+    })
+}
+
+/// Stats card with a user's top languages and, optionally, top projects
+pub async fn stats_card(
+    State(app_state): State<AppState>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(username): Path<String>,
+    Query(query): Query<CardQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    let username = username.chars().take(100).collect::<String>();
+    let range = query.range.unwrap_or(TimeRange::All);
+
+    let key = CardCacheKey {
+        username: username.to_lowercase(),
+        range,
+    };
+    let data = match app_state.cache.cards.get(&key) {
+        Some(cached) => cached,
+        None => {
+            let data = db_query!(
+                User::find_public_by_name(&mut conn, &username).and_then(|user| {
+                    user.map(|user| card_data(&mut conn, &user, range))
+                        .transpose()
+                }),
+                "Failed to render card"
+            );
+            app_state.cache.cards.insert(key, data.clone());
+            data
+        }
+    };
+
+    // missing users and private profiles look the same
+    let svg = match data {
+        Some(data) => {
+            let hide = query.hidden_languages();
+            render_card(
+                &data,
+                &CardOptions {
+                    range_label: card_range_label(range),
+                    theme: query.theme.unwrap_or_default(),
+                    layout: query.layout.unwrap_or_default(),
+                    hide: &hide,
+                    show_projects: query.projects.unwrap_or(false),
+                },
+            )
+        }
+        None => render_badge("coding stats", "not found", INACTIVE_COLOR),
+    };
+
+    Ok(svg_response(&headers, svg))
+}
@@ -0,0 +1,59 @@
+use crate::db_query;
+use crate::models::heartbeat::{DependencyGroup, Heartbeat, TimeRange, UsageStat};
+use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use crate::utils::time::{TimeFormat, human_readable_duration};
+use aide::NoApi;
+use axum::Json;
+use axum::extract::Query;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const DEPENDENCY_GROUP_LIMIT: i32 = 10;
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DependencyQuery {
+    #[serde(default)]
+    pub range: TimeRange,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct DependencyStatsResponse {
+    pub range: String,
+    pub total_seconds: i64,
+    pub human_readable_total: String,
+    pub dependencies: Vec<UsageStat>,
+    pub projects: Vec<DependencyGroup>,
+    pub languages: Vec<DependencyGroup>,
+}
+
+/// Handler for dependency usage stats
+pub async fn dependency_stats(
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Query(query): Query<DependencyQuery>,
+) -> Result<Json<DependencyStatsResponse>, Response> {
+    let stats = db_query!(
+        Heartbeat::get_dependency_stats_by_range(
+            &mut conn,
+            current_user.id,
+            query.range,
+            &current_user.timezone,
+            DEPENDENCY_GROUP_LIMIT
+        ),
+        "Failed to fetch dependency stats"
+    );
+
+    Ok(Json(DependencyStatsResponse {
+        range: query.range.as_str().to_string(),
+        total_seconds: stats.total_time,
+        human_readable_total: human_readable_duration(stats.total_time, TimeFormat::NoDays)
+            .human_readable,
+        dependencies: stats.top_dependencies,
+        projects: stats.by_project,
+        languages: stats.by_language,
+    }))
+}
@@ -0,0 +1,178 @@
+use crate::db_query;
+use crate::models::goal::{Goal, GoalPeriod, GoalProgress, NewGoal, weekdays_to_mask};
+use crate::models::heartbeat::{MAX_LANGUAGE_LENGTH, MAX_PROJECT_LENGTH};
+use crate::models::user::User;
+use crate::state::AppState;
+use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use aide::NoApi;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use diesel::PgConnection;
+use diesel::QueryResult;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const MAX_GOALS_PER_USER: i64 = 20;
+
+#[derive(Serialize, JsonSchema)]
+pub struct GoalsResponse {
+    pub goals: Vec<GoalProgress>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateGoalRequest {
+    pub period: GoalPeriod,
+    pub target_seconds: i32,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub ignored_weekdays: Vec<String>,
+}
+
+/// Progress of all of a user's goals, served from the cache when possible
+pub fn cached_goal_progress(
+    app_state: &AppState,
+    conn: &mut PgConnection,
+    user: &User,
+) -> QueryResult<Vec<GoalProgress>> {
+    if let Some(cached) = app_state.cache.goals.get(&user.id) {
+        return Ok(cached);
+    }
+
+    let progress = Goal::progress_for_user(conn, user.id, &user.timezone)?;
+    app_state.cache.goals.insert(user.id, progress.clone());
+
+    Ok(progress)
+}
+
+fn optional_filter(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Handler for listing the user's goals with their progress
+pub async fn goals(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<GoalsResponse>, Response> {
+    let goals = db_query!(
+        cached_goal_progress(&app_state, &mut conn, &current_user),
+        "Failed to fetch goals"
+    );
+
+    Ok(Json(GoalsResponse { goals }))
+}
+
+/// Handler for creating a goal
+pub async fn create_goal(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(request): Json<CreateGoalRequest>,
+) -> Result<(StatusCode, Json<GoalsResponse>), Response> {
+    let max_seconds = match request.period {
+        GoalPeriod::Day => 24 * 3600,
+        GoalPeriod::Week => 7 * 24 * 3600,
+    };
+    if request.target_seconds <= 0 || request.target_seconds > max_seconds {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Target must be between 1 second and {} hours per {}",
+                max_seconds / 3600,
+                request.period.as_str()
+            ),
+        )
+            .into_response());
+    }
+
+    let project = optional_filter(request.project);
+    if project
+        .as_ref()
+        .is_some_and(|p| p.chars().count() > MAX_PROJECT_LENGTH)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Project must be at most {MAX_PROJECT_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    let language = optional_filter(request.language);
+    if language
+        .as_ref()
+        .is_some_and(|l| l.chars().count() > MAX_LANGUAGE_LENGTH)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Language must be at most {MAX_LANGUAGE_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    let ignored_weekdays = weekdays_to_mask(&request.ignored_weekdays)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+    if ignored_weekdays == 0b111_1111 {
+        return Err((StatusCode::BAD_REQUEST, "A goal cannot ignore every day").into_response());
+    }
+
+    let existing = db_query!(
+        Goal::count_for_user(&mut conn, current_user.id),
+        "Failed to count goals"
+    );
+    if existing >= MAX_GOALS_PER_USER {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You can have at most {MAX_GOALS_PER_USER} goals"),
+        )
+            .into_response());
+    }
+
+    let new_goal = NewGoal {
+        user_id: current_user.id,
+        period: request.period.as_str().to_string(),
+        target_seconds: request.target_seconds,
+        project,
+        language,
+        ignored_weekdays,
+    };
+
+    db_query!(Goal::create(&mut conn, &new_goal), "Failed to create goal");
+
+    app_state.cache.invalidate_user_goals(current_user.id);
+
+    let goals = db_query!(
+        cached_goal_progress(&app_state, &mut conn, &current_user),
+        "Failed to fetch goals"
+    );
+
+    Ok((StatusCode::CREATED, Json(GoalsResponse { goals })))
+}
+
+pub async fn delete_goal(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let deleted = db_query!(
+        Goal::delete(&mut conn, current_user.id, id),
+        "Failed to delete goal"
+    );
+
+    if deleted == 0 {
+        return Err((StatusCode::NOT_FOUND, "Goal not found").into_response());
+    }
+
+    app_state.cache.invalidate_user_goals(current_user.id);
+
+    Ok(StatusCode::OK)
+}
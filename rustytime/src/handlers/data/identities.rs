@@ -0,0 +1,86 @@
+use crate::db_query;
+use crate::models::user_identity::UserIdentity;
+use crate::state::AppState;
+use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use crate::utils::oauth::ProviderInfo;
+use aide::NoApi;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+#[derive(Serialize, JsonSchema)]
+pub struct IdentityRecord {
+    pub id: i32,
+    pub provider: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct IdentitiesResponse {
+    pub identities: Vec<IdentityRecord>,
+    /// Providers configured on the server, accounts from any of them can be linked
+    pub providers: Vec<ProviderInfo>,
+}
+
+/// Handler for the linked login accounts
+pub async fn identities(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<IdentitiesResponse>, Response> {
+    let identities = db_query!(
+        UserIdentity::list_for_user(&mut conn, current_user.id),
+        "Failed to fetch linked accounts"
+    );
+
+    Ok(Json(IdentitiesResponse {
+        identities: identities
+            .into_iter()
+            .map(|identity| IdentityRecord {
+                id: identity.id,
+                provider: identity.provider,
+                username: identity.username,
+                email: identity.email,
+                created_at: identity.created_at,
+            })
+            .collect(),
+        providers: app_state.auth_providers.list(),
+    }))
+}
+
+pub async fn delete_identity(
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let identities = db_query!(
+        UserIdentity::list_for_user(&mut conn, current_user.id),
+        "Failed to fetch linked accounts"
+    );
+
+    if !identities.iter().any(|identity| identity.id == id) {
+        return Err((StatusCode::NOT_FOUND, "Linked account not found").into_response());
+    }
+    if identities.len() <= 1 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The last linked account cannot be removed",
+        )
+            .into_response());
+    }
+
+    db_query!(
+        UserIdentity::delete(&mut conn, current_user.id, id),
+        "Failed to unlink account"
+    );
+
+    Ok(StatusCode::OK)
+}
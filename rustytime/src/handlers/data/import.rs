@@ -8,7 +8,6 @@ use axum::response::Response;
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
-use tower_cookies::Cookies;
 use tracing::{error, info};
 
 use crate::db_query;
@@ -18,7 +17,6 @@ use crate::models::import_job::{ImportJob, ImportJobStatus};
 use crate::state::AppState;
 use crate::utils::extractors::AuthenticatedUser;
 use crate::utils::extractors::DbConnection;
-use crate::utils::session::SessionManager;
 use crate::utils::transaction::{TxError, TxResultExt};
 
 #[derive(Deserialize, JsonSchema)]
@@ -67,29 +65,9 @@ impl From<ImportJob> for ImportStatusResponse {
 pub async fn import_heartbeats(
     State(app_state): State<AppState>,
     Query(query): Query<ImportQuery>,
-    cookies: NoApi<Cookies>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
 ) -> Result<Json<ImportStartResponse>, Response> {
-    let Some(session_id) = SessionManager::get_session_from_cookies(&cookies) else {
-        return Err((StatusCode::UNAUTHORIZED, "User session is invalid").into_response());
-    };
-
-    let Some(session_data) = db_query!(
-        SessionManager::validate_session(&app_state.db_pool, session_id).await,
-        "Session validation error"
-    ) else {
-        return Err((StatusCode::UNAUTHORIZED, "User session is invalid").into_response());
-    };
-
-    if session_data.impersonated_by.is_some() && !current_user.is_owner() {
-        return Err((
-            StatusCode::FORBIDDEN,
-            "Impersonators cannot perform data imports",
-        )
-            .into_response());
-    }
-
     let api_key = query.api_key.trim().to_string();
     if api_key.is_empty() {
         return Err((
@@ -0,0 +1,161 @@
+use crate::db_query;
+use crate::jobs::history::{HistoryJob, start_history_job};
+use crate::models::heartbeat::MAX_LANGUAGE_LENGTH;
+use crate::models::language_mapping::{
+    LanguageMapping, LanguageMappingKind, NewLanguageMapping, is_valid_extension,
+};
+use crate::state::AppState;
+use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use aide::NoApi;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const MAX_MAPPINGS_PER_USER: i64 = 100;
+const MAX_EXTENSION_LENGTH: usize = 20;
+
+#[derive(Serialize, JsonSchema)]
+pub struct LanguageMappingsResponse {
+    pub mappings: Vec<LanguageMapping>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateLanguageMappingRequest {
+    pub kind: LanguageMappingKind,
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ApplyLanguageMappingsResponse {
+    pub message: String,
+}
+
+/// Handler for listing the user's language mappings
+pub async fn language_mappings(
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<LanguageMappingsResponse>, Response> {
+    let mappings = db_query!(
+        LanguageMapping::list_for_user(&mut conn, current_user.id),
+        "Failed to fetch language mappings"
+    );
+
+    Ok(Json(LanguageMappingsResponse { mappings }))
+}
+
+/// Handler for creating or replacing a language mapping
+pub async fn create_language_mapping(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(request): Json<CreateLanguageMappingRequest>,
+) -> Result<Json<LanguageMapping>, Response> {
+    let source = match request.kind {
+        LanguageMappingKind::Extension => {
+            let extension = request.source.trim().trim_start_matches('.').to_lowercase();
+            if extension.len() > MAX_EXTENSION_LENGTH || !is_valid_extension(&extension) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Extensions may only contain letters, numbers, '+' and '-'",
+                )
+                    .into_response());
+            }
+            extension
+        }
+        LanguageMappingKind::Language => {
+            let language = request.source.trim().to_lowercase();
+            if language.is_empty() || language.chars().count() > MAX_LANGUAGE_LENGTH {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Language must be between 1 and {MAX_LANGUAGE_LENGTH} characters"),
+                )
+                    .into_response());
+            }
+            language
+        }
+    };
+
+    let target = request.target.trim().to_string();
+    if target.is_empty() || target.chars().count() > MAX_LANGUAGE_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Target language must be between 1 and {MAX_LANGUAGE_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    let existing = db_query!(
+        LanguageMapping::count_for_user(&mut conn, current_user.id),
+        "Failed to count language mappings"
+    );
+    if existing >= MAX_MAPPINGS_PER_USER {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You can have at most {MAX_MAPPINGS_PER_USER} language mappings"),
+        )
+            .into_response());
+    }
+
+    let new_mapping = NewLanguageMapping {
+        user_id: current_user.id,
+        kind: request.kind.as_str().to_string(),
+        source,
+        target,
+    };
+
+    let mapping = db_query!(
+        LanguageMapping::upsert(&mut conn, &new_mapping),
+        "Failed to save language mapping"
+    );
+
+    app_state.cache.invalidate_ingest_settings(current_user.id);
+
+    Ok(Json(mapping))
+}
+
+pub async fn delete_language_mapping(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let deleted = db_query!(
+        LanguageMapping::delete(&mut conn, current_user.id, id),
+        "Failed to delete language mapping"
+    );
+
+    if deleted == 0 {
+        return Err((StatusCode::NOT_FOUND, "Language mapping not found").into_response());
+    }
+
+    app_state.cache.invalidate_ingest_settings(current_user.id);
+
+    Ok(StatusCode::OK)
+}
+
+/// Handler for normalizing the languages of all existing heartbeats
+pub async fn apply_language_mappings(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+) -> Result<(StatusCode, Json<ApplyLanguageMappingsResponse>), Response> {
+    start_history_job(
+        &app_state,
+        HistoryJob::NormalizeLanguages {
+            user_id: current_user.id,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ApplyLanguageMappingsResponse {
+            message: "Your languages are being normalized".to_string(),
+        }),
+    ))
+}
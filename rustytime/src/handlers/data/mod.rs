@@ -1,3 +1,10 @@
+pub mod dependencies;
+pub mod goals;
+pub mod identities;
 pub mod import;
+pub mod language_mappings;
 pub mod project_aliases;
+pub mod project_rules;
 pub mod projects;
+pub mod sessions;
+pub mod webhooks;
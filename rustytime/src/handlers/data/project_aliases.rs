@@ -84,6 +84,7 @@ pub async fn add_project_alias(
 
     app_state.cache.invalidate_user_dashboard(current_user.id);
     app_state.cache.invalidate_user_projects(current_user.id);
+    app_state.cache.invalidate_ingest_settings(current_user.id);
 
     Ok(StatusCode::CREATED)
 }
@@ -101,6 +102,7 @@ pub async fn delete_project_alias(
 
     app_state.cache.invalidate_user_dashboard(current_user.id);
     app_state.cache.invalidate_user_projects(current_user.id);
+    app_state.cache.invalidate_ingest_settings(current_user.id);
 
     Ok(StatusCode::OK)
 }
@@ -0,0 +1,150 @@
+use crate::db_query;
+use crate::jobs::history::{HistoryJob, start_history_job};
+use crate::models::heartbeat::MAX_PROJECT_LENGTH;
+use crate::models::project_rule::{NewProjectRule, ProjectRule, ProjectRuleField, compile_pattern};
+use crate::state::AppState;
+use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use aide::NoApi;
+use axum::Json;
+use axum::extract::{Path, State};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+const MAX_RULES_PER_USER: i64 = 50;
+const MAX_PATTERN_LENGTH: usize = 255;
+
+#[derive(Serialize, JsonSchema)]
+pub struct ProjectRulesResponse {
+    pub rules: Vec<ProjectRule>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateProjectRuleRequest {
+    pub match_field: ProjectRuleField,
+    pub pattern: String,
+    pub target_project: String,
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ApplyProjectRulesResponse {
+    pub message: String,
+}
+
+/// Handler for listing the user's project rules
+pub async fn project_rules(
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<ProjectRulesResponse>, Response> {
+    let rules = db_query!(
+        ProjectRule::list_for_user(&mut conn, current_user.id),
+        "Failed to fetch project rules"
+    );
+
+    Ok(Json(ProjectRulesResponse { rules }))
+}
+
+pub async fn create_project_rule(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(request): Json<CreateProjectRuleRequest>,
+) -> Result<(StatusCode, Json<ProjectRule>), Response> {
+    let pattern = request.pattern.trim().to_string();
+    let target_project = request.target_project.trim().to_string();
+
+    if pattern.is_empty() || pattern.len() > MAX_PATTERN_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Pattern must be between 1 and {MAX_PATTERN_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    if let Err(e) = compile_pattern(&pattern) {
+        return Err((StatusCode::BAD_REQUEST, format!("Invalid pattern: {e}")).into_response());
+    }
+
+    if target_project.is_empty() || target_project.chars().count() > MAX_PROJECT_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Target project must be between 1 and {MAX_PROJECT_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    let existing = db_query!(
+        ProjectRule::count_for_user(&mut conn, current_user.id),
+        "Failed to count project rules"
+    );
+    if existing >= MAX_RULES_PER_USER {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You can have at most {MAX_RULES_PER_USER} project rules"),
+        )
+            .into_response());
+    }
+
+    let new_rule = NewProjectRule {
+        user_id: current_user.id,
+        match_field: request.match_field.as_str().to_string(),
+        pattern,
+        target_project,
+        priority: request.priority,
+    };
+
+    let rule = db_query!(
+        ProjectRule::create(&mut conn, &new_rule),
+        "Failed to create project rule"
+    );
+
+    app_state.cache.invalidate_ingest_settings(current_user.id);
+
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+pub async fn delete_project_rule(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let deleted = db_query!(
+        ProjectRule::delete(&mut conn, current_user.id, id),
+        "Failed to delete project rule"
+    );
+
+    if deleted == 0 {
+        return Err((StatusCode::NOT_FOUND, "Project rule not found").into_response());
+    }
+
+    app_state.cache.invalidate_ingest_settings(current_user.id);
+
+    Ok(StatusCode::OK)
+}
+
+/// Handler for re-applying project rules to all existing heartbeats
+pub async fn apply_project_rules(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+) -> Result<(StatusCode, Json<ApplyProjectRulesResponse>), Response> {
+    start_history_job(
+        &app_state,
+        HistoryJob::ApplyProjectRules {
+            user_id: current_user.id,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ApplyProjectRulesResponse {
+            message: "Project rules are being applied to your history".to_string(),
+        }),
+    ))
+}
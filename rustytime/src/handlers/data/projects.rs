@@ -1,4 +1,6 @@
 use crate::db_query;
+use crate::jobs::history::{HistoryJob, start_history_job};
+use crate::models::heartbeat::{EntityPrivacy, MAX_PROJECT_LENGTH};
 use crate::models::project::Project as ProjectModel;
 use crate::state::AppState;
 use crate::utils::extractors::{AuthenticatedUser, DbConnection};
@@ -10,7 +12,7 @@ use axum::{
     response::{IntoResponse, Response},
 };
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 const MAX_PROJECT_URL_LENGTH: usize = 255;
 
@@ -31,6 +33,18 @@ pub struct UpdateProjectRequest {
     pub project_url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hidden: Option<bool>,
+    /// Set to null to inherit the user's entity privacy setting
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub entity_privacy: Option<Option<EntityPrivacy>>,
+}
+
+/// Distinguishes an explicit null from a missing field
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
 }
 
 /// Handler for the projects list
@@ -62,6 +76,7 @@ pub async fn update_project(
 ) -> Result<Response, Response> {
     let mut cache_hidden: Option<bool> = None;
     let mut cache_project_url: Option<Option<String>> = None;
+    let mut cache_entity_privacy: Option<Option<EntityPrivacy>> = None;
 
     // validate and set project_url if provided
     if let Some(ref url) = request.project_url {
@@ -113,17 +128,153 @@ pub async fn update_project(
             "Failed to set project hidden status"
         );
 
+        state.cache.invalidate_user_badges(&current_user.name);
         cache_hidden = Some(hidden);
     }
 
-    if cache_hidden.is_some() || cache_project_url.is_some() {
+    // set entity privacy override if provided
+    if let Some(entity_privacy) = request.entity_privacy {
+        db_query!(
+            ProjectModel::set_entity_privacy(
+                &mut conn,
+                project_id,
+                current_user.id,
+                entity_privacy.map(|mode| mode as i16)
+            ),
+            "Failed to set project entity privacy"
+        );
+
+        state.cache.invalidate_ingest_settings(current_user.id);
+        cache_entity_privacy = Some(entity_privacy);
+    }
+
+    if cache_hidden.is_some() || cache_project_url.is_some() || cache_entity_privacy.is_some() {
         state.cache.update_project_settings(
             current_user.id,
             project_id,
             cache_hidden,
             cache_project_url,
+            cache_entity_privacy,
         );
     }
 
     Ok(StatusCode::OK.into_response())
 }
+
+#[derive(Deserialize, JsonSchema)]
+pub struct RenameProjectRequest {
+    pub name: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct MergeProjectRequest {
+    /// Project that will receive all of this project's heartbeats
+    pub target_id: i32,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ProjectJobResponse {
+    pub message: String,
+}
+
+/// Handler for renaming a project and rewriting its history
+pub async fn rename_project(
+    State(state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(project_id): Path<i32>,
+    Json(request): Json<RenameProjectRequest>,
+) -> Result<(StatusCode, Json<ProjectJobResponse>), Response> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_PROJECT_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Project name must be between 1 and {MAX_PROJECT_LENGTH} characters"),
+        )
+            .into_response());
+    }
+
+    let Some(project) = db_query!(
+        ProjectModel::find_user_project(&mut conn, current_user.id, project_id),
+        "Failed to fetch project"
+    ) else {
+        return Err((StatusCode::NOT_FOUND, "Project not found").into_response());
+    };
+
+    if project.name == name {
+        return Err((StatusCode::BAD_REQUEST, "Project already has this name").into_response());
+    }
+
+    let existing = db_query!(
+        ProjectModel::find_by_name(&mut conn, current_user.id, &name),
+        "Failed to fetch project"
+    );
+    if existing.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            "A project with this name already exists, merge the projects instead",
+        )
+            .into_response());
+    }
+
+    start_history_job(
+        &state,
+        HistoryJob::RenameProject {
+            user_id: current_user.id,
+            project_id: project.id,
+            name,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ProjectJobResponse {
+            message: "The project is being renamed".to_string(),
+        }),
+    ))
+}
+
+/// Handler for merging a project into another one and deleting it
+pub async fn merge_project(
+    State(state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(project_id): Path<i32>,
+    Json(request): Json<MergeProjectRequest>,
+) -> Result<(StatusCode, Json<ProjectJobResponse>), Response> {
+    if project_id == request.target_id {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Cannot merge a project into itself",
+        )
+            .into_response());
+    }
+
+    for id in [project_id, request.target_id] {
+        let project = db_query!(
+            ProjectModel::find_user_project(&mut conn, current_user.id, id),
+            "Failed to fetch project"
+        );
+        if project.is_none() {
+            return Err((StatusCode::NOT_FOUND, "Project not found").into_response());
+        }
+    }
+
+    start_history_job(
+        &state,
+        HistoryJob::MergeProjects {
+            user_id: current_user.id,
+            source_id: project_id,
+            target_id: request.target_id,
+        },
+    )
+    .await?;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(ProjectJobResponse {
+            message: "The projects are being merged".to_string(),
+        }),
+    ))
+}
@@ -0,0 +1,106 @@
+use crate::db_query;
+use crate::models::session::Session;
+use crate::utils::extractors::{CurrentSession, DbConnection};
+use crate::utils::session::SessionManager;
+use aide::NoApi;
+use axum::Json;
+use axum::extract::Path;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::Serialize;
+use tower_cookies::Cookies;
+use uuid::Uuid;
+
+#[derive(Serialize, JsonSchema)]
+pub struct SessionRecord {
+    #[schemars(with = "String")]
+    pub id: Uuid,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session making the request
+    pub current: bool,
+    /// Name of the user this session is impersonating
+    pub impersonating: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SessionsResponse {
+    pub sessions: Vec<SessionRecord>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct RevokeSessionsResponse {
+    pub revoked: usize,
+}
+
+/// Handler for the active login sessions
+pub async fn sessions(
+    NoApi(CurrentSession(current)): NoApi<CurrentSession>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<SessionsResponse>, Response> {
+    let sessions = db_query!(
+        Session::list_for_owner(&mut conn, current.owner_id()),
+        "Failed to fetch sessions"
+    );
+
+    Ok(Json(SessionsResponse {
+        sessions: sessions
+            .into_iter()
+            .map(|(session, user_name)| SessionRecord {
+                id: session.id,
+                device: session.device,
+                ip_address: session.ip_address,
+                created_at: session.created_at,
+                last_seen_at: session.last_seen_at,
+                expires_at: session.expires_at,
+                current: session.id == current.id,
+                impersonating: session.impersonated_by.map(|_| user_name),
+            })
+            .collect(),
+    }))
+}
+
+pub async fn revoke_session(
+    NoApi(CurrentSession(current)): NoApi<CurrentSession>,
+    cookies: NoApi<Cookies>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, Response> {
+    let Ok(id) = Uuid::parse_str(&id) else {
+        return Err((StatusCode::NOT_FOUND, "Session not found").into_response());
+    };
+
+    let revoked = db_query!(
+        Session::revoke(&mut conn, current.owner_id(), id),
+        "Failed to revoke session"
+    );
+    if revoked == 0 {
+        return Err((StatusCode::NOT_FOUND, "Session not found").into_response());
+    }
+
+    if id == current.id {
+        cookies.0.add(SessionManager::remove_session_cookie());
+    }
+
+    Ok(StatusCode::OK)
+}
+
+/// Handler to sign out everywhere except the current session
+pub async fn revoke_other_sessions(
+    NoApi(CurrentSession(current)): NoApi<CurrentSession>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<RevokeSessionsResponse>, Response> {
+    let revoked = db_query!(
+        Session::revoke_others(&mut conn, current.owner_id(), current.id),
+        "Failed to revoke sessions"
+    );
+
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
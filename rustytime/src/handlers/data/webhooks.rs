@@ -0,0 +1,246 @@
+use crate::db_query;
+use crate::jobs::webhooks::{WebhookJob, enqueue_webhook};
+use crate::models::role::{Permission, Role};
+use crate::models::webhook::{
+    NewWebhook, Webhook, WebhookDelivery, WebhookEvent, generate_secret, validate_webhook_url,
+};
+use crate::state::AppState;
+use crate::utils::env::allow_private_webhook_urls;
+use crate::utils::extractors::{AuthenticatedUser, CurrentRole, DbConnection};
+use crate::utils::session::ImpersonationContext;
+use aide::NoApi;
+use axum::extract::{Path, State};
+use axum::{Extension, Json};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::error;
+
+const MAX_WEBHOOKS_PER_USER: i64 = 10;
+const DELIVERY_LOG_LIMIT: i64 = 50;
+
+/// Global webhooks are saved for the signed-in user, so an impersonating admin's role
+/// can't grant them
+fn can_create_global(role: &Role, impersonating: bool) -> bool {
+    !impersonating && role.has(Permission::GlobalWebhooks)
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct WebhookResponse {
+    pub id: i32,
+    pub url: String,
+    pub events: Vec<String>,
+    pub global: bool,
+    pub enabled: bool,
+    /// Last characters of the signing secret, to tell secrets apart
+    pub secret_hint: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        let tail: String = webhook
+            .secret
+            .chars()
+            .rev()
+            .take(4)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+
+        Self {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook.events,
+            global: webhook.global,
+            enabled: webhook.enabled,
+            secret_hint: format!("…{tail}"),
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct WebhooksResponse {
+    pub webhooks: Vec<WebhookResponse>,
+    /// Latest delivery attempts of all webhooks, newest first
+    pub deliveries: Vec<WebhookDelivery>,
+    pub events: Vec<WebhookEvent>,
+    /// Whether the user may create global webhooks
+    pub can_create_global: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct CreatedWebhookResponse {
+    pub webhook: WebhookResponse,
+    /// Signing secret, only shown once
+    pub secret: String,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Receive the events of every user, needs the `global_webhooks` permission
+    #[serde(default)]
+    pub global: bool,
+}
+
+/// Handler for listing the user's webhooks and their delivery log
+pub async fn webhooks(
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<WebhooksResponse>, Response> {
+    let webhooks = db_query!(
+        Webhook::list_for_user(&mut conn, current_user.id),
+        "Failed to fetch webhooks"
+    );
+    let deliveries = db_query!(
+        WebhookDelivery::recent_for_user(&mut conn, current_user.id, DELIVERY_LOG_LIMIT),
+        "Failed to fetch webhook deliveries"
+    );
+
+    Ok(Json(WebhooksResponse {
+        webhooks: webhooks.into_iter().map(WebhookResponse::from).collect(),
+        deliveries,
+        events: WebhookEvent::SUBSCRIBABLE.to_vec(),
+        can_create_global: can_create_global(&current_role, impersonation.is_some()),
+    }))
+}
+
+/// Handler for creating a webhook
+pub async fn create_webhook(
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<CreatedWebhookResponse>), Response> {
+    let url = validate_webhook_url(&request.url, allow_private_webhook_urls())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e).into_response())?;
+
+    let mut events: Vec<String> = WebhookEvent::SUBSCRIBABLE
+        .iter()
+        .filter(|event| request.events.contains(event))
+        .map(|event| event.as_str().to_string())
+        .collect();
+    events.dedup();
+    if events.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Select at least one event to subscribe to",
+        )
+            .into_response());
+    }
+
+    if request.global && !can_create_global(&current_role, impersonation.is_some()) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "No permission to create global webhooks",
+        )
+            .into_response());
+    }
+
+    let existing = db_query!(
+        Webhook::count_for_user(&mut conn, current_user.id),
+        "Failed to count webhooks"
+    );
+    if existing >= MAX_WEBHOOKS_PER_USER {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("You can have at most {MAX_WEBHOOKS_PER_USER} webhooks"),
+        )
+            .into_response());
+    }
+
+    let secret = generate_secret();
+    let webhook = db_query!(
+        Webhook::create(
+            &mut conn,
+            &NewWebhook {
+                user_id: current_user.id,
+                url: url.to_string(),
+                secret: secret.clone(),
+                events,
+                global: request.global,
+            },
+        ),
+        "Failed to create webhook"
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(CreatedWebhookResponse {
+            webhook: webhook.into(),
+            secret,
+        }),
+    ))
+}
+
+pub async fn delete_webhook(
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let deleted = db_query!(
+        Webhook::delete(&mut conn, current_user.id, id),
+        "Failed to delete webhook"
+    );
+
+    if deleted == 0 {
+        return Err((StatusCode::NOT_FOUND, "Webhook not found").into_response());
+    }
+
+    Ok(StatusCode::OK)
+}
+
+/// Handler for queueing a ping event to a webhook
+pub async fn test_webhook(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, Response> {
+    let webhook = db_query!(
+        Webhook::find_for_user(&mut conn, current_user.id, id),
+        "Failed to fetch webhook"
+    );
+    let Some(webhook) = webhook else {
+        return Err((StatusCode::NOT_FOUND, "Webhook not found").into_response());
+    };
+
+    let webhook_store = app_state.webhook_store.read().await;
+    let Some(ref store) = *webhook_store else {
+        error!("Webhook store not initialized");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Background jobs are not available",
+        )
+            .into_response());
+    };
+
+    let job = WebhookJob::new(
+        webhook.id,
+        WebhookEvent::Ping,
+        Some(current_user.id),
+        json!({ "message": "Hello from rustytime!" }),
+    );
+    if let Err(e) = enqueue_webhook(store, job).await {
+        error!(error = ?e, webhook_id = webhook.id, "Failed to enqueue webhook ping");
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to queue test delivery",
+        )
+            .into_response());
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -1,7 +1,8 @@
 pub mod admin;
 pub mod api;
+pub mod auth;
+pub mod badge;
 pub mod data;
-pub mod github;
 pub mod homepage;
 pub mod info;
 pub mod page;
@@ -8,20 +8,25 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
+use chrono::{DateTime, Utc};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use tower_cookies::Cookies;
+use std::sync::Arc;
 
 use crate::db_query;
 use crate::db_transaction;
-use crate::models::heartbeat::Heartbeat;
+use crate::models::audit_log::{AuditAction, AuditLogEntry, NewAuditLogEntry};
+use crate::models::heartbeat::{DurationInput, Heartbeat, SourceType, TimeRange};
+use crate::models::import_job::ImportJob;
+use crate::models::project::Project;
+use crate::models::role::{Permission, Role};
 use crate::models::session::Session;
-use crate::models::user::{PartialUser, User};
+use crate::models::user::{PartialUser, User, UserFilter, UserSort};
 use crate::state::AppState;
 use crate::tx_bail;
 use crate::utils::cache::CachedAdminStats;
-use crate::utils::extractors::{AuthenticatedUser, DbConnection};
-use crate::utils::session::{ImpersonationContext, SessionManager};
+use crate::utils::extractors::{AuthenticatedUser, CurrentRole, CurrentSession, DbConnection};
+use crate::utils::session::ImpersonationContext;
 use crate::utils::transaction::{TxOptionExt, TxResultExt};
 
 #[derive(Deserialize, JsonSchema)]
@@ -30,12 +35,29 @@ pub struct AdminQuery {
     pub limit: i64,
     #[serde(default)]
     pub offset: i64,
+    /// Part of a user name, or an exact GitHub id
+    pub search: Option<String>,
+    pub banned: Option<bool>,
+    /// Only users with (or without) a role above the default one
+    pub admin: Option<bool>,
+    pub has_imports: Option<bool>,
+    /// Only users with heartbeats in the last this many days
+    pub active_days: Option<i64>,
+    #[serde(default)]
+    pub sort: UserSort,
 }
 
 fn default_limit() -> i64 {
     50
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct ImpersonateQuery {
+    /// Allow changes while impersonating, needs the `impersonate_write` permission
+    #[serde(default)]
+    pub write: bool,
+}
+
 #[derive(Serialize, JsonSchema)]
 pub struct FormattedDailyActivity {
     pub date: String,
@@ -50,7 +72,11 @@ pub struct AdminDashboardResponse {
     pub heartbeats_last_24h: i64,
     pub requests_per_second: f64,
     pub daily_activity: Vec<FormattedDailyActivity>,
+    /// Number of users matching the filters
+    pub matching_users: i64,
     pub all_users: Vec<PartialUser>,
+    /// All roles with their ranks and permissions, highest ranked first
+    pub roles: Vec<Role>,
     pub limit: i64,
     pub offset: i64,
 }
@@ -58,33 +84,40 @@ pub struct AdminDashboardResponse {
 pub async fn admin_dashboard(
     State(app_state): State<AppState>,
     Query(query): Query<AdminQuery>,
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
 ) -> Result<Json<AdminDashboardResponse>, Response> {
-    if !current_user.is_admin() {
+    if !current_role.has(Permission::ViewUsers) {
         return Err((StatusCode::FORBIDDEN, "No permission").into_response());
     }
 
-    let include_api_key = current_user.is_owner();
+    let include_api_key = current_role.has(Permission::ViewApiKeys);
     let limit = query.limit.clamp(1, 100);
     let offset = query.offset.max(0);
 
-    let daily_activity = match app_state.cache.admin.get(&()) {
-        Some(cached) => cached.daily_activity,
+    let stats = match app_state.cache.admin.get(&()) {
+        Some(cached) => cached,
         None => {
-            let raw_daily_activity = db_query!(
-                Heartbeat::get_daily_activity_last_week(&mut conn),
+            let daily_activity = db_query!(
+                Heartbeat::get_daily_activity_last_week(&mut conn, None),
                 "Failed to fetch daily activity"
             );
+            let user_totals = db_query!(
+                Heartbeat::get_all_user_durations(&mut conn, DateTime::UNIX_EPOCH, Utc::now()),
+                "Failed to fetch user totals"
+            )
+            .into_iter()
+            .map(|row| (row.user_id, row.total_seconds))
+            .collect();
 
-            app_state.cache.admin.insert(
-                (),
-                CachedAdminStats {
-                    daily_activity: raw_daily_activity.clone(),
-                },
-            );
-
-            raw_daily_activity
+            let stats = CachedAdminStats {
+                daily_activity,
+                user_totals: Arc::new(user_totals),
+            };
+            app_state.cache.admin.insert((), stats.clone());
+            stats
         }
     };
 
@@ -93,11 +126,46 @@ pub async fn admin_dashboard(
     let heartbeats_last_hour = db_query!(Heartbeat::count_heartbeats_last_hour(&mut conn));
     let heartbeats_last_24h = db_query!(Heartbeat::count_heartbeats_last_24h(&mut conn));
 
+    let filter = UserFilter {
+        search: query
+            .search
+            .map(|search| search.trim().to_string())
+            .filter(|search| !search.is_empty()),
+        banned: query.banned,
+        admin: query.admin,
+        has_imports: query.has_imports,
+        active_days: query.active_days.map(|days| days.clamp(1, 3650)),
+    };
+
+    let matching_users = db_query!(
+        User::count_filtered(&mut conn, &filter),
+        "Failed to count users"
+    );
     let paginated_users = db_query!(
-        User::list_users_paginated(&mut conn, limit, offset),
+        User::list_filtered(
+            &mut conn,
+            &filter,
+            query.sort,
+            &stats.user_totals,
+            limit,
+            offset
+        ),
         "Failed to fetch users"
     );
 
+    if include_api_key {
+        let user_ids: Vec<i32> = paginated_users.iter().map(|user| user.id).collect();
+        let actor_id = impersonation.map_or(current_user.id, |Extension(ctx)| ctx.admin.id);
+        db_query!(
+            AuditLogEntry::record(
+                &mut conn,
+                &NewAuditLogEntry::new(actor_id, current_user.id, AuditAction::ApiKeysViewed)
+                    .metadata(serde_json::json!({ "user_ids": user_ids })),
+            ),
+            "Failed to write audit log"
+        );
+    }
+
     let partial_users = paginated_users
         .iter()
         .map(|user| PartialUser {
@@ -105,15 +173,19 @@ pub async fn admin_dashboard(
             github_id: user.github_id,
             name: user.name.clone(),
             avatar_url: user.avatar_url.clone(),
-            admin_level: user.admin_level,
+            role: user.role.clone(),
             is_banned: user.is_banned,
+            total_seconds: stats.user_totals.get(&user.id).copied().unwrap_or(0),
             api_key: include_api_key.then_some(user.api_key),
             created_at: user.created_at,
             updated_at: user.updated_at,
         })
         .collect();
 
-    let daily_activity: Vec<FormattedDailyActivity> = daily_activity
+    let roles = db_query!(Role::list(&mut conn), "Failed to fetch roles");
+
+    let daily_activity: Vec<FormattedDailyActivity> = stats
+        .daily_activity
         .into_iter()
         .map(|activity| FormattedDailyActivity {
             date: activity.date.format("%m-%d").to_string(),
@@ -129,47 +201,194 @@ pub async fn admin_dashboard(
         requests_per_second: (app_state.metrics.get_metrics().requests_per_second * 1000.0).round()
             / 1000.0,
         daily_activity,
+        matching_users,
         all_users: partial_users,
+        roles,
         limit,
         offset,
     }))
 }
 
-pub async fn impersonate_user(
-    State(app_state): State<AppState>,
-    Path(user_id): Path<i64>,
-    cookies: NoApi<Cookies>,
-    impersonation: NoApi<Option<Extension<ImpersonationContext>>>,
-    NoApi(AuthenticatedUser(session_user)): NoApi<AuthenticatedUser>,
+#[derive(Serialize, JsonSchema)]
+pub struct AdminUserHeartbeats {
+    pub today: i64,
+    pub this_week: i64,
+    pub this_month: i64,
+    pub all_time: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct AdminUserSession {
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Name of the user this session is impersonating
+    pub impersonating: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct AdminUserProject {
+    pub id: i32,
+    pub name: String,
+    pub hidden: bool,
+    pub total_seconds: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SourceTypeCount {
+    pub source_type: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct AdminUserDetailResponse {
+    pub user: PartialUser,
+    pub timezone: String,
+    pub heartbeats: AdminUserHeartbeats,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    /// Heartbeats per UTC day of the past week
+    pub daily_activity: Vec<FormattedDailyActivity>,
+    pub sessions: Vec<AdminUserSession>,
+    /// Most recent import jobs, newest first
+    pub import_jobs: Vec<ImportJob>,
+    /// Projects with their all time tracked seconds, most tracked first
+    pub projects: Vec<AdminUserProject>,
+    pub source_types: Vec<SourceTypeCount>,
+}
+
+pub async fn admin_user_detail(
+    Path(user_id): Path<i32>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
-) -> Result<StatusCode, Response> {
-    let cookies = cookies.0;
-    let impersonation = impersonation.0;
+) -> Result<Json<AdminUserDetailResponse>, Response> {
+    if !current_role.has(Permission::ViewUsers) {
+        return Err((StatusCode::FORBIDDEN, "No permission").into_response());
+    }
 
-    let Some(session_id) = SessionManager::get_session_from_cookies(&cookies) else {
-        return Err((StatusCode::UNAUTHORIZED, "Session missing").into_response());
+    let Some(user) = db_query!(User::get_by_id(&mut conn, user_id), "Failed to fetch user") else {
+        return Err((StatusCode::NOT_FOUND, "User not found").into_response());
     };
 
-    let Some(session_data) = db_query!(
-        SessionManager::validate_session(&app_state.db_pool, session_id).await,
-        "Failed to validate session"
-    ) else {
-        return Err((StatusCode::UNAUTHORIZED, "Session invalid").into_response());
-    };
+    let total_seconds = db_query!(
+        Heartbeat::get_user_duration_seconds(
+            &mut conn,
+            DurationInput {
+                user_id: Some(user.id),
+                start_date: None,
+                end_date: None,
+                project: None,
+                language: None,
+                entity: None,
+                type_filter: None,
+            }
+        ),
+        "Failed to fetch total time"
+    );
 
-    let acting_admin = if let Some(ctx) = impersonation.as_ref() {
-        ctx.0.admin.clone()
-    } else if let Some(admin_id) = session_data.impersonated_by {
-        let Some(admin) = db_query!(
-            User::get_by_id(&mut conn, admin_id),
-            "Failed to fetch impersonating admin"
-        ) else {
-            return Err((StatusCode::FORBIDDEN, "Impersonation source not found").into_response());
-        };
-        admin
-    } else {
-        session_user.clone()
+    let mut heartbeat_count = |range: TimeRange| {
+        Heartbeat::get_user_heartbeat_count_by_range(&mut conn, user.id, range, &user.timezone)
     };
+    let heartbeats = AdminUserHeartbeats {
+        today: db_query!(heartbeat_count(TimeRange::Day)),
+        this_week: db_query!(heartbeat_count(TimeRange::Week)),
+        this_month: db_query!(heartbeat_count(TimeRange::Month)),
+        all_time: db_query!(heartbeat_count(TimeRange::All)),
+    };
+
+    let last_heartbeat_at = db_query!(
+        Heartbeat::last_heartbeat_at(&mut conn, user.id),
+        "Failed to fetch last heartbeat"
+    );
+    let daily_activity = db_query!(
+        Heartbeat::get_daily_activity_last_week(&mut conn, Some(user.id)),
+        "Failed to fetch daily activity"
+    );
+    let sessions = db_query!(
+        Session::list_for_owner(&mut conn, user.id),
+        "Failed to fetch sessions"
+    );
+    let import_jobs = db_query!(
+        ImportJob::list_for_user(&mut conn, user.id, 10),
+        "Failed to fetch import jobs"
+    );
+    let mut projects = db_query!(
+        Project::list_projects_by_user_with_time(&mut conn, user.id),
+        "Failed to fetch projects"
+    );
+    projects.sort_by_key(|(_, total_seconds)| std::cmp::Reverse(*total_seconds));
+    let source_types = db_query!(
+        Heartbeat::count_by_source_type(&mut conn, user.id),
+        "Failed to fetch source types"
+    );
+
+    Ok(Json(AdminUserDetailResponse {
+        user: PartialUser {
+            id: user.id,
+            github_id: user.github_id,
+            name: user.name,
+            avatar_url: user.avatar_url,
+            api_key: None,
+            role: user.role,
+            is_banned: user.is_banned,
+            total_seconds,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        },
+        timezone: user.timezone,
+        heartbeats,
+        last_heartbeat_at,
+        daily_activity: daily_activity
+            .into_iter()
+            .map(|activity| FormattedDailyActivity {
+                date: activity.date.format("%m-%d").to_string(),
+                count: activity.count,
+            })
+            .collect(),
+        sessions: sessions
+            .into_iter()
+            .map(|(session, user_name)| AdminUserSession {
+                device: session.device,
+                ip_address: session.ip_address,
+                created_at: session.created_at,
+                last_seen_at: session.last_seen_at,
+                expires_at: session.expires_at,
+                impersonating: session.impersonated_by.map(|_| user_name),
+            })
+            .collect(),
+        import_jobs,
+        projects: projects
+            .into_iter()
+            .map(|(project, total_seconds)| AdminUserProject {
+                id: project.id,
+                name: project.name,
+                hidden: project.hidden,
+                total_seconds,
+            })
+            .collect(),
+        source_types: source_types
+            .into_iter()
+            .map(|(source_type, count)| SourceTypeCount {
+                source_type: SourceType::name(source_type).to_string(),
+                count,
+            })
+            .collect(),
+    }))
+}
+
+pub async fn impersonate_user(
+    Path(user_id): Path<i64>,
+    Query(query): Query<ImpersonateQuery>,
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
+    NoApi(AuthenticatedUser(session_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(acting_role)): NoApi<CurrentRole>,
+    NoApi(CurrentSession(session_data)): NoApi<CurrentSession>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<StatusCode, Response> {
+    let session_id = session_data.id;
+
+    let acting_admin = impersonation.map_or_else(|| session_user.clone(), |ctx| ctx.0.admin);
 
     if session_data
         .impersonated_by
@@ -179,32 +398,62 @@ pub async fn impersonate_user(
         return Err((StatusCode::FORBIDDEN, "Impersonation mismatch").into_response());
     }
 
-    if !acting_admin.is_admin() {
+    if !acting_role.has(Permission::Impersonate) {
         return Err((StatusCode::FORBIDDEN, "No permission").into_response());
     }
 
-    let updated_session = db_transaction!(conn, |conn| {
+    if query.write && !acting_role.has(Permission::ImpersonateWrite) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "No permission to impersonate with write access",
+        )
+            .into_response());
+    }
+
+    db_transaction!(conn, |conn| {
         let target_user = User::get_by_id(conn, user_id as i32)
             .db_err("Failed to fetch target user")?
             .or_not_found("User not found")?;
 
-        if acting_admin.admin_level <= target_user.admin_level && acting_admin.id != target_user.id
-        {
+        let target_role = target_user
+            .role(conn)
+            .db_err("Failed to fetch target role")?;
+        if !acting_role.outranks(&target_role) && acting_admin.id != target_user.id {
             tx_bail!(StatusCode::BAD_REQUEST, "Cannot impersonate another admin");
         }
 
         if target_user.id == acting_admin.id {
             Session::clear_impersonation(conn, session_id, &acting_admin)
-                .db_err("Failed to clear impersonation")
+                .db_err("Failed to clear impersonation")?;
+            AuditLogEntry::record(
+                conn,
+                &NewAuditLogEntry::new(
+                    acting_admin.id,
+                    session_user.id,
+                    AuditAction::ImpersonationStop,
+                )
+                .target_user(session_user.id),
+            )
+            .db_err("Failed to write audit log")?;
+            Ok(())
         } else {
-            Session::impersonate(conn, session_id, &target_user, acting_admin.id)
-                .db_err("Failed to impersonate user")
+            Session::impersonate(conn, session_id, &target_user, acting_admin.id, query.write)
+                .db_err("Failed to impersonate user")?;
+            AuditLogEntry::record(
+                conn,
+                &NewAuditLogEntry::new(
+                    acting_admin.id,
+                    acting_admin.id,
+                    AuditAction::ImpersonationStart,
+                )
+                .target_user(target_user.id)
+                .metadata(serde_json::json!({ "write": query.write })),
+            )
+            .db_err("Failed to write audit log")?;
+            Ok(())
         }
     });
 
-    let session_cookie =
-        SessionManager::create_session_cookie(updated_session.id, updated_session.expires_at);
-    cookies.add(session_cookie);
-
+    // the session keeps its id and expiry, so the cookie stays as it is
     Ok(StatusCode::OK)
 }
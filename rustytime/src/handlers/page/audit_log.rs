@@ -0,0 +1,71 @@
+use aide::NoApi;
+use axum::Json;
+use axum::extract::Query;
+use axum::{http::StatusCode, response::IntoResponse, response::Response};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::db_query;
+use crate::models::audit_log::{AuditLogEntry, AuditLogFilter, AuditLogRecord};
+use crate::models::role::Permission;
+use crate::utils::extractors::{CurrentRole, DbConnection};
+
+#[derive(Deserialize, JsonSchema)]
+pub struct AuditLogQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+    pub action: Option<String>,
+    pub actor_id: Option<i32>,
+    pub effective_user_id: Option<i32>,
+    pub target: Option<String>,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditLogRecord>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+pub async fn audit_log(
+    Query(query): Query<AuditLogQuery>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+) -> Result<Json<AuditLogResponse>, Response> {
+    if !current_role.has(Permission::ViewAuditLog) {
+        return Err((StatusCode::FORBIDDEN, "No permission").into_response());
+    }
+
+    let limit = query.limit.clamp(1, 100);
+    let offset = query.offset.max(0);
+    let filter = AuditLogFilter {
+        action: query.action.filter(|action| !action.is_empty()),
+        actor_id: query.actor_id,
+        effective_user_id: query.effective_user_id,
+        target: query.target.filter(|target| !target.is_empty()),
+    };
+
+    let total = db_query!(
+        AuditLogEntry::count(&mut conn, &filter),
+        "Failed to count audit log entries"
+    );
+
+    let entries = db_query!(
+        AuditLogEntry::list(&mut conn, &filter, limit, offset),
+        "Failed to fetch audit log"
+    );
+
+    Ok(Json(AuditLogResponse {
+        entries,
+        total,
+        limit,
+        offset,
+    }))
+}
@@ -1,6 +1,6 @@
 use crate::db_query;
 use crate::models::heartbeat::Heartbeat;
-use crate::models::heartbeat::{TimeRange, UsageStat};
+use crate::models::heartbeat::{LineChangeStats, TimeRange, UsageStat};
 use crate::state::AppState;
 use crate::utils::cache::{CachedDashboardStats, DashboardCacheKey};
 use crate::utils::extractors::{AuthenticatedUser, DbConnection};
@@ -31,6 +31,10 @@ pub struct DashboardResponse {
     editors: Vec<UsageStat>,
     operating_systems: Vec<UsageStat>,
     languages: Vec<UsageStat>,
+    machines: Vec<UsageStat>,
+    categories: Vec<UsageStat>,
+    dependencies: Vec<UsageStat>,
+    line_changes: LineChangeStats,
 }
 
 /// Handler for the dashboard page
@@ -62,40 +66,57 @@ pub async fn dashboard(
         timezone: user_timezone.clone(),
     };
 
-    let (total_heartbeats, dashboard_stats) = match app_state.cache.dashboard.get(&cache_key) {
-        Some(cached) => (cached.heartbeat_count, cached.stats),
-        None => {
-            let total_heartbeats = db_query!(
-                Heartbeat::get_user_heartbeat_count_by_range(
-                    &mut conn,
-                    session_data.user_id,
-                    query.range,
-                    &user_timezone
-                ),
-                "Database error getting heartbeat count"
-            );
+    let (total_heartbeats, dashboard_stats, dependencies) =
+        match app_state.cache.dashboard.get(&cache_key) {
+            Some(cached) => (cached.heartbeat_count, cached.stats, cached.dependencies),
+            None => {
+                let total_heartbeats = db_query!(
+                    Heartbeat::get_user_heartbeat_count_by_range(
+                        &mut conn,
+                        session_data.user_id,
+                        query.range,
+                        &user_timezone
+                    ),
+                    "Database error getting heartbeat count"
+                );
 
-            let dashboard_stats = db_query!(
-                Heartbeat::get_dashboard_stats_by_range(
-                    &mut conn,
-                    session_data.user_id,
-                    query.range,
-                    &user_timezone
-                ),
-                "Database error getting dashboard stats"
-            );
+                let dashboard_stats = db_query!(
+                    Heartbeat::get_dashboard_stats_by_range(
+                        &mut conn,
+                        session_data.user_id,
+                        query.range,
+                        &user_timezone
+                    ),
+                    "Database error getting dashboard stats"
+                );
 
-            app_state.cache.dashboard.insert(
-                cache_key,
-                CachedDashboardStats {
-                    stats: dashboard_stats.clone(),
-                    heartbeat_count: total_heartbeats,
-                },
-            );
+                let dependency_stats = db_query!(
+                    Heartbeat::get_dependency_stats_by_range(
+                        &mut conn,
+                        session_data.user_id,
+                        query.range,
+                        &user_timezone,
+                        0
+                    ),
+                    "Database error getting dependency stats"
+                );
 
-            (total_heartbeats, dashboard_stats)
-        }
-    };
+                app_state.cache.dashboard.insert(
+                    cache_key,
+                    CachedDashboardStats {
+                        stats: dashboard_stats.clone(),
+                        dependencies: dependency_stats.top_dependencies.clone(),
+                        heartbeat_count: total_heartbeats,
+                    },
+                );
+
+                (
+                    total_heartbeats,
+                    dashboard_stats,
+                    dependency_stats.top_dependencies,
+                )
+            }
+        };
 
     Ok(Json(DashboardResponse {
         total_heartbeats,
@@ -109,5 +130,9 @@ pub async fn dashboard(
         editors: dashboard_stats.top_editors,
         operating_systems: dashboard_stats.top_oses,
         languages: dashboard_stats.top_languages,
+        machines: dashboard_stats.top_machines,
+        categories: dashboard_stats.top_categories,
+        dependencies,
+        line_changes: dashboard_stats.line_changes,
     }))
 }
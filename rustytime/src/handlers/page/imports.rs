@@ -1,14 +1,17 @@
 use aide::NoApi;
-use axum::Json;
 use axum::extract::Query;
+use axum::{Extension, Json};
 use axum::{http::StatusCode, response::IntoResponse, response::Response};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::db_query;
+use crate::models::audit_log::{AuditAction, AuditLogEntry, NewAuditLogEntry};
 use crate::models::import_job::ImportJob;
 use crate::models::import_job::ImportJobWithUser;
-use crate::utils::extractors::{AuthenticatedUser, DbConnection};
+use crate::models::role::Permission;
+use crate::utils::extractors::{AuthenticatedUser, CurrentRole, DbConnection};
+use crate::utils::session::ImpersonationContext;
 
 #[derive(Deserialize, JsonSchema)]
 pub struct ImportsQuery {
@@ -32,15 +35,27 @@ pub struct AdminImportsResponse {
 
 pub async fn admin_imports(
     Query(query): Query<ImportsQuery>,
+    NoApi(impersonation): NoApi<Option<Extension<ImpersonationContext>>>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(CurrentRole(current_role)): NoApi<CurrentRole>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
 ) -> Result<Json<AdminImportsResponse>, Response> {
-    if !current_user.is_owner() {
+    if !current_role.has(Permission::ManageImports) {
         return Err((StatusCode::FORBIDDEN, "No permission").into_response());
     }
 
     let limit = query.limit.clamp(1, 100);
     let offset = query.offset.max(0);
+    let actor_id = impersonation.map_or(current_user.id, |Extension(ctx)| ctx.admin.id);
+
+    db_query!(
+        AuditLogEntry::record(
+            &mut conn,
+            &NewAuditLogEntry::new(actor_id, current_user.id, AuditAction::ImportsViewed)
+                .metadata(serde_json::json!({ "limit": limit, "offset": offset })),
+        ),
+        "Failed to write audit log"
+    );
 
     let total = db_query!(
         ImportJob::count_all(&mut conn),
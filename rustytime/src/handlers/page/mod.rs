@@ -1,4 +1,5 @@
 pub mod admin;
+pub mod audit_log;
 pub mod dashboard;
 pub mod imports;
 pub mod leaderboard;
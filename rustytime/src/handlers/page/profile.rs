@@ -1,4 +1,6 @@
 use crate::db_query;
+use crate::models::daily_total::ActivitySummary;
+use crate::models::heartbeat::{DailyTotal, LineChangeStat};
 use crate::models::user::User;
 use crate::state::AppState;
 use crate::utils::extractors::DbConnection;
@@ -26,18 +28,37 @@ pub struct UserProfileProject {
     pub total_seconds: i64,
 }
 
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct UserProfileCategory {
+    pub name: String,
+    pub total_seconds: i64,
+}
+
+#[derive(Serialize, JsonSchema, Clone)]
+pub struct UserProfileActivity {
+    /// Daily totals of the last year in the user's timezone, oldest first
+    pub days: Vec<DailyTotal>,
+    #[serde(flatten)]
+    pub summary: ActivitySummary,
+}
+
 #[derive(Serialize, JsonSchema, Clone)]
 pub struct ProfileUser {
     pub username: String,
     pub avatar_url: String,
-    pub admin_level: i16,
+    pub role: String,
 }
 
 #[derive(Serialize, JsonSchema, Clone)]
 pub struct UserProfile {
     pub user: ProfileUser,
     pub projects: Vec<UserProfileProject>,
+    pub categories: Vec<UserProfileCategory>,
+    /// Only present when the user opted in to public line stats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_changes: Option<LineChangeStat>,
     pub time: UserProfileTime,
+    pub activity: UserProfileActivity,
 }
 
 pub async fn profile_handler(
@@ -66,7 +87,7 @@ pub async fn profile_handler(
         user: ProfileUser {
             username: user_info.user.username,
             avatar_url: user_info.user.avatar_url,
-            admin_level: user_info.user.admin_level,
+            role: user_info.user.role,
         },
         projects: user_info
             .projects
@@ -77,11 +98,14 @@ pub async fn profile_handler(
                 total_seconds: p.total_seconds,
             })
             .collect(),
+        categories: user_info.categories,
+        line_changes: user_info.line_changes,
         time: UserProfileTime {
             today: user_info.time.today,
             week: user_info.time.week,
             all_time: user_info.time.all_time,
         },
+        activity: user_info.activity,
     };
 
     // store in cache
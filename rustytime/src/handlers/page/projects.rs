@@ -1,18 +1,21 @@
 use crate::db_query;
+use crate::models::heartbeat::{
+    DailyTotal, EntityPrivacy, Heartbeat, LineChangeStats, TimeRange, UsageStat,
+};
 use crate::models::project::Project as ProjectModel;
 use crate::state::AppState;
-use crate::utils::cache::ProjectsCacheKey;
+use crate::utils::cache::{ProjectDetailCacheKey, ProjectsCacheKey};
 use crate::utils::extractors::{AuthenticatedUser, DbConnection};
 use crate::utils::time::{TimeFormat, human_readable_duration};
 use aide::NoApi;
 use axum::Json;
-use axum::extract::State;
+use axum::extract::{Path, Query, State};
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, JsonSchema, Clone)]
 pub struct Project {
@@ -23,6 +26,8 @@ pub struct Project {
     pub created_at: Option<chrono::DateTime<chrono::Utc>>,
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
     pub hidden: bool,
+    /// Project override for entity privacy, None inherits the user's setting
+    pub entity_privacy: Option<EntityPrivacy>,
     pub total_seconds: i64,
     pub human_readable_total: String,
 }
@@ -32,6 +37,35 @@ pub struct ProjectsDashboardResponse {
     pub projects: Vec<Project>,
 }
 
+#[derive(Deserialize, JsonSchema)]
+pub struct ProjectDetailQuery {
+    #[serde(default)]
+    pub range: TimeRange,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct ProjectDetailResponse {
+    pub id: i32,
+    pub name: String,
+    pub project_url: Option<String>,
+    pub hidden: bool,
+    pub entity_privacy: Option<EntityPrivacy>,
+    pub range: String,
+    pub total_seconds: i64,
+    pub human_readable_total: String,
+    pub daily: Vec<DailyTotal>,
+    /// Seconds per weekday, Monday first
+    pub weekdays: Vec<i64>,
+    /// Seconds per hour of the day in the user's timezone
+    pub hours: Vec<i64>,
+    pub branches: Vec<UsageStat>,
+    pub entities: Vec<UsageStat>,
+    pub languages: Vec<UsageStat>,
+    pub editors: Vec<UsageStat>,
+    pub categories: Vec<UsageStat>,
+    pub line_changes: LineChangeStats,
+}
+
 /// Handler for the projects dashboard page
 pub async fn projects_dashboard(
     State(app_state): State<AppState>,
@@ -62,6 +96,7 @@ pub async fn projects_dashboard(
                     created_at: proj.created_at,
                     updated_at: proj.updated_at,
                     hidden: proj.hidden,
+                    entity_privacy: proj.entity_privacy.map(EntityPrivacy::from_i16),
                     total_seconds: time,
                     human_readable_total: human_readable_duration(time, TimeFormat::NoDays)
                         .human_readable,
@@ -76,3 +111,71 @@ pub async fn projects_dashboard(
 
     Ok(Json(ProjectsDashboardResponse { projects }))
 }
+
+/// Handler for the project detail page
+pub async fn project_detail(
+    State(app_state): State<AppState>,
+    NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ProjectDetailQuery>,
+) -> Result<Json<ProjectDetailResponse>, Response> {
+    // aliased projects show the project they resolve to
+    let Some(project) = db_query!(
+        ProjectModel::find_resolved_user_project(&mut conn, current_user.id, project_id),
+        "Failed to fetch project"
+    ) else {
+        return Err((StatusCode::NOT_FOUND, "Project not found").into_response());
+    };
+
+    let cache_key = ProjectDetailCacheKey {
+        user_id: current_user.id,
+        project_id: project.id,
+        range: query.range,
+        timezone: current_user.timezone.clone(),
+    };
+
+    let stats = match app_state.cache.project_detail.get(&cache_key) {
+        Some(cached) => cached,
+        None => {
+            let stats = db_query!(
+                Heartbeat::get_project_stats_by_range(
+                    &mut conn,
+                    current_user.id,
+                    project.id,
+                    query.range,
+                    &current_user.timezone
+                ),
+                "Failed to fetch project stats"
+            );
+
+            app_state
+                .cache
+                .project_detail
+                .insert(cache_key, stats.clone());
+
+            stats
+        }
+    };
+
+    Ok(Json(ProjectDetailResponse {
+        id: project.id,
+        name: project.name,
+        project_url: project.project_url,
+        hidden: project.hidden,
+        entity_privacy: project.entity_privacy.map(EntityPrivacy::from_i16),
+        range: query.range.as_str().to_string(),
+        total_seconds: stats.total_time,
+        human_readable_total: human_readable_duration(stats.total_time, TimeFormat::NoDays)
+            .human_readable,
+        daily: stats.daily,
+        weekdays: stats.weekdays,
+        hours: stats.hours,
+        branches: stats.top_branches,
+        entities: stats.top_entities,
+        languages: stats.top_languages,
+        editors: stats.top_editors,
+        categories: stats.top_categories,
+        line_changes: stats.line_changes,
+    }))
+}
@@ -1,14 +1,19 @@
 use std::env;
 
 use crate::db_query;
+use crate::models::daily_total::DailyTotalRow;
+use crate::models::heartbeat::EntityPrivacy;
+use crate::models::role::Permission;
 use crate::models::user::User;
+use crate::models::user_identity::UserIdentity;
 use crate::state::AppState;
 use crate::utils::extractors::{AuthenticatedUser, DbConnection};
-use crate::utils::session::SessionManager;
+use crate::utils::local_auth::validate_email;
+use crate::utils::session::{ImpersonationContext, SessionManager};
 use aide::NoApi;
-use axum::Json;
 use axum::extract::State;
 use axum::response::Redirect;
+use axum::{Extension, Json};
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -25,12 +30,31 @@ pub struct SettingsResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_key: Option<Uuid>,
     pub timezone: String,
+    pub public_line_stats: bool,
+    pub entity_privacy: EntityPrivacy,
+    pub private_profile: bool,
+    /// Email address the weekly digest is sent to
+    pub email: Option<String>,
+    /// Whether the email can be set here, otherwise it comes from the login provider
+    pub email_editable: bool,
+    pub weekly_digest: bool,
 }
 
 #[derive(Deserialize, JsonSchema)]
 pub struct UpdateSettingsRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_line_stats: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_privacy: Option<EntityPrivacy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_profile: Option<bool>,
+    /// Only for accounts whose profile isn't from a login provider, empty to remove it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weekly_digest: Option<bool>,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -42,7 +66,9 @@ pub struct UpdateSettingsResponse {
 pub async fn settings_page(
     State(app_state): State<AppState>,
     cookies: NoApi<Cookies>,
+    impersonation: NoApi<Option<Extension<ImpersonationContext>>>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
+    NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
 ) -> Result<Json<SettingsResponse>, Response> {
     let cookies = cookies.0;
 
@@ -64,23 +90,45 @@ pub async fn settings_page(
         );
     };
 
-    let show_api_key = session_data.impersonated_by.is_none() || current_user.is_owner();
+    // impersonating admins only see the key when their role allows viewing API keys
+    let show_api_key = session_data.impersonated_by.is_none()
+        || impersonation
+            .0
+            .is_some_and(|ctx| ctx.admin_role.has(Permission::ViewApiKeys));
+
+    let email_editable = db_query!(
+        UserIdentity::profile_is_local(&mut conn, current_user.id),
+        "Failed to load login accounts"
+    );
 
     if show_api_key {
         Ok(Json(SettingsResponse {
             api_key: Some(current_user.api_key),
             timezone: current_user.timezone,
+            public_line_stats: current_user.public_line_stats,
+            entity_privacy: EntityPrivacy::from_i16(current_user.entity_privacy),
+            private_profile: current_user.private_profile,
+            email: current_user.email,
+            email_editable,
+            weekly_digest: current_user.weekly_digest,
         }))
     } else {
         Ok(Json(SettingsResponse {
             api_key: None,
             timezone: current_user.timezone,
+            public_line_stats: current_user.public_line_stats,
+            entity_privacy: EntityPrivacy::from_i16(current_user.entity_privacy),
+            private_profile: current_user.private_profile,
+            email: current_user.email,
+            email_editable,
+            weekly_digest: current_user.weekly_digest,
         }))
     }
 }
 
 /// Handler for updating user settings
 pub async fn update_settings(
+    State(app_state): State<AppState>,
     NoApi(AuthenticatedUser(current_user)): NoApi<AuthenticatedUser>,
     NoApi(DbConnection(mut conn)): NoApi<DbConnection>,
     Json(request): Json<UpdateSettingsRequest>,
@@ -101,6 +149,116 @@ pub async fn update_settings(
             )
                 .into_response()
         })?;
+
+        // the activity rollup is kept per local day, so it has to be rebuilt
+        DailyTotalRow::clear_for_user(&mut conn, current_user.id).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update settings: {}", e),
+            )
+                .into_response()
+        })?;
+
+        app_state.cache.invalidate_user_goals(current_user.id);
+        app_state.cache.invalidate_user_profile(&current_user.name);
+    }
+
+    // update public line stats visibility if provided
+    if let Some(public_line_stats) = request.public_line_stats {
+        User::set_public_line_stats(&mut conn, current_user.id, public_line_stats).map_err(
+            |e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to update settings: {}", e),
+                )
+                    .into_response()
+            },
+        )?;
+
+        app_state.cache.invalidate_user_profile(&current_user.name);
+    }
+
+    // update entity privacy mode if provided
+    if let Some(entity_privacy) = request.entity_privacy {
+        User::set_entity_privacy(&mut conn, current_user.id, entity_privacy as i16).map_err(
+            |e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to update settings: {}", e),
+                )
+                    .into_response()
+            },
+        )?;
+
+        app_state.cache.invalidate_ingest_settings(current_user.id);
+    }
+
+    // update profile visibility if provided
+    if let Some(private_profile) = request.private_profile {
+        User::set_private_profile(&mut conn, current_user.id, private_profile).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update settings: {}", e),
+            )
+                .into_response()
+        })?;
+
+        app_state.cache.invalidate_user_profile(&current_user.name);
+        app_state.cache.invalidate_user_badges(&current_user.name);
+    }
+
+    // update email if provided, provider accounts get theirs on login
+    let mut email = current_user.email;
+    if let Some(ref new_email) = request.email {
+        let editable = UserIdentity::profile_is_local(&mut conn, current_user.id).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update settings: {}", e),
+            )
+                .into_response()
+        })?;
+        if !editable {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "Your email address comes from your login provider and can't be changed here.",
+            )
+                .into_response());
+        }
+
+        let new_email = Some(new_email.trim()).filter(|e| !e.is_empty());
+        if let Some(new_email) = new_email
+            && let Err(e) = validate_email(new_email)
+        {
+            return Err((StatusCode::BAD_REQUEST, e).into_response());
+        }
+
+        User::set_email(&mut conn, current_user.id, new_email).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update settings: {}", e),
+            )
+                .into_response()
+        })?;
+        email = new_email.map(str::to_string);
+    }
+
+    // update weekly digest subscription if provided
+    if let Some(weekly_digest) = request.weekly_digest {
+        if weekly_digest && email.is_none() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "No email address is known for your account.",
+            )
+                .into_response());
+        }
+
+        User::set_weekly_digest(&mut conn, current_user.id, weekly_digest).map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to update settings: {}", e),
+            )
+                .into_response()
+        })?;
     }
 
     Ok(Json(UpdateSettingsResponse { success: true }))
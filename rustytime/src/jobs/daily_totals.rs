@@ -0,0 +1,77 @@
+use std::str::FromStr;
+
+use apalis::{
+    layers::{WorkerBuilderExt, prometheus::PrometheusLayer},
+    prelude::{Data, WorkerBuilder},
+};
+use apalis_cron::{CronStream, Tick};
+use chrono::{Duration, Utc};
+use cron::Schedule;
+use tokio::signal::ctrl_c;
+
+use crate::db::connection::DbPool;
+use crate::models::daily_total::DailyTotalRow;
+use crate::utils::time::parse_timezone;
+
+/// How often the activity rollup is refreshed
+const REFRESH_INTERVAL_MINUTES: i64 = 5;
+
+fn refresh_daily_totals(pool: &DbPool) -> Result<(), diesel::result::Error> {
+    let mut conn = pool.get().map_err(|e| {
+        tracing::error!(error = ?e, "Failed to get connection for daily totals refresh");
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::Unknown,
+            Box::new(e.to_string()),
+        )
+    })?;
+
+    let now = Utc::now();
+    // overlap with the previous run so heartbeats ingested while it ran are not missed
+    let active_since = now - Duration::minutes(REFRESH_INTERVAL_MINUTES * 2);
+    let users = DailyTotalRow::stale_users(&mut conn, active_since)?;
+
+    let mut refreshed = 0;
+    for user in users {
+        let today = now
+            .with_timezone(&parse_timezone(&user.timezone))
+            .date_naive();
+        match DailyTotalRow::refresh_recent(&mut conn, user.user_id, &user.timezone, today) {
+            Ok(_) => refreshed += 1,
+            Err(e) => {
+                tracing::error!(error = ?e, user_id = user.user_id, "Failed to refresh daily totals");
+            }
+        }
+    }
+
+    tracing::debug!(refreshed, "Daily totals refresh complete");
+
+    Ok(())
+}
+
+async fn run_refresh(_tick: Tick, pool: Data<DbPool>) {
+    if let Err(e) = refresh_daily_totals(&pool) {
+        tracing::error!(error = ?e, "Failed to run daily totals refresh");
+    }
+}
+
+pub async fn setup(diesel_pool: DbPool) -> impl std::future::Future<Output = ()> {
+    let refresh_schedule =
+        Schedule::from_str("0 */5 * * * *").expect("valid cron: every 5 minutes");
+
+    let refresh_worker = WorkerBuilder::new("daily-totals-refresh")
+        .backend(CronStream::new(refresh_schedule))
+        .enable_tracing()
+        .layer(PrometheusLayer::default())
+        .catch_panic()
+        .data(diesel_pool)
+        .build(run_refresh);
+
+    async move {
+        tokio::select! {
+            _ = refresh_worker.run() => {}
+            _ = ctrl_c() => {
+                tracing::info!("Shutting down daily totals workers");
+            }
+        }
+    }
+}
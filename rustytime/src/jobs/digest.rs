@@ -0,0 +1,198 @@
+use std::str::FromStr;
+
+use apalis::{
+    layers::{WorkerBuilderExt, prometheus::PrometheusLayer},
+    prelude::{Data, WorkerBuilder},
+};
+use apalis_cron::{CronStream, Tick};
+use chrono::{Datelike, Duration, NaiveDate, Timelike, Utc, Weekday};
+use cron::Schedule;
+use diesel::prelude::*;
+use tokio::signal::ctrl_c;
+use tracing::{debug, error, info};
+
+use crate::db::connection::DbPool;
+use crate::models::heartbeat::{DurationInput, Heartbeat};
+use crate::models::project::Project;
+use crate::models::user::User;
+use crate::utils::digest::{DigestStat, MAX_DIGEST_ITEMS, WeeklyDigest};
+use crate::utils::mailer::{MailError, Mailer};
+use crate::utils::time::{get_day_start_utc, get_week_start, parse_timezone};
+
+/// Digests go out on monday morning in the user's timezone
+const DIGEST_WEEKDAY: Weekday = Weekday::Mon;
+const DIGEST_HOUR: u32 = 8;
+
+fn site_url() -> String {
+    std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string())
+}
+
+fn week_seconds(
+    conn: &mut PgConnection,
+    user_id: i32,
+    start: NaiveDate,
+    tz: chrono_tz::Tz,
+) -> QueryResult<i64> {
+    Heartbeat::get_user_duration_seconds(
+        conn,
+        DurationInput {
+            user_id: Some(user_id),
+            start_date: Some(get_day_start_utc(start, tz)),
+            end_date: Some(get_day_start_utc(start + Duration::days(7), tz)),
+            project: None,
+            language: None,
+            entity: None,
+            type_filter: None,
+        },
+    )
+}
+
+/// Build the digest of the week starting at `week_start`, compared with the week before it
+pub fn build_digest(
+    conn: &mut PgConnection,
+    user: &User,
+    week_start: NaiveDate,
+) -> QueryResult<WeeklyDigest> {
+    let tz = parse_timezone(&user.timezone);
+    let start = get_day_start_utc(week_start, tz);
+    let end = get_day_start_utc(week_start + Duration::days(7), tz);
+
+    let total_seconds = week_seconds(conn, user.id, week_start, tz)?;
+    let previous_total_seconds = week_seconds(conn, user.id, week_start - Duration::days(7), tz)?;
+
+    let projects =
+        Project::top_projects_by_range(conn, user.id, start, end, MAX_DIGEST_ITEMS as i32)?
+            .into_iter()
+            .map(|p| DigestStat {
+                name: p.name,
+                total_seconds: p.total_seconds,
+            })
+            .collect();
+    let languages =
+        Heartbeat::top_languages_by_range(conn, user.id, start, end, MAX_DIGEST_ITEMS as i32)?
+            .into_iter()
+            .map(|l| DigestStat {
+                name: l.name,
+                total_seconds: l.total_seconds,
+            })
+            .collect();
+
+    Ok(WeeklyDigest {
+        username: user.name.clone(),
+        week_start,
+        total_seconds,
+        previous_total_seconds,
+        projects,
+        languages,
+    })
+}
+
+/// Render and send a digest to an address
+pub async fn send_digest(
+    mailer: &Mailer,
+    to: &str,
+    digest: &WeeklyDigest,
+    site_url: &str,
+) -> Result<(), MailError> {
+    let rendered = digest.render(site_url);
+    mailer
+        .send(to, &rendered.subject, rendered.text, rendered.html)
+        .await
+}
+
+/// The local day a digest is due for the user, if it is due and not sent yet
+fn digest_due_on(user: &User) -> Option<NaiveDate> {
+    let local_now = Utc::now().with_timezone(&parse_timezone(&user.timezone));
+    let today = local_now.date_naive();
+
+    let due = today.weekday() == DIGEST_WEEKDAY && local_now.hour() >= DIGEST_HOUR;
+    let already_sent = user.last_digest_sent_on.is_some_and(|sent| sent >= today);
+
+    (due && !already_sent).then_some(today)
+}
+
+async fn send_weekly_digests(_tick: Tick, pool: Data<DbPool>, mailer: Data<Mailer>) {
+    let due = (|| {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        let recipients = User::list_digest_recipients(&mut conn).map_err(|e| e.to_string())?;
+        let mut due = Vec::new();
+
+        for user in recipients {
+            let Some(today) = digest_due_on(&user) else {
+                continue;
+            };
+            let Some(email) = user.email.clone() else {
+                continue;
+            };
+
+            let last_week = get_week_start(today) - Duration::days(7);
+            match build_digest(&mut conn, &user, last_week) {
+                Ok(digest) => due.push((user.id, email, today, digest)),
+                // skip this user so the rest of the batch still gets their digest
+                Err(e) => error!(error = %e, user_id = user.id, "Failed to build weekly digest"),
+            }
+        }
+
+        Ok::<_, String>(due)
+    })();
+
+    let due = match due {
+        Ok(due) => due,
+        Err(e) => {
+            error!(error = %e, "Failed to build weekly digests");
+            return;
+        }
+    };
+
+    let site_url = site_url();
+    for (user_id, email, today, digest) in due {
+        // a week without any activity is not worth an email, but still counts as handled
+        if digest.has_activity()
+            && let Err(e) = send_digest(&mailer, &email, &digest, &site_url).await
+        {
+            error!(error = %e, user_id, "Failed to send weekly digest");
+            continue;
+        }
+
+        let marked = pool.get().map_err(|e| e.to_string()).and_then(|mut conn| {
+            User::mark_digest_sent(&mut conn, user_id, today).map_err(|e| e.to_string())
+        });
+        match marked {
+            Ok(_) => debug!(user_id, "Weekly digest handled"),
+            Err(e) => error!(error = %e, user_id, "Failed to mark weekly digest as sent"),
+        }
+    }
+}
+
+pub async fn setup(diesel_pool: DbPool) -> impl std::future::Future<Output = ()> {
+    let digest_schedule = Schedule::from_str("0 15 * * * *").expect("valid cron: every hour");
+
+    let mailer = Mailer::from_env();
+    if mailer.is_none() {
+        info!("SMTP_URL is not set, weekly digest emails are disabled");
+    }
+
+    let digest_worker = mailer.map(|mailer| {
+        WorkerBuilder::new("weekly-digest")
+            .backend(CronStream::new(digest_schedule))
+            .enable_tracing()
+            .layer(PrometheusLayer::default())
+            .catch_panic()
+            .data(diesel_pool)
+            .data(mailer)
+            .build(send_weekly_digests)
+    });
+
+    async move {
+        let Some(digest_worker) = digest_worker else {
+            return;
+        };
+
+        tokio::select! {
+            _ = digest_worker.run() => {}
+            _ = ctrl_c() => {
+                tracing::info!("Shutting down digest workers");
+            }
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use apalis::{
+    layers::{WorkerBuilderExt, prometheus::PrometheusLayer},
+    prelude::{
+        BackoffConfig, BoxDynError, Data, IntervalStrategy, StrategyBuilder, TaskSink,
+        WorkerBuilder,
+    },
+};
+use apalis_postgres::PostgresStorage;
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use futures::{FutureExt, TryFutureExt};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tokio::signal::ctrl_c;
+use tracing::{error, info, info_span, warn};
+
+use crate::db::connection::DbPool;
+use crate::jobs::import::JsonCodec;
+use crate::models::language_mapping::{
+    LanguageOverrides, clear_pending_normalization, pending_normalizations,
+};
+use crate::models::project::{Project, invalidate_project_id_cache};
+use crate::models::project_rule::ProjectRuleSet;
+use crate::models::user::User;
+use crate::state::AppState;
+use crate::utils::cache::AppCache;
+
+/// Background jobs that rewrite a user's stored heartbeats
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HistoryJob {
+    ApplyProjectRules {
+        user_id: i32,
+    },
+    NormalizeLanguages {
+        user_id: i32,
+    },
+    RenameProject {
+        user_id: i32,
+        project_id: i32,
+        name: String,
+    },
+    MergeProjects {
+        user_id: i32,
+        source_id: i32,
+        target_id: i32,
+    },
+}
+
+impl HistoryJob {
+    fn user_id(&self) -> i32 {
+        match self {
+            HistoryJob::ApplyProjectRules { user_id }
+            | HistoryJob::NormalizeLanguages { user_id }
+            | HistoryJob::RenameProject { user_id, .. }
+            | HistoryJob::MergeProjects { user_id, .. } => *user_id,
+        }
+    }
+}
+
+/// Run a history job, returning the number of updated heartbeats and the user's name
+fn execute_history_job(pool: &DbPool, job: &HistoryJob) -> Result<(usize, Option<String>), String> {
+    let mut conn = pool.get().map_err(|e| e.to_string())?;
+    let user_id = job.user_id();
+
+    let updated = match job {
+        HistoryJob::ApplyProjectRules { .. } => ProjectRuleSet::load(&mut conn, user_id)
+            .and_then(|rules| rules.apply_to_history(&mut conn, user_id)),
+        HistoryJob::NormalizeLanguages { .. } => LanguageOverrides::load(&mut conn, user_id)
+            .and_then(|overrides| overrides.apply_to_history(&mut conn, user_id)),
+        HistoryJob::RenameProject {
+            project_id, name, ..
+        } => Project::rename(&mut conn, user_id, *project_id, name),
+        HistoryJob::MergeProjects {
+            source_id,
+            target_id,
+            ..
+        } => Project::merge_into(&mut conn, user_id, *source_id, *target_id),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let username = User::get_by_id(&mut conn, user_id)
+        .map_err(|e| e.to_string())?
+        .map(|user| user.name);
+
+    Ok((updated, username))
+}
+
+async fn run_history_job(
+    job: HistoryJob,
+    pool: Data<DbPool>,
+    cache: Data<AppCache>,
+) -> Result<String, BoxDynError> {
+    let user_id = job.user_id();
+
+    let span = info_span!("history_job", user_id = user_id);
+    let _guard = span.enter();
+
+    let pool = (*pool).clone();
+    let task = job.clone();
+    let result = tokio::task::spawn_blocking(move || execute_history_job(&pool, &task)).await?;
+
+    match result {
+        Ok((updated, username)) => {
+            invalidate_project_id_cache(user_id);
+            cache.invalidate_user_dashboard(user_id);
+            cache.invalidate_user_projects(user_id);
+            cache.invalidate_ingest_settings(user_id);
+            cache.invalidate_user_goals(user_id);
+            if let Some(username) = username {
+                cache.invalidate_user_profile(&username);
+                cache.invalidate_user_badges(&username);
+            }
+
+            info!(updated, job = ?job, "History job completed");
+            Ok(format!(
+                "History job completed: {} heartbeats updated",
+                updated
+            ))
+        }
+        Err(error_message) => {
+            error!(error = %error_message, job = ?job, "History job failed");
+            Err(error_message.into())
+        }
+    }
+}
+
+pub type HistoryStore = PostgresStorage<HistoryJob, Vec<u8>, JsonCodec, apalis_postgres::PgNotify>;
+
+pub async fn create_storage(sqlx_pool: &PgPool) -> HistoryStore {
+    let storage_config = apalis_postgres::Config::new("history_jobs").with_poll_interval(
+        StrategyBuilder::new()
+            .apply(
+                IntervalStrategy::new(Duration::from_secs(5))
+                    .with_backoff(BackoffConfig::default()),
+            )
+            .build(),
+    );
+
+    PostgresStorage::new_with_notify(sqlx_pool, &storage_config).with_codec::<JsonCodec>()
+}
+
+pub async fn enqueue_history_job(
+    storage: &HistoryStore,
+    job: HistoryJob,
+) -> Result<(), BoxDynError> {
+    let mut storage = storage.clone();
+    storage.push(job).await?;
+    Ok(())
+}
+
+/// Queue language normalization for the users flagged by migrations
+pub async fn enqueue_pending_normalizations(
+    storage: &HistoryStore,
+    pool: &DbPool,
+) -> Result<usize, BoxDynError> {
+    let mut conn = pool.get()?;
+    let user_ids = pending_normalizations(&mut conn)?;
+
+    for &user_id in &user_ids {
+        enqueue_history_job(storage, HistoryJob::NormalizeLanguages { user_id }).await?;
+        if let Err(e) = clear_pending_normalization(&mut conn, user_id) {
+            warn!(error = ?e, user_id, "Failed to clear pending language normalization");
+        }
+    }
+
+    Ok(user_ids.len())
+}
+
+/// Enqueue a history job from a request handler, mapping failures to a response
+pub async fn start_history_job(app_state: &AppState, job: HistoryJob) -> Result<(), Response> {
+    let history_store = app_state.history_store.read().await;
+    let Some(ref store) = *history_store else {
+        error!("History store not initialized");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Background jobs are not available",
+        )
+            .into_response());
+    };
+
+    let user_id = job.user_id();
+    if let Err(e) = enqueue_history_job(store, job).await {
+        error!(error = ?e, user_id, "Failed to enqueue history job");
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to start background job",
+        )
+            .into_response());
+    }
+
+    Ok(())
+}
+
+pub async fn setup(
+    history_store: HistoryStore,
+    diesel_pool: DbPool,
+    cache: AppCache,
+) -> impl std::future::Future<Output = ()> {
+    WorkerBuilder::new("history-worker")
+        .backend(history_store)
+        .enable_tracing()
+        .layer(PrometheusLayer::default())
+        .catch_panic()
+        .concurrency(1)
+        .data(diesel_pool)
+        .data(cache)
+        .build(run_history_job)
+        .run_until(ctrl_c())
+        .map_err(|e| tracing::error!("History worker error: {}", e))
+        .map(|_| ())
+}
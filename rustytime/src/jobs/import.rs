@@ -12,15 +12,18 @@ use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, SecondsFormat, Utc
 use futures::{FutureExt, TryFutureExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
-use serde_json::{Value, from_str};
+use serde_json::{Value, from_str, json};
 use sqlx::PgPool;
 use tokio::signal::ctrl_c;
 use tracing::{debug, error, info, info_span, warn};
 
 use crate::db::connection::DbPool;
 use crate::handlers::api::user::store_heartbeats_in_db_count_only;
-use crate::models::heartbeat::{HackatimeHeartbeat, NewHeartbeat};
+use crate::jobs::webhooks::{WebhookStore, dispatch};
+use crate::models::daily_total::DailyTotalRow;
+use crate::models::heartbeat::{HackatimeHeartbeat, IngestSettings, NewHeartbeat};
 use crate::models::import_job::ImportJob as ImportJobModel;
+use crate::models::webhook::WebhookEvent;
 use crate::utils::time::{determine_range, format_rfc3339, split_range_midpoint};
 
 const HACKATIME_HEARTBEATS_ENDPOINT: &str = "https://hackatime.hackclub.com/api/v1/my/heartbeats";
@@ -99,7 +102,11 @@ enum HackatimeFetchError {
     },
 }
 
-async fn run_import(job: ImportJob, pool: Data<DbPool>) -> Result<String, BoxDynError> {
+async fn run_import(
+    job: ImportJob,
+    pool: Data<DbPool>,
+    webhook_store: Data<WebhookStore>,
+) -> Result<String, BoxDynError> {
     let started = std::time::Instant::now();
     let user_id = job.user_id;
     let job_id = job.job_id;
@@ -123,6 +130,11 @@ async fn run_import(job: ImportJob, pool: Data<DbPool>) -> Result<String, BoxDyn
 
     let conn = &mut *pool.get().expect("Failed to get DB connection from pool");
 
+    // imported heartbeats land in the past, so the activity rollup has to be rebuilt
+    if let Err(e) = DailyTotalRow::clear_for_user(conn, user_id) {
+        error!(error = ?e, "Failed to clear daily totals after import");
+    }
+
     match result {
         Ok((imported, processed, requests, earliest_requested)) => {
             let start_date = earliest_requested
@@ -149,6 +161,21 @@ async fn run_import(job: ImportJob, pool: Data<DbPool>) -> Result<String, BoxDyn
                 "Import job completed successfully"
             );
 
+            dispatch(
+                &webhook_store,
+                &pool,
+                WebhookEvent::ImportCompleted,
+                user_id,
+                json!({
+                    "job_id": job_id,
+                    "imported": imported,
+                    "processed": processed,
+                    "requests": requests,
+                    "elapsed_seconds": elapsed.as_secs_f64(),
+                }),
+            )
+            .await;
+
             Ok(format!(
                 "Import completed: {} imported, {} processed in {:.2}s",
                 imported,
@@ -162,6 +189,16 @@ async fn run_import(job: ImportJob, pool: Data<DbPool>) -> Result<String, BoxDyn
             }
 
             error!(error = %error_message, "Import job failed");
+
+            dispatch(
+                &webhook_store,
+                &pool,
+                WebhookEvent::ImportFailed,
+                user_id,
+                json!({ "job_id": job_id, "error": error_message }),
+            )
+            .await;
+
             Err(error_message.into())
         }
     }
@@ -180,6 +217,14 @@ async fn execute_import(
     let mut requests_made = 0usize;
     let mut earliest_requested: Option<DateTime<Utc>> = None;
 
+    let settings = {
+        let mut conn = db_pool
+            .get()
+            .map_err(|e| format!("Failed to get DB connection: {e}"))?;
+        IngestSettings::load(&mut conn, user_id)
+            .map_err(|e| format!("Failed to load ingest settings: {e}"))?
+    };
+
     while period_end > cutoff {
         let (range_start, next_period_end) =
             determine_range(period_end, cutoff, Some(BROAD_SEARCH_YEAR));
@@ -208,7 +253,7 @@ async fn execute_import(
 
             let mut chunked_heartbeats = Vec::with_capacity(HEARTBEAT_IMPORT_BATCH_SIZE);
             for hb in heartbeats {
-                chunked_heartbeats.push(hb.to_new_heartbeat(user_id));
+                chunked_heartbeats.push(hb.to_new_heartbeat(user_id, &settings));
                 if chunked_heartbeats.len() == HEARTBEAT_IMPORT_BATCH_SIZE {
                     match persist_heartbeat_chunk(db_pool, &mut chunked_heartbeats).await {
                         Ok(inserted) => total_inserted += inserted,
@@ -518,6 +563,7 @@ pub async fn enqueue_import(
 pub async fn setup(
     import_store: ImportStore,
     diesel_pool: DbPool,
+    webhook_store: WebhookStore,
 ) -> impl std::future::Future<Output = ()> {
     WorkerBuilder::new("import-worker")
         .backend(import_store)
@@ -526,6 +572,7 @@ pub async fn setup(
         .catch_panic()
         .concurrency(2)
         .data(diesel_pool)
+        .data(webhook_store)
         .build(run_import)
         .run_until(ctrl_c())
         .map_err(|e| tracing::error!("Import worker error: {}", e))
@@ -7,14 +7,17 @@ use apalis::{
 use apalis_cron::{CronStream, Tick};
 use chrono::{NaiveDate, Utc};
 use cron::Schedule;
+use serde_json::json;
 use tokio::signal::ctrl_c;
 
 use axum_prometheus::metrics;
 
 use crate::db::connection::DbPool;
 use crate::db_transaction_result;
+use crate::jobs::webhooks::{WebhookStore, dispatch_to};
 use crate::models::heartbeat::Heartbeat;
-use crate::models::leaderboard::{Leaderboard, NewLeaderboard};
+use crate::models::leaderboard::{Leaderboard, NewLeaderboard, RankChange, rank_changes};
+use crate::models::webhook::{Webhook, WebhookEvent};
 use crate::utils::time::get_week_start;
 
 const DAILY_RETENTION_DAYS: i64 = 30;
@@ -93,6 +96,81 @@ async fn regenerate_all_time_leaderboard(_tick: Tick, pool: Data<DbPool>) {
     );
 }
 
+/// Send rank changes since the last notification of every current leaderboard.
+/// Runs once a day so users trading places between regenerations don't get a delivery per swap
+async fn notify_rank_changes(_tick: Tick, pool: Data<DbPool>, store: Data<WebhookStore>) {
+    let today = Utc::now().date_naive();
+    let periods = [
+        ("daily", today),
+        ("weekly", get_week_start(today)),
+        ("all_time", NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()),
+    ];
+
+    let webhooks = rank_change_webhooks(&pool);
+
+    for (period_type, period_date) in periods {
+        let changes = match take_rank_changes(&pool, period_type, period_date) {
+            Ok(changes) => changes,
+            Err(e) => {
+                tracing::error!(error = ?e, period = period_type, "Failed to load rank changes");
+                continue;
+            }
+        };
+
+        for change in changes {
+            let data = json!({
+                "period": period_type,
+                "period_date": period_date,
+                "previous_rank": change.previous_rank,
+                "rank": change.rank,
+                "total_seconds": change.total_seconds,
+            });
+            dispatch_to(
+                &store,
+                &webhooks,
+                WebhookEvent::LeaderboardRankChanged,
+                change.user_id,
+                &data,
+            )
+            .await;
+        }
+    }
+}
+
+/// Webhooks to notify about rank changes
+fn rank_change_webhooks(pool: &DbPool) -> Vec<Webhook> {
+    let webhooks = pool.get().map_err(|e| e.to_string()).and_then(|mut conn| {
+        Webhook::subscribed_to(&mut conn, WebhookEvent::LeaderboardRankChanged)
+            .map_err(|e| e.to_string())
+    });
+
+    webhooks.unwrap_or_else(|e| {
+        tracing::error!(error = %e, "Failed to load rank change webhooks");
+        Vec::new()
+    })
+}
+
+/// Rank changes of a leaderboard since the last notification, marking the current ranks as notified
+fn take_rank_changes(
+    pool: &DbPool,
+    period_type: &str,
+    period_date: NaiveDate,
+) -> Result<Vec<RankChange>, diesel::result::Error> {
+    let mut conn = pool.get().map_err(|e| {
+        tracing::error!(error = ?e, "Failed to get connection");
+        diesel::result::Error::DatabaseError(
+            diesel::result::DatabaseErrorKind::Unknown,
+            Box::new(e.to_string()),
+        )
+    })?;
+
+    conn.build_transaction().run(|conn| {
+        let entries = Leaderboard::get_by_period(conn, period_type, period_date)?;
+        Leaderboard::mark_notified(conn, period_type, period_date)?;
+        Ok(rank_changes(&entries))
+    })
+}
+
 fn regenerate_leaderboard_period(
     pool: &DbPool,
     period_type: &str,
@@ -144,6 +222,7 @@ fn regenerate_leaderboard_period(
                 period_date,
                 total_seconds: row.total_seconds,
                 rank: (idx + 1) as i32,
+                notified_rank: (idx + 1) as i32,
             })
             .collect();
 
@@ -186,18 +265,24 @@ async fn run_cleanup(_tick: Tick, pool: Data<DbPool>) {
     }
 }
 
-pub async fn setup(diesel_pool: DbPool) -> impl std::future::Future<Output = ()> {
+pub async fn setup(
+    diesel_pool: DbPool,
+    webhook_store: WebhookStore,
+) -> impl std::future::Future<Output = ()> {
     let daily_schedule = Schedule::from_str("0 */5 * * * *").expect("valid cron: every 5 minutes");
     let weekly_schedule = Schedule::from_str("0 0 * * * *").expect("valid cron: every hour");
     let all_time_schedule =
         Schedule::from_str("0 0 0 * * *").expect("valid cron: daily at midnight");
     let cleanup_schedule =
         Schedule::from_str("0 10 0 * * *").expect("valid cron: daily at 00:10 AM");
+    let notify_schedule =
+        Schedule::from_str("0 55 23 * * *").expect("valid cron: daily at 11:55 PM");
 
     let daily_pool = diesel_pool.clone();
     let weekly_pool = diesel_pool.clone();
     let all_time_pool = diesel_pool.clone();
-    let cleanup_pool = diesel_pool;
+    let cleanup_pool = diesel_pool.clone();
+    let notify_pool = diesel_pool;
 
     let daily_worker = WorkerBuilder::new("leaderboard-daily")
         .backend(CronStream::new(daily_schedule))
@@ -227,12 +312,21 @@ pub async fn setup(diesel_pool: DbPool) -> impl std::future::Future<Output = ()>
         .data(cleanup_pool)
         .build(run_cleanup);
 
+    let notify_worker = WorkerBuilder::new("leaderboard-notify")
+        .backend(CronStream::new(notify_schedule))
+        .enable_tracing()
+        .catch_panic()
+        .data(notify_pool)
+        .data(webhook_store)
+        .build(notify_rank_changes);
+
     async move {
         tokio::select! {
             _ = daily_worker.run() => {}
             _ = weekly_worker.run() => {}
             _ = all_time_worker.run() => {}
             _ = cleanup_worker.run() => {}
+            _ = notify_worker.run() => {}
             _ = ctrl_c() => {
                 tracing::info!("Shutting down leaderboard workers");
             }
@@ -1,6 +1,10 @@
+mod daily_totals;
+pub mod digest;
+pub mod history;
 pub mod import;
 mod leaderboard;
 mod sessions;
+pub mod webhooks;
 
 use apalis_postgres::PostgresStorage;
 use axum_prometheus::metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
@@ -8,6 +12,7 @@ use sqlx::PgPool;
 use std::future::Future;
 
 use crate::db::connection::DbPool;
+use crate::utils::cache::AppCache;
 
 pub fn install_metrics_recorder() -> PrometheusHandle {
     PrometheusBuilder::new()
@@ -18,23 +23,47 @@ pub fn install_metrics_recorder() -> PrometheusHandle {
 pub async fn setup_jobs(
     sqlx_pool: PgPool,
     diesel_pool: DbPool,
+    cache: AppCache,
 ) -> (
+    impl Future<Output = ()>,
+    impl Future<Output = ()>,
+    impl Future<Output = ()>,
+    impl Future<Output = ()>,
     impl Future<Output = ()>,
     impl Future<Output = ()>,
     impl Future<Output = ()>,
     import::ImportStore,
+    history::HistoryStore,
+    webhooks::WebhookStore,
 ) {
     PostgresStorage::setup(&sqlx_pool).await.unwrap();
 
+    let webhook_store = webhooks::create_storage(&sqlx_pool).await;
+    let webhook_worker = webhooks::setup(webhook_store.clone(), diesel_pool.clone()).await;
     let import_store = import::create_storage(&sqlx_pool).await;
-    let leaderboard_worker = leaderboard::setup(diesel_pool.clone()).await;
-    let import_worker = import::setup(import_store.clone(), diesel_pool.clone()).await;
+    let leaderboard_worker = leaderboard::setup(diesel_pool.clone(), webhook_store.clone()).await;
+    let import_worker = import::setup(
+        import_store.clone(),
+        diesel_pool.clone(),
+        webhook_store.clone(),
+    )
+    .await;
+    let history_store = history::create_storage(&sqlx_pool).await;
+    let history_worker = history::setup(history_store.clone(), diesel_pool.clone(), cache).await;
+    let digest_worker = digest::setup(diesel_pool.clone()).await;
+    let daily_totals_worker = daily_totals::setup(diesel_pool.clone()).await;
     let sessions_worker = sessions::setup(diesel_pool).await;
 
     (
         leaderboard_worker,
         import_worker,
         sessions_worker,
+        history_worker,
+        webhook_worker,
+        digest_worker,
+        daily_totals_worker,
         import_store,
+        history_store,
+        webhook_store,
     )
 }
@@ -24,10 +24,9 @@ fn cleanup_expired_sessions(pool: &DbPool) -> Result<(), diesel::result::Error>
     })?;
 
     db_transaction_result!(conn, |conn| {
-        let scrubbed = Session::scrub_expired(conn)?;
         let deleted = Session::delete_stale(conn, SESSION_RETENTION_DAYS)?;
 
-        tracing::debug!(scrubbed, deleted, "Session cleanup complete");
+        tracing::debug!(deleted, "Session cleanup complete");
 
         Ok(())
     })
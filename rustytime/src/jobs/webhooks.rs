@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use apalis::{
+    layers::{
+        WorkerBuilderExt,
+        prometheus::PrometheusLayer,
+        retry::{BackoffRetryPolicy, RetryPolicy},
+    },
+    prelude::{
+        Attempt, BackoffConfig, BoxDynError, Data, IntervalStrategy, StrategyBuilder, TaskSink,
+        WorkerBuilder,
+    },
+};
+use apalis_cron::{CronStream, Tick};
+use apalis_postgres::PostgresStorage;
+use axum::http::StatusCode;
+use chrono::{Duration as ChronoDuration, NaiveDate, SecondsFormat, Timelike, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use futures::{FutureExt, TryFutureExt};
+use reqwest::Client;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use tokio::signal::ctrl_c;
+use tower::retry::backoff::{ExponentialBackoff, ExponentialBackoffMaker, MakeBackoff};
+use tower::util::rng::HasherRng;
+use tracing::{debug, error, warn};
+use uuid::Uuid;
+
+use crate::db::connection::DbPool;
+use crate::jobs::import::JsonCodec;
+use crate::models::goal::Goal;
+use crate::models::heartbeat::{DurationInput, Heartbeat};
+use crate::models::user::User;
+use crate::models::webhook::{
+    NewWebhookDelivery, Webhook, WebhookDelivery, WebhookEvent, is_private_ip, sign_payload,
+    validate_webhook_url,
+};
+use crate::utils::env::allow_private_webhook_urls;
+use crate::utils::time::{
+    format_hour_minute, get_day_end_utc, get_day_start_utc, get_today_in_timezone, parse_timezone,
+};
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Retries after the first attempt, waiting roughly 10s, 20s, 40s, 80s and 160s
+const MAX_RETRIES: usize = 5;
+const MIN_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+const MAX_ERROR_LENGTH: usize = 500;
+const DELIVERY_RETENTION_DAYS: i64 = 14;
+
+/// A signed event waiting to be delivered to a single webhook
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WebhookJob {
+    pub webhook_id: i32,
+    pub event: WebhookEvent,
+    /// Full JSON body sent to the receiver
+    pub payload: Value,
+}
+
+impl WebhookJob {
+    /// Wrap event data in the envelope every delivery shares
+    pub fn new(webhook_id: i32, event: WebhookEvent, user_id: Option<i32>, data: Value) -> Self {
+        Self {
+            webhook_id,
+            event,
+            payload: json!({
+                "id": Uuid::new_v4().to_string(),
+                "event": event.as_str(),
+                "created_at": Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                "user_id": user_id,
+                "data": data,
+            }),
+        }
+    }
+
+    fn delivery_id(&self) -> &str {
+        self.payload["id"].as_str().unwrap_or_default()
+    }
+}
+
+pub type WebhookStore = PostgresStorage<WebhookJob, Vec<u8>, JsonCodec, apalis_postgres::PgNotify>;
+
+pub async fn create_storage(sqlx_pool: &PgPool) -> WebhookStore {
+    let storage_config = apalis_postgres::Config::new("webhook_deliveries").with_poll_interval(
+        StrategyBuilder::new()
+            .apply(
+                IntervalStrategy::new(Duration::from_secs(5))
+                    .with_backoff(BackoffConfig::default()),
+            )
+            .build(),
+    );
+
+    PostgresStorage::new_with_notify(sqlx_pool, &storage_config).with_codec::<JsonCodec>()
+}
+
+pub async fn enqueue_webhook(store: &WebhookStore, job: WebhookJob) -> Result<(), BoxDynError> {
+    let mut store = store.clone();
+    store.push(job).await?;
+    Ok(())
+}
+
+/// Queue an event about `user_id` for the given webhooks that receive it
+pub async fn dispatch_to(
+    store: &WebhookStore,
+    webhooks: &[Webhook],
+    event: WebhookEvent,
+    user_id: i32,
+    data: &Value,
+) {
+    for webhook in webhooks.iter().filter(|w| w.receives(event, user_id)) {
+        let job = WebhookJob::new(webhook.id, event, Some(user_id), data.clone());
+        if let Err(e) = enqueue_webhook(store, job).await {
+            error!(error = ?e, webhook_id = webhook.id, event = event.as_str(), "Failed to enqueue webhook");
+        }
+    }
+}
+
+/// Queue an event about `user_id` for every webhook that receives it
+pub async fn dispatch(
+    store: &WebhookStore,
+    pool: &DbPool,
+    event: WebhookEvent,
+    user_id: i32,
+    data: Value,
+) {
+    let webhooks = match pool.get() {
+        Ok(mut conn) => Webhook::subscribed_to(&mut conn, event),
+        Err(e) => {
+            error!(error = ?e, "Failed to get DB connection for webhooks");
+            return;
+        }
+    };
+
+    match webhooks {
+        Ok(webhooks) => dispatch_to(store, &webhooks, event, user_id, &data).await,
+        Err(e) => error!(error = ?e, event = event.as_str(), "Failed to load webhooks"),
+    }
+}
+
+/// Resolves receiver hosts for the webhook client, refusing hosts with private addresses unless
+/// they are allowed. The checked addresses are the ones connected to, so a host cannot resolve
+/// to a public address for the check and a private one for the request.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if !allow_private_webhook_urls()
+                && addresses.iter().any(|address| is_private_ip(address.ip()))
+            {
+                return Err("Host resolves to a private address".into());
+            }
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Send a signed payload, returning the receiver's status code
+pub async fn send_webhook(
+    client: &Client,
+    url: &str,
+    secret: &str,
+    event: WebhookEvent,
+    delivery_id: &str,
+    body: &[u8],
+) -> Result<StatusCode, String> {
+    let parsed = validate_webhook_url(url, allow_private_webhook_urls())?;
+
+    let timestamp = Utc::now().timestamp();
+    let response = client
+        .post(parsed)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header("X-Rustytime-Event", event.as_str())
+        .header("X-Rustytime-Delivery", delivery_id)
+        .header("X-Rustytime-Timestamp", timestamp.to_string())
+        .header(
+            "X-Rustytime-Signature",
+            sign_payload(secret, timestamp, body),
+        )
+        .body(body.to_vec())
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {e}"))?;
+
+    StatusCode::from_u16(response.status().as_u16()).map_err(|e| e.to_string())
+}
+
+pub fn build_client() -> Client {
+    Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .dns_resolver(PublicResolver)
+        .user_agent(concat!("rustytime-webhooks/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .expect("Failed to build webhook HTTP client")
+}
+
+async fn run_delivery(
+    job: WebhookJob,
+    pool: Data<DbPool>,
+    client: Data<Client>,
+    attempt: Attempt,
+) -> Result<String, BoxDynError> {
+    let webhook = {
+        let mut conn = pool.get()?;
+        Webhook::find(&mut conn, job.webhook_id)?
+    };
+    let Some(webhook) = webhook.filter(|w| w.enabled) else {
+        debug!(
+            webhook_id = job.webhook_id,
+            "Skipping delivery to removed or disabled webhook"
+        );
+        return Ok("Webhook removed or disabled".to_string());
+    };
+
+    let body = serde_json::to_vec(&job.payload)?;
+    let started = Instant::now();
+    let result = send_webhook(
+        &client,
+        &webhook.url,
+        &webhook.secret,
+        job.event,
+        job.delivery_id(),
+        &body,
+    )
+    .await;
+    let duration_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (status_code, error) = match &result {
+        Ok(status) if status.is_success() => (Some(status.as_u16() as i32), None),
+        Ok(status) => (
+            Some(status.as_u16() as i32),
+            Some(format!("Receiver responded with {status}")),
+        ),
+        Err(e) => (None, Some(e.chars().take(MAX_ERROR_LENGTH).collect())),
+    };
+
+    let delivery = NewWebhookDelivery {
+        webhook_id: webhook.id,
+        event: job.event.as_str().to_string(),
+        attempt: attempt.current().max(1) as i32,
+        status_code,
+        success: error.is_none(),
+        error: error.clone(),
+        duration_ms,
+    };
+    match pool.get() {
+        Ok(mut conn) => {
+            if let Err(e) = WebhookDelivery::record(&mut conn, &delivery) {
+                error!(error = ?e, webhook_id = webhook.id, "Failed to record webhook delivery");
+            }
+        }
+        Err(e) => error!(error = ?e, "Failed to get DB connection for webhook delivery log"),
+    }
+
+    match error {
+        None => Ok(format!(
+            "Delivered {} to webhook {}",
+            job.event.as_str(),
+            webhook.id
+        )),
+        Some(error) => {
+            warn!(webhook_id = webhook.id, attempt = delivery.attempt, error = %error, "Webhook delivery failed");
+            Err(error.into())
+        }
+    }
+}
+
+fn retry_policy() -> BackoffRetryPolicy<ExponentialBackoff> {
+    let backoff = ExponentialBackoffMaker::new(MIN_BACKOFF, MAX_BACKOFF, 0.1, HasherRng::default())
+        .expect("valid webhook backoff")
+        .make_backoff();
+    RetryPolicy::retries(MAX_RETRIES).with_backoff(backoff)
+}
+
+/// Send goal.reached for goals met in their current period, once per period
+async fn check_goals(_tick: Tick, pool: Data<DbPool>, store: Data<WebhookStore>) {
+    let result = (|| {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        let webhooks = Webhook::subscribed_to(&mut conn, WebhookEvent::GoalReached)
+            .map_err(|e| e.to_string())?;
+        if webhooks.is_empty() {
+            return Ok((webhooks, Vec::new()));
+        }
+
+        // global webhooks want everyone's goals, so only narrow down without them
+        let goals = if webhooks.iter().any(|w| w.global) {
+            Goal::list_all(&mut conn)
+        } else {
+            let user_ids: Vec<i32> = webhooks.iter().map(|w| w.user_id).collect();
+            Goal::list_for_users(&mut conn, &user_ids)
+        }
+        .map_err(|e| e.to_string())?;
+
+        let mut timezones: HashMap<i32, Tz> = HashMap::new();
+        let mut reached = Vec::new();
+        for goal in goals {
+            let tz = match timezones.entry(goal.user_id) {
+                Entry::Occupied(entry) => *entry.get(),
+                Entry::Vacant(entry) => {
+                    let timezone = User::get_by_id(&mut conn, goal.user_id)
+                        .map_err(|e| e.to_string())?
+                        .map(|u| u.timezone)
+                        .unwrap_or_default();
+                    *entry.insert(parse_timezone(&timezone))
+                }
+            };
+            let today = get_today_in_timezone(tz);
+
+            let (period_start, seconds) = goal
+                .current_period_seconds(&mut conn, tz, today)
+                .map_err(|e| e.to_string())?;
+            if goal.is_newly_reached(period_start, seconds) {
+                Goal::mark_reached(&mut conn, goal.id, period_start).map_err(|e| e.to_string())?;
+                reached.push((goal, period_start, seconds));
+            }
+        }
+
+        Ok::<_, String>((webhooks, reached))
+    })();
+
+    let (webhooks, reached) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            error!(error = %e, "Failed to check goals for webhooks");
+            return;
+        }
+    };
+
+    for (goal, period_start, seconds) in reached {
+        let data = json!({
+            "goal_id": goal.id,
+            "title": goal.title(),
+            "period": goal.period().as_str(),
+            "period_start": period_start,
+            "target_seconds": goal.target_seconds,
+            "actual_seconds": seconds,
+        });
+        dispatch_to(
+            &store,
+            &webhooks,
+            WebhookEvent::GoalReached,
+            goal.user_id,
+            &data,
+        )
+        .await;
+    }
+}
+
+fn day_summary(seconds: i64, date: NaiveDate) -> Value {
+    json!({
+        "date": date,
+        "total_seconds": seconds,
+        "text": format_hour_minute(seconds),
+    })
+}
+
+/// Send each subscriber a summary of the previous day, shortly after their local midnight
+async fn send_daily_summaries(_tick: Tick, pool: Data<DbPool>, store: Data<WebhookStore>) {
+    let result = (|| {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        let webhooks = Webhook::subscribed_to(&mut conn, WebhookEvent::DailySummary)
+            .map_err(|e| e.to_string())?;
+        let mut jobs = Vec::new();
+
+        for webhook in webhooks {
+            if webhook.global {
+                // the instance summary follows UTC days
+                if Utc::now().hour() != 0 {
+                    continue;
+                }
+                let yesterday = Utc::now().date_naive() - ChronoDuration::days(1);
+                let tz = Tz::UTC;
+                let rows = Heartbeat::get_all_user_durations(
+                    &mut conn,
+                    get_day_start_utc(yesterday, tz),
+                    get_day_end_utc(yesterday, tz),
+                )
+                .map_err(|e| e.to_string())?;
+                let total_seconds = rows.iter().map(|r| r.total_seconds).sum();
+
+                let mut data = day_summary(total_seconds, yesterday);
+                data["active_users"] = json!(rows.iter().filter(|r| r.total_seconds > 0).count());
+                jobs.push(WebhookJob::new(
+                    webhook.id,
+                    WebhookEvent::DailySummary,
+                    None,
+                    data,
+                ));
+                continue;
+            }
+
+            let Some(user) =
+                User::get_by_id(&mut conn, webhook.user_id).map_err(|e| e.to_string())?
+            else {
+                continue;
+            };
+            let tz = parse_timezone(&user.timezone);
+            if Utc::now().with_timezone(&tz).hour() != 0 {
+                continue;
+            }
+
+            let yesterday = get_today_in_timezone(tz) - ChronoDuration::days(1);
+            let seconds = Heartbeat::get_user_duration_seconds(
+                &mut conn,
+                DurationInput {
+                    user_id: Some(user.id),
+                    start_date: Some(get_day_start_utc(yesterday, tz)),
+                    end_date: Some(get_day_end_utc(yesterday, tz)),
+                    project: None,
+                    language: None,
+                    entity: None,
+                    type_filter: None,
+                },
+            )
+            .map_err(|e| e.to_string())?;
+
+            jobs.push(WebhookJob::new(
+                webhook.id,
+                WebhookEvent::DailySummary,
+                Some(user.id),
+                day_summary(seconds, yesterday),
+            ));
+        }
+
+        Ok::<_, String>(jobs)
+    })();
+
+    match result {
+        Ok(jobs) => {
+            for job in jobs {
+                let webhook_id = job.webhook_id;
+                if let Err(e) = enqueue_webhook(&store, job).await {
+                    error!(error = ?e, webhook_id, "Failed to enqueue daily summary");
+                }
+            }
+        }
+        Err(e) => error!(error = %e, "Failed to build daily summaries"),
+    }
+}
+
+async fn run_cleanup(_tick: Tick, pool: Data<DbPool>) {
+    let result = pool.get().map_err(|e| e.to_string()).and_then(|mut conn| {
+        WebhookDelivery::delete_older_than(&mut conn, DELIVERY_RETENTION_DAYS)
+            .map_err(|e| e.to_string())
+    });
+
+    match result {
+        Ok(deleted) => debug!(deleted, "Cleaned up old webhook deliveries"),
+        Err(e) => error!(error = %e, "Failed to clean up webhook deliveries"),
+    }
+}
+
+pub async fn setup(
+    webhook_store: WebhookStore,
+    diesel_pool: DbPool,
+) -> impl std::future::Future<Output = ()> {
+    let goals_schedule =
+        Schedule::from_str("0 */10 * * * *").expect("valid cron: every 10 minutes");
+    let summary_schedule = Schedule::from_str("0 5 * * * *").expect("valid cron: every hour");
+    let cleanup_schedule =
+        Schedule::from_str("0 30 0 * * *").expect("valid cron: daily at 00:30 AM");
+
+    let delivery_worker = WorkerBuilder::new("webhook-delivery")
+        .backend(webhook_store.clone())
+        .enable_tracing()
+        .layer(PrometheusLayer::default())
+        .catch_panic()
+        .retry(retry_policy())
+        .concurrency(4)
+        .data(diesel_pool.clone())
+        .data(build_client())
+        .build(run_delivery);
+
+    let goals_worker = WorkerBuilder::new("webhook-goals")
+        .backend(CronStream::new(goals_schedule))
+        .enable_tracing()
+        .catch_panic()
+        .data(diesel_pool.clone())
+        .data(webhook_store.clone())
+        .build(check_goals);
+
+    let summary_worker = WorkerBuilder::new("webhook-daily-summary")
+        .backend(CronStream::new(summary_schedule))
+        .enable_tracing()
+        .catch_panic()
+        .data(diesel_pool.clone())
+        .data(webhook_store)
+        .build(send_daily_summaries);
+
+    let cleanup_worker = WorkerBuilder::new("webhook-cleanup")
+        .backend(CronStream::new(cleanup_schedule))
+        .enable_tracing()
+        .catch_panic()
+        .data(diesel_pool)
+        .build(run_cleanup);
+
+    async move {
+        tokio::select! {
+            _ = delivery_worker
+                .run_until(ctrl_c())
+                .map_err(|e| tracing::error!("Webhook worker error: {}", e))
+                .map(|_| ()) => {}
+            _ = goals_worker.run() => {}
+            _ = summary_worker.run() => {}
+            _ = cleanup_worker.run() => {}
+            _ = ctrl_c() => {
+                tracing::info!("Shutting down webhook workers");
+            }
+        }
+    }
+}
@@ -29,7 +29,9 @@ use state::AppState;
 use std::sync::OnceLock;
 use std::time::Instant;
 use utils::http::{CloudflareAwareKeyExtractor, extract_client_ip};
+use utils::local_auth::LocalAuth;
 use utils::middleware::cors_layer;
+use utils::oauth::AuthProviders;
 
 use crate::{
     routes::create_app_router,
@@ -168,12 +170,26 @@ async fn async_main(is_production: bool) -> Result<(), Box<dyn std::error::Error
         }
     }
 
-    // create GitHub OAuth client
-    let github_client = handlers::github::create_github_client();
-    info!("✅ GitHub OAuth client created");
+    // load the configured login providers
+    let auth_providers = AuthProviders::from_env(&reqwest::Client::new()).await;
+    info!(
+        "✅ {} login provider(s) configured",
+        auth_providers.list().len()
+    );
+
+    // local accounts, and the owner account in single-user mode
+    let local_auth = LocalAuth::from_env();
+    match local_auth.provision_single_user(&pool).await {
+        Ok(Some(owner)) => info!("✅ Single-user owner ready: {}", owner.name),
+        Ok(None) => {}
+        Err(e) => {
+            error!("❌ Failed to provision single-user owner: {}", e);
+            std::process::exit(1);
+        }
+    }
 
     // create application state
-    let app_state = AppState::new(pool.clone(), github_client);
+    let app_state = AppState::new(pool.clone(), auth_providers, local_auth);
 
     // get database URL for sqlx pool
     let database_url = env::var("DATABASE_URL").unwrap_or_else(|_| {
@@ -191,12 +207,33 @@ async fn async_main(is_production: bool) -> Result<(), Box<dyn std::error::Error
     let sqlx_pool = sqlx::PgPool::connect(&database_url)
         .await
         .expect("Failed to connect to database for jobs");
-    let (leaderboard_worker, import_worker, sessions_worker, import_store) =
-        jobs::setup_jobs(sqlx_pool, pool.clone()).await;
+    let (
+        leaderboard_worker,
+        import_worker,
+        sessions_worker,
+        history_worker,
+        webhook_worker,
+        digest_worker,
+        daily_totals_worker,
+        import_store,
+        history_store,
+        webhook_store,
+    ) = jobs::setup_jobs(sqlx_pool, pool.clone(), app_state.cache.clone()).await;
+    match jobs::history::enqueue_pending_normalizations(&history_store, &pool).await {
+        Ok(0) => {}
+        Ok(count) => info!("✅ Queued language normalization for {} user(s)", count),
+        Err(e) => error!("❌ Failed to queue language normalization: {}", e),
+    }
     app_state.set_import_store(import_store);
+    app_state.set_history_store(history_store);
+    app_state.set_webhook_store(webhook_store);
     tokio::spawn(leaderboard_worker);
     tokio::spawn(import_worker);
     tokio::spawn(sessions_worker);
+    tokio::spawn(history_worker);
+    tokio::spawn(webhook_worker);
+    tokio::spawn(digest_worker);
+    tokio::spawn(daily_totals_worker);
     info!("✅ Jobs system started");
 
     let rate_period = if is_production {
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::schema::{audit_log, users};
+use crate::utils::instrumented;
+
+/// Kinds of actions written to the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    ImpersonationStart,
+    ImpersonationStop,
+    /// A write made while impersonating another user
+    ImpersonatedRequest,
+    RoleChange,
+    UserBan,
+    UserUnban,
+    ApiKeysViewed,
+    ImportsViewed,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::ImpersonationStart => "impersonation.start",
+            AuditAction::ImpersonationStop => "impersonation.stop",
+            AuditAction::ImpersonatedRequest => "impersonation.request",
+            AuditAction::RoleChange => "user.role",
+            AuditAction::UserBan => "user.ban",
+            AuditAction::UserUnban => "user.unban",
+            AuditAction::ApiKeysViewed => "admin.api_keys_viewed",
+            AuditAction::ImportsViewed => "admin.imports_viewed",
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub actor_id: i32,
+    pub effective_user_id: i32,
+    pub action: String,
+    pub target: Option<String>,
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLogEntry {
+    /// The user who performed the action, the admin when impersonating
+    pub actor_id: i32,
+    /// The user the action was performed as
+    pub effective_user_id: i32,
+    pub action: String,
+    pub target: Option<String>,
+    pub metadata: serde_json::Value,
+}
+
+impl NewAuditLogEntry {
+    pub fn new(actor_id: i32, effective_user_id: i32, action: AuditAction) -> Self {
+        Self {
+            actor_id,
+            effective_user_id,
+            action: action.as_str().to_string(),
+            target: None,
+            metadata: serde_json::Value::Object(Default::default()),
+        }
+    }
+
+    pub fn target_user(mut self, user_id: i32) -> Self {
+        self.target = Some(format!("user:{user_id}"));
+        self
+    }
+
+    pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AuditLogFilter {
+    pub action: Option<String>,
+    pub actor_id: Option<i32>,
+    pub effective_user_id: Option<i32>,
+    /// Exact target, like `user:42`
+    pub target: Option<String>,
+}
+
+#[derive(Serialize, JsonSchema, Debug)]
+pub struct AuditLogRecord {
+    pub id: i64,
+    pub actor_id: i32,
+    pub actor_name: Option<String>,
+    pub effective_user_id: i32,
+    pub effective_user_name: Option<String>,
+    pub action: String,
+    pub target: Option<String>,
+    pub metadata: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AuditLogEntry {
+    pub fn record(conn: &mut PgConnection, entry: &NewAuditLogEntry) -> QueryResult<usize> {
+        instrumented::execute("AuditLogEntry::record", || {
+            diesel::insert_into(audit_log::table)
+                .values(entry)
+                .execute(conn)
+        })
+    }
+
+    fn filtered(filter: &AuditLogFilter) -> audit_log::BoxedQuery<'_, diesel::pg::Pg> {
+        let mut query = audit_log::table.into_boxed();
+        if let Some(action) = &filter.action {
+            query = query.filter(audit_log::action.eq(action));
+        }
+        if let Some(actor_id) = filter.actor_id {
+            query = query.filter(audit_log::actor_id.eq(actor_id));
+        }
+        if let Some(effective_user_id) = filter.effective_user_id {
+            query = query.filter(audit_log::effective_user_id.eq(effective_user_id));
+        }
+        if let Some(target) = &filter.target {
+            query = query.filter(audit_log::target.eq(target));
+        }
+        query
+    }
+
+    pub fn count(conn: &mut PgConnection, filter: &AuditLogFilter) -> QueryResult<i64> {
+        instrumented::first("AuditLogEntry::count", || {
+            Self::filtered(filter).count().get_result(conn)
+        })
+    }
+
+    /// Newest entries first, with the names of the users involved
+    pub fn list(
+        conn: &mut PgConnection,
+        filter: &AuditLogFilter,
+        limit: i64,
+        offset: i64,
+    ) -> QueryResult<Vec<AuditLogRecord>> {
+        let entries = instrumented::load("AuditLogEntry::list", || {
+            Self::filtered(filter)
+                .order(audit_log::id.desc())
+                .limit(limit)
+                .offset(offset)
+                .select(AuditLogEntry::as_select())
+                .load::<AuditLogEntry>(conn)
+        })?;
+
+        let user_ids: Vec<i32> = entries
+            .iter()
+            .flat_map(|entry| [entry.actor_id, entry.effective_user_id])
+            .collect();
+        let names: HashMap<i32, String> = instrumented::load("AuditLogEntry::user_names", || {
+            users::table
+                .filter(users::id.eq_any(&user_ids))
+                .select((users::id, users::name))
+                .load::<(i32, String)>(conn)
+        })?
+        .into_iter()
+        .collect();
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| AuditLogRecord {
+                id: entry.id,
+                actor_id: entry.actor_id,
+                actor_name: names.get(&entry.actor_id).cloned(),
+                effective_user_id: entry.effective_user_id,
+                effective_user_name: names.get(&entry.effective_user_id).cloned(),
+                action: entry.action,
+                target: entry.target,
+                metadata: entry.metadata,
+                created_at: entry.created_at,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,234 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Date, Int4, Text, Timestamptz};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::models::heartbeat::{DailyTotal, TIMEOUT_SECONDS};
+use crate::schema::user_daily_totals;
+use crate::utils::instrumented;
+
+/// Number of days covered by the activity heatmap, including today
+pub const HEATMAP_DAYS: i64 = 365;
+
+/// A stored day of the activity rollup
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = user_daily_totals)]
+pub struct DailyTotalRow {
+    pub day: NaiveDate,
+    pub total_seconds: i64,
+}
+
+impl From<DailyTotalRow> for DailyTotal {
+    fn from(row: DailyTotalRow) -> Self {
+        DailyTotal {
+            date: row.day,
+            total_seconds: row.total_seconds,
+        }
+    }
+}
+
+/// A user whose rollup is due for a refresh
+#[derive(QueryableByName, Debug, Clone)]
+pub struct StaleRollupUser {
+    #[diesel(sql_type = Int4)]
+    pub user_id: i32,
+    #[diesel(sql_type = Text)]
+    pub timezone: String,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct ActivitySummary {
+    /// Consecutive active days ending today, or yesterday if nothing was tracked today yet
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub best_day: Option<DailyTotal>,
+}
+
+/// Streaks and best day of a series of consecutive days, oldest first and ending today
+pub fn summarize_activity(days: &[DailyTotal]) -> ActivitySummary {
+    let mut longest_streak = 0;
+    let mut running = 0;
+    for day in days {
+        if day.total_seconds > 0 {
+            running += 1;
+            longest_streak = longest_streak.max(running);
+        } else {
+            running = 0;
+        }
+    }
+
+    // today not being tracked yet should not break the streak
+    let skip_today = days.last().is_some_and(|d| d.total_seconds == 0);
+    let current_streak = days
+        .iter()
+        .rev()
+        .skip(usize::from(skip_today))
+        .take_while(|d| d.total_seconds > 0)
+        .count() as u32;
+
+    let best_day = days
+        .iter()
+        .filter(|d| d.total_seconds > 0)
+        .max_by(|a, b| {
+            a.total_seconds
+                .cmp(&b.total_seconds)
+                .then_with(|| b.date.cmp(&a.date))
+        })
+        .cloned();
+
+    ActivitySummary {
+        current_streak,
+        longest_streak,
+        best_day,
+    }
+}
+
+/// Days of the heatmap window ending at `today`, filling days without a stored total with zero
+pub fn fill_days(stored: &[DailyTotal], today: NaiveDate) -> Vec<DailyTotal> {
+    let first_day = today - Duration::days(HEATMAP_DAYS - 1);
+    let mut stored = stored.iter().peekable();
+
+    (0..HEATMAP_DAYS)
+        .map(|offset| {
+            let date = first_day + Duration::days(offset);
+            while stored.next_if(|d| d.date < date).is_some() {}
+            let total_seconds = stored
+                .next_if(|d| d.date == date)
+                .map_or(0, |d| d.total_seconds);
+            DailyTotal {
+                date,
+                total_seconds,
+            }
+        })
+        .collect()
+}
+
+impl DailyTotalRow {
+    /// The latest day the rollup has been computed for
+    fn last_refreshed_day(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<NaiveDate>> {
+        instrumented::first("DailyTotalRow::last_refreshed_day", || {
+            user_daily_totals::table
+                .filter(user_daily_totals::user_id.eq(user_id))
+                .select(diesel::dsl::max(user_daily_totals::day))
+                .first(conn)
+        })
+    }
+
+    /// Recompute the rollup for the days between `start` and `end` (inclusive)
+    fn refresh(
+        conn: &mut PgConnection,
+        user_id: i32,
+        timezone: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> QueryResult<usize> {
+        instrumented::execute("DailyTotalRow::refresh", || {
+            diesel::sql_query("SELECT refresh_user_daily_totals($1, $2, $3, $4, $5)")
+                .bind::<Int4, _>(user_id)
+                .bind::<Text, _>(timezone)
+                .bind::<Date, _>(start)
+                .bind::<Date, _>(end)
+                .bind::<Int4, _>(TIMEOUT_SECONDS)
+                .execute(conn)
+        })
+    }
+
+    /// Bring the rollup up to date for the days of the heatmap window ending at `today`.
+    /// Only the days since the last refresh are recomputed from heartbeats.
+    pub fn refresh_recent(
+        conn: &mut PgConnection,
+        user_id: i32,
+        timezone: &str,
+        today: NaiveDate,
+    ) -> QueryResult<usize> {
+        let first_day = today - Duration::days(HEATMAP_DAYS - 1);
+
+        // the last refreshed day may have been partial at the time, and yesterday can still get
+        // heartbeats that were sent late, so both are always recomputed
+        let refresh_from = Self::last_refreshed_day(conn, user_id)?.map_or(first_day, |day| {
+            day.min(today - Duration::days(1)).max(first_day)
+        });
+        Self::refresh(conn, user_id, timezone, refresh_from, today)
+    }
+
+    /// Users whose rollup needs a refresh: those with heartbeats since `active_since`, and those
+    /// with heartbeats whose rollup stops before yesterday in their timezone, e.g. after it was
+    /// cleared or invalidated
+    pub fn stale_users(
+        conn: &mut PgConnection,
+        active_since: DateTime<Utc>,
+    ) -> QueryResult<Vec<StaleRollupUser>> {
+        instrumented::load("DailyTotalRow::stale_users", || {
+            diesel::sql_query(
+                "SELECT u.id AS user_id, u.timezone
+                 FROM users u
+                 WHERE EXISTS (
+                     SELECT 1 FROM heartbeats h WHERE h.user_id = u.id AND h.time >= $1
+                 )
+                 OR (
+                     NOT EXISTS (
+                         SELECT 1 FROM user_daily_totals d
+                         WHERE d.user_id = u.id
+                           AND d.day >= (NOW() AT TIME ZONE u.timezone)::date - 1
+                     )
+                     AND EXISTS (SELECT 1 FROM heartbeats h WHERE h.user_id = u.id)
+                 )",
+            )
+            .bind::<Timestamptz, _>(active_since)
+            .load(conn)
+        })
+    }
+
+    /// Stored daily totals of the last year, oldest first and ending today. Days the rollup has
+    /// not been refreshed for yet count as zero.
+    pub fn last_year_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        today: NaiveDate,
+    ) -> QueryResult<Vec<DailyTotal>> {
+        let first_day = today - Duration::days(HEATMAP_DAYS - 1);
+
+        let stored = instrumented::load("DailyTotalRow::last_year_for_user", || {
+            user_daily_totals::table
+                .filter(user_daily_totals::user_id.eq(user_id))
+                .filter(user_daily_totals::day.between(first_day, today))
+                .order(user_daily_totals::day.asc())
+                .select(DailyTotalRow::as_select())
+                .load(conn)
+        })?;
+        let stored: Vec<DailyTotal> = stored.into_iter().map(DailyTotal::from).collect();
+
+        Ok(fill_days(&stored, today))
+    }
+
+    /// Forget the rollup from the local day containing `since` onwards, for heartbeats that
+    /// arrive more than a day late
+    pub fn invalidate_since(
+        conn: &mut PgConnection,
+        user_id: i32,
+        since: DateTime<Utc>,
+    ) -> QueryResult<usize> {
+        // local days can start up to a day before the UTC one
+        let day = (since - Duration::days(1)).date_naive();
+        instrumented::execute("DailyTotalRow::invalidate_since", || {
+            diesel::delete(
+                user_daily_totals::table
+                    .filter(user_daily_totals::user_id.eq(user_id))
+                    .filter(user_daily_totals::day.ge(day)),
+            )
+            .execute(conn)
+        })
+    }
+
+    /// Forget the whole rollup, e.g. after a timezone change or an import
+    pub fn clear_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
+        instrumented::execute("DailyTotalRow::clear_for_user", || {
+            diesel::delete(user_daily_totals::table.filter(user_daily_totals::user_id.eq(user_id)))
+                .execute(conn)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
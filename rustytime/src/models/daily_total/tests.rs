@@ -0,0 +1,104 @@
+use super::*;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+/// Consecutive days ending on 2026-03-20 with the given totals
+fn days(totals: &[i64]) -> Vec<DailyTotal> {
+    let last = date(2026, 3, 20);
+    totals
+        .iter()
+        .enumerate()
+        .map(|(i, &total_seconds)| DailyTotal {
+            date: last - Duration::days((totals.len() - 1 - i) as i64),
+            total_seconds,
+        })
+        .collect()
+}
+
+// ==================== summarize_activity tests ====================
+
+#[test]
+fn empty_series_has_no_streaks() {
+    let summary = summarize_activity(&[]);
+    assert_eq!(summary.current_streak, 0);
+    assert_eq!(summary.longest_streak, 0);
+    assert_eq!(summary.best_day, None);
+}
+
+#[test]
+fn current_streak_ends_today() {
+    let summary = summarize_activity(&days(&[60, 0, 60, 60, 60]));
+    assert_eq!(summary.current_streak, 3);
+    assert_eq!(summary.longest_streak, 3);
+}
+
+#[test]
+fn current_streak_survives_untracked_today() {
+    let summary = summarize_activity(&days(&[60, 60, 0]));
+    assert_eq!(summary.current_streak, 2);
+}
+
+#[test]
+fn current_streak_breaks_on_missed_yesterday() {
+    let summary = summarize_activity(&days(&[60, 60, 0, 0]));
+    assert_eq!(summary.current_streak, 0);
+    assert_eq!(summary.longest_streak, 2);
+}
+
+#[test]
+fn longest_streak_can_be_in_the_past() {
+    let summary = summarize_activity(&days(&[60, 60, 60, 60, 0, 60]));
+    assert_eq!(summary.current_streak, 1);
+    assert_eq!(summary.longest_streak, 4);
+}
+
+#[test]
+fn best_day_prefers_the_earliest_tie() {
+    let summary = summarize_activity(&days(&[100, 300, 50, 300]));
+    assert_eq!(
+        summary.best_day,
+        Some(DailyTotal {
+            date: date(2026, 3, 18),
+            total_seconds: 300,
+        })
+    );
+}
+
+// ==================== fill_days tests ====================
+
+#[test]
+fn fill_days_covers_the_whole_window() {
+    let today = date(2026, 3, 20);
+    let filled = fill_days(&[], today);
+    assert_eq!(filled.len(), HEATMAP_DAYS as usize);
+    assert_eq!(filled.first().unwrap().date, date(2025, 3, 21));
+    assert_eq!(filled.last().unwrap().date, today);
+    assert!(filled.iter().all(|d| d.total_seconds == 0));
+}
+
+#[test]
+fn fill_days_keeps_stored_totals_and_drops_outside_days() {
+    let today = date(2026, 3, 20);
+    let stored = vec![
+        DailyTotal {
+            date: date(2025, 1, 1),
+            total_seconds: 999,
+        },
+        DailyTotal {
+            date: date(2026, 3, 18),
+            total_seconds: 120,
+        },
+        DailyTotal {
+            date: today,
+            total_seconds: 60,
+        },
+    ];
+    let filled = fill_days(&stored, today);
+    assert_eq!(filled.len(), HEATMAP_DAYS as usize);
+    assert_eq!(filled[filled.len() - 3].total_seconds, 120);
+    assert_eq!(filled[filled.len() - 2].total_seconds, 0);
+    assert_eq!(filled[filled.len() - 1].total_seconds, 60);
+    assert_eq!(filled.iter().map(|d| d.total_seconds).sum::<i64>(), 180);
+}
@@ -0,0 +1,451 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Date, Int4, Nullable as SqlNullable, Text, Timestamptz};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::heartbeat::{DurationInput, Heartbeat, TIMEOUT_SECONDS};
+use crate::schema::goals;
+use crate::utils::instrumented;
+use crate::utils::time::{
+    TimeFormat, get_day_end_utc, get_day_start_utc, get_today_in_timezone, get_week_start_date,
+    human_readable_duration, parse_timezone,
+};
+
+/// Number of periods returned in a goal's history
+const GOAL_HISTORY_PERIODS: usize = 7;
+/// How far back the current streak is searched for
+const STREAK_LOOKBACK_DAYS: usize = 60;
+const STREAK_LOOKBACK_WEEKS: usize = 26;
+
+pub const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalPeriod {
+    Day,
+    Week,
+}
+
+impl GoalPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Week => "week",
+        }
+    }
+
+    /// Inclusive date ranges of the last `count` periods, newest (containing `today`) first
+    pub fn ranges(self, today: NaiveDate, count: usize) -> Vec<(NaiveDate, NaiveDate)> {
+        match self {
+            GoalPeriod::Day => (0..count as i64)
+                .map(|offset| {
+                    let day = today - Duration::days(offset);
+                    (day, day)
+                })
+                .collect(),
+            GoalPeriod::Week => {
+                let current_week = get_week_start_date(today);
+                (0..count as i64)
+                    .map(|offset| {
+                        let start = current_week - Duration::weeks(offset);
+                        (start, start + Duration::days(6))
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn streak_lookback(self) -> usize {
+        match self {
+            GoalPeriod::Day => STREAK_LOOKBACK_DAYS,
+            GoalPeriod::Week => STREAK_LOOKBACK_WEEKS,
+        }
+    }
+}
+
+impl From<&str> for GoalPeriod {
+    fn from(s: &str) -> Self {
+        match s {
+            "week" => GoalPeriod::Week,
+            _ => GoalPeriod::Day,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RangeStatus {
+    Success,
+    Fail,
+    /// An ignored weekday where the goal was not met
+    Ignored,
+    /// The current period, which has not reached the goal yet
+    Pending,
+}
+
+/// Status of a single period of a goal
+pub fn range_status(
+    actual_seconds: i64,
+    goal_seconds: i64,
+    ignored: bool,
+    is_current: bool,
+) -> RangeStatus {
+    if actual_seconds >= goal_seconds {
+        RangeStatus::Success
+    } else if ignored {
+        RangeStatus::Ignored
+    } else if is_current {
+        RangeStatus::Pending
+    } else {
+        RangeStatus::Fail
+    }
+}
+
+/// Consecutive successful periods, newest first, skipping ignored and pending ones
+pub fn current_streak(statuses_newest_first: &[RangeStatus]) -> u32 {
+    let mut streak = 0;
+    for status in statuses_newest_first {
+        match status {
+            RangeStatus::Success => streak += 1,
+            RangeStatus::Ignored | RangeStatus::Pending => {}
+            RangeStatus::Fail => break,
+        }
+    }
+    streak
+}
+
+/// Convert weekday names into the stored bitmask, bit 0 being monday
+pub fn weekdays_to_mask(names: &[String]) -> Result<i16, String> {
+    names.iter().try_fold(0i16, |mask, name| {
+        let index = WEEKDAY_NAMES
+            .iter()
+            .position(|day| day.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Unknown weekday '{name}'"))?;
+        Ok(mask | (1 << index))
+    })
+}
+
+pub fn mask_to_weekdays(mask: i16) -> Vec<String> {
+    WEEKDAY_NAMES
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| mask & (1 << index) != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = goals)]
+pub struct Goal {
+    pub id: i32,
+    pub user_id: i32,
+    pub period: String,
+    pub target_seconds: i32,
+    pub project: Option<String>,
+    pub language: Option<String>,
+    pub ignored_weekdays: i16,
+    pub last_reached_on: Option<NaiveDate>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = goals)]
+pub struct NewGoal {
+    pub user_id: i32,
+    pub period: String,
+    pub target_seconds: i32,
+    pub project: Option<String>,
+    pub language: Option<String>,
+    pub ignored_weekdays: i16,
+}
+
+#[derive(QueryableByName)]
+struct PeriodDurationRow {
+    #[diesel(sql_type = Date)]
+    period_start: NaiveDate,
+    #[diesel(sql_type = BigInt)]
+    total_seconds: i64,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct GoalPeriodProgress {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub actual_seconds: i64,
+    pub goal_seconds: i64,
+    pub status: RangeStatus,
+}
+
+#[derive(Serialize, JsonSchema, Debug, Clone)]
+pub struct GoalProgress {
+    pub id: i32,
+    pub title: String,
+    pub period: GoalPeriod,
+    pub target_seconds: i64,
+    pub project: Option<String>,
+    pub language: Option<String>,
+    pub ignored_weekdays: Vec<String>,
+    /// Status of the current period
+    pub status: RangeStatus,
+    /// Share of the current period's goal reached, capped at 100
+    pub percent: f64,
+    pub current_streak: u32,
+    /// The last periods, oldest first and ending with the current one
+    pub history: Vec<GoalPeriodProgress>,
+}
+
+impl Goal {
+    pub fn period(&self) -> GoalPeriod {
+        GoalPeriod::from(self.period.as_str())
+    }
+
+    /// Human readable description, e.g. "Code 2h 0m per day in rustytime"
+    pub fn title(&self) -> String {
+        let mut title = format!(
+            "Code {} per {}",
+            human_readable_duration(self.target_seconds as i64, TimeFormat::HourMinute)
+                .human_readable,
+            self.period().as_str()
+        );
+        if let Some(ref project) = self.project {
+            title.push_str(&format!(" in {project}"));
+        }
+        if let Some(ref language) = self.language {
+            title.push_str(&format!(" using {language}"));
+        }
+        title
+    }
+
+    fn is_ignored(&self, period: GoalPeriod, date: NaiveDate) -> bool {
+        period == GoalPeriod::Day
+            && self.ignored_weekdays & (1 << date.weekday().num_days_from_monday()) != 0
+    }
+
+    pub fn list_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Goal>> {
+        instrumented::load("Goal::list_for_user", || {
+            goals::table
+                .filter(goals::user_id.eq(user_id))
+                .order(goals::id.asc())
+                .select(Goal::as_select())
+                .load(conn)
+        })
+    }
+
+    pub fn list_all(conn: &mut PgConnection) -> QueryResult<Vec<Goal>> {
+        instrumented::load("Goal::list_all", || {
+            goals::table
+                .order(goals::id.asc())
+                .select(Goal::as_select())
+                .load(conn)
+        })
+    }
+
+    /// Goals of the given users, for checking whether they were reached
+    pub fn list_for_users(conn: &mut PgConnection, user_ids: &[i32]) -> QueryResult<Vec<Goal>> {
+        instrumented::load("Goal::list_for_users", || {
+            goals::table
+                .filter(goals::user_id.eq_any(user_ids))
+                .order(goals::id.asc())
+                .select(Goal::as_select())
+                .load(conn)
+        })
+    }
+
+    pub fn count_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<i64> {
+        instrumented::first("Goal::count_for_user", || {
+            goals::table
+                .filter(goals::user_id.eq(user_id))
+                .count()
+                .get_result(conn)
+        })
+    }
+
+    pub fn create(conn: &mut PgConnection, new_goal: &NewGoal) -> QueryResult<Goal> {
+        instrumented::first("Goal::create", || {
+            diesel::insert_into(goals::table)
+                .values(new_goal)
+                .returning(Goal::as_returning())
+                .get_result(conn)
+        })
+    }
+
+    pub fn delete(conn: &mut PgConnection, user_id: i32, goal_id: i32) -> QueryResult<usize> {
+        instrumented::execute("Goal::delete", || {
+            diesel::delete(
+                goals::table
+                    .filter(goals::user_id.eq(user_id))
+                    .filter(goals::id.eq(goal_id)),
+            )
+            .execute(conn)
+        })
+    }
+
+    /// Remember that the goal was reached in the period starting on `period_start`
+    pub fn mark_reached(
+        conn: &mut PgConnection,
+        goal_id: i32,
+        period_start: NaiveDate,
+    ) -> QueryResult<usize> {
+        instrumented::execute("Goal::mark_reached", || {
+            diesel::update(goals::table.filter(goals::id.eq(goal_id)))
+                .set(goals::last_reached_on.eq(period_start))
+                .execute(conn)
+        })
+    }
+
+    /// Whether reaching the goal in the period starting on `period_start` is news
+    pub fn is_newly_reached(&self, period_start: NaiveDate, actual_seconds: i64) -> bool {
+        actual_seconds >= self.target_seconds as i64
+            && self.last_reached_on.is_none_or(|day| day < period_start)
+    }
+
+    /// Start of the current period and the time counted towards the goal in it
+    pub fn current_period_seconds(
+        &self,
+        conn: &mut PgConnection,
+        tz: Tz,
+        today: NaiveDate,
+    ) -> QueryResult<(NaiveDate, i64)> {
+        let (start, end) = self.period().ranges(today, 1)[0];
+        let actual_seconds = Heartbeat::get_user_duration_seconds(
+            conn,
+            DurationInput {
+                user_id: Some(self.user_id),
+                start_date: Some(get_day_start_utc(start, tz)),
+                end_date: Some(get_day_end_utc(end, tz)),
+                project: self.project.clone(),
+                language: self.language.clone(),
+                entity: None,
+                type_filter: None,
+            },
+        )?;
+
+        Ok((start, actual_seconds))
+    }
+
+    /// Progress of all of a user's goals in their timezone
+    pub fn progress_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        timezone: &str,
+    ) -> QueryResult<Vec<GoalProgress>> {
+        let tz = parse_timezone(timezone);
+        let today = get_today_in_timezone(tz);
+
+        Self::list_for_user(conn, user_id)?
+            .iter()
+            .map(|goal| goal.progress(conn, tz, today))
+            .collect()
+    }
+
+    /// Coded time towards the goal in each of the given periods, newest first
+    fn period_seconds(
+        &self,
+        conn: &mut PgConnection,
+        tz: Tz,
+        ranges: &[(NaiveDate, NaiveDate)],
+    ) -> QueryResult<HashMap<NaiveDate, i64>> {
+        let (Some(&(_, last_end)), Some(&(first_start, _))) = (ranges.first(), ranges.last())
+        else {
+            return Ok(HashMap::new());
+        };
+        let period_starts: Vec<NaiveDate> = ranges.iter().map(|(start, _)| *start).collect();
+
+        let rows: Vec<PeriodDurationRow> = instrumented::load("Goal::period_durations", || {
+            diesel::sql_query(
+                "SELECT period_start, total_seconds \
+                     FROM calculate_goal_period_durations($1, $2, $3, $4, $5, $6, $7, $8)",
+            )
+            .bind::<Int4, _>(self.user_id)
+            .bind::<Array<Date>, _>(&period_starts)
+            .bind::<Timestamptz, _>(get_day_start_utc(first_start, tz))
+            .bind::<Timestamptz, _>(get_day_end_utc(last_end, tz))
+            .bind::<Text, _>(tz.name())
+            .bind::<SqlNullable<Text>, _>(self.project.as_deref())
+            .bind::<SqlNullable<Text>, _>(self.language.as_deref())
+            .bind::<Int4, _>(TIMEOUT_SECONDS)
+            .load(conn)
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.period_start, row.total_seconds))
+            .collect())
+    }
+
+    /// Compute the goal's recent history and current streak in the user's timezone
+    pub fn progress(
+        &self,
+        conn: &mut PgConnection,
+        tz: Tz,
+        today: NaiveDate,
+    ) -> QueryResult<GoalProgress> {
+        let period = self.period();
+        let goal_seconds = self.target_seconds as i64;
+        let ranges = period.ranges(today, period.streak_lookback());
+        let seconds_by_period = self.period_seconds(conn, tz, &ranges)?;
+        let mut history = Vec::with_capacity(GOAL_HISTORY_PERIODS);
+        let mut statuses = Vec::new();
+
+        for (index, (start, end)) in ranges.into_iter().enumerate() {
+            // past the visible history only the streak matters, so stop once it is broken
+            if index >= GOAL_HISTORY_PERIODS && statuses.contains(&RangeStatus::Fail) {
+                break;
+            }
+
+            let actual_seconds = seconds_by_period.get(&start).copied().unwrap_or(0);
+
+            let status = range_status(
+                actual_seconds,
+                goal_seconds,
+                self.is_ignored(period, start),
+                index == 0,
+            );
+            statuses.push(status);
+
+            if index < GOAL_HISTORY_PERIODS {
+                history.push(GoalPeriodProgress {
+                    start,
+                    end,
+                    actual_seconds,
+                    goal_seconds,
+                    status,
+                });
+            }
+        }
+
+        history.reverse();
+        let current = history.last();
+        let status = current.map_or(RangeStatus::Pending, |p| p.status);
+        let percent = current.map_or(0.0, |p| {
+            ((p.actual_seconds as f64 / goal_seconds as f64) * 100.0).min(100.0)
+        });
+
+        Ok(GoalProgress {
+            id: self.id,
+            title: self.title(),
+            period,
+            target_seconds: goal_seconds,
+            project: self.project.clone(),
+            language: self.language.clone(),
+            ignored_weekdays: mask_to_weekdays(self.ignored_weekdays),
+            status,
+            percent: (percent * 100.0).round() / 100.0,
+            current_streak: current_streak(&statuses),
+            history,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
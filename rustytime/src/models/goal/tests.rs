@@ -0,0 +1,136 @@
+use super::*;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn goal(period: GoalPeriod) -> Goal {
+    Goal {
+        id: 1,
+        user_id: 1,
+        period: period.as_str().to_string(),
+        target_seconds: 7200,
+        project: None,
+        language: None,
+        ignored_weekdays: 0,
+        last_reached_on: None,
+    }
+}
+
+// ==================== GoalPeriod tests ====================
+
+#[test]
+fn daily_ranges_are_single_days_newest_first() {
+    let ranges = GoalPeriod::Day.ranges(date(2026, 3, 18), 3);
+    assert_eq!(
+        ranges,
+        vec![
+            (date(2026, 3, 18), date(2026, 3, 18)),
+            (date(2026, 3, 17), date(2026, 3, 17)),
+            (date(2026, 3, 16), date(2026, 3, 16)),
+        ]
+    );
+}
+
+#[test]
+fn weekly_ranges_start_on_monday() {
+    // 2026-03-18 is a wednesday
+    let ranges = GoalPeriod::Week.ranges(date(2026, 3, 18), 2);
+    assert_eq!(
+        ranges,
+        vec![
+            (date(2026, 3, 16), date(2026, 3, 22)),
+            (date(2026, 3, 9), date(2026, 3, 15)),
+        ]
+    );
+}
+
+#[test]
+fn goal_period_round_trip() {
+    for period in [GoalPeriod::Day, GoalPeriod::Week] {
+        assert_eq!(GoalPeriod::from(period.as_str()), period);
+    }
+}
+
+// ==================== status and streak tests ====================
+
+#[test]
+fn range_status_prefers_success_over_ignored() {
+    assert_eq!(range_status(7200, 7200, true, false), RangeStatus::Success);
+    assert_eq!(range_status(100, 7200, true, false), RangeStatus::Ignored);
+    assert_eq!(range_status(100, 7200, false, true), RangeStatus::Pending);
+    assert_eq!(range_status(100, 7200, false, false), RangeStatus::Fail);
+}
+
+#[test]
+fn current_streak_skips_pending_and_ignored_periods() {
+    use RangeStatus::*;
+
+    assert_eq!(
+        current_streak(&[Pending, Success, Ignored, Success, Fail, Success]),
+        2
+    );
+    assert_eq!(current_streak(&[Success, Success, Success]), 3);
+    assert_eq!(current_streak(&[Fail, Success]), 0);
+    assert_eq!(current_streak(&[]), 0);
+}
+
+// ==================== weekday tests ====================
+
+#[test]
+fn weekday_mask_round_trip() {
+    let days = vec!["saturday".to_string(), "Sunday".to_string()];
+    let mask = weekdays_to_mask(&days).unwrap();
+
+    assert_eq!(mask, 0b110_0000);
+    assert_eq!(mask_to_weekdays(mask), vec!["saturday", "sunday"]);
+}
+
+#[test]
+fn weekday_mask_rejects_unknown_days() {
+    assert!(weekdays_to_mask(&["funday".to_string()]).is_err());
+    assert_eq!(weekdays_to_mask(&[]).unwrap(), 0);
+}
+
+#[test]
+fn ignored_weekdays_only_apply_to_daily_goals() {
+    let mut daily = goal(GoalPeriod::Day);
+    daily.ignored_weekdays = weekdays_to_mask(&["sunday".to_string()]).unwrap();
+    let mut weekly = goal(GoalPeriod::Week);
+    weekly.ignored_weekdays = daily.ignored_weekdays;
+
+    // 2026-03-22 is a sunday
+    assert!(daily.is_ignored(GoalPeriod::Day, date(2026, 3, 22)));
+    assert!(!daily.is_ignored(GoalPeriod::Day, date(2026, 3, 23)));
+    assert!(!weekly.is_ignored(GoalPeriod::Week, date(2026, 3, 22)));
+}
+
+// ==================== title tests ====================
+
+#[test]
+fn title_mentions_scope() {
+    let mut scoped = goal(GoalPeriod::Week);
+    scoped.project = Some("rustytime".to_string());
+    scoped.language = Some("Rust".to_string());
+
+    assert_eq!(goal(GoalPeriod::Day).title(), "Code 2h 0m per day");
+    assert_eq!(
+        scoped.title(),
+        "Code 2h 0m per week in rustytime using Rust"
+    );
+}
+
+// ==================== reached tests ====================
+
+#[test]
+fn newly_reached_once_per_period() {
+    let mut daily = goal(GoalPeriod::Day);
+    let today = date(2026, 3, 24);
+
+    assert!(!daily.is_newly_reached(today, 7199));
+    assert!(daily.is_newly_reached(today, 7200));
+
+    daily.last_reached_on = Some(today);
+    assert!(!daily.is_newly_reached(today, 9000));
+    assert!(daily.is_newly_reached(date(2026, 3, 25), 7200));
+}
@@ -1,5 +1,5 @@
 use axum::http::HeaderMap;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use chrono_tz::Tz;
 use diesel::dsl::sql;
 use diesel::prelude::*;
@@ -8,11 +8,19 @@ use ipnetwork::IpNetwork;
 use schemars::JsonSchema;
 use serde::de::{self, Deserializer, Visitor};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::fmt;
+use uuid::Uuid;
 
+use crate::models::language_mapping::LanguageOverrides;
+use crate::models::project::Project;
+use crate::models::project_rule::ProjectRuleSet;
 use crate::schema::heartbeats::{self};
-use crate::utils::http::parse_user_agent;
+use crate::schema::users;
+use crate::utils::http::{parse_plugin, parse_plugin_from_user_agent, parse_user_agent};
 use crate::utils::instrumented;
+use crate::utils::language::normalize_language;
 use crate::utils::time::{
     TimeFormat, get_day_start_utc, get_month_start_date, get_week_start_date,
     human_readable_duration, parse_timezone,
@@ -80,14 +88,16 @@ pub const TIMEOUT_SECONDS: i32 = 120; // 2 minutes in seconds
 // Character limits
 const MAX_ENTITY_LENGTH: usize = 512;
 const MAX_TYPE_LENGTH: usize = 50;
-const MAX_PROJECT_LENGTH: usize = 100;
+pub const MAX_PROJECT_LENGTH: usize = 100;
 const MAX_BRANCH_LENGTH: usize = 100;
-const MAX_LANGUAGE_LENGTH: usize = 50;
+pub const MAX_LANGUAGE_LENGTH: usize = 50;
 const MAX_CATEGORY_LENGTH: usize = 50;
 const MAX_EDITOR_LENGTH: usize = 50;
 const MAX_OS_LENGTH: usize = 100;
 const MAX_MACHINE_LENGTH: usize = 100;
 const MAX_USER_AGENT_LENGTH: usize = 255;
+const MAX_PLUGIN_LENGTH: usize = 100;
+const MAX_PLUGIN_VERSION_LENGTH: usize = 50;
 const MAX_DEPENDENCIES: usize = 50;
 const MAX_DEPENDENCY_LENGTH: usize = 254;
 
@@ -168,6 +178,140 @@ pub enum SourceType {
     WakaTimeImport = 4,
 }
 
+impl SourceType {
+    /// Name of a stored source type, `unknown` for missing or unrecognized values
+    pub fn name(value: Option<i16>) -> &'static str {
+        match value {
+            Some(0) => "direct_entry",
+            Some(1) => "seeding",
+            Some(2) => "test_entry",
+            Some(3) => "hackatime_import",
+            Some(4) => "wakatime_import",
+            _ => "unknown",
+        }
+    }
+}
+
+/// How heartbeat entities (file paths, urls) are stored
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(i16)]
+pub enum EntityPrivacy {
+    #[default]
+    Full = 0,
+    Basename = 1,
+    Hashed = 2,
+    Hidden = 3,
+}
+
+impl EntityPrivacy {
+    pub fn from_i16(value: i16) -> Self {
+        match value {
+            1 => Self::Basename,
+            2 => Self::Hashed,
+            3 => Self::Hidden,
+            _ => Self::Full,
+        }
+    }
+
+    /// Apply this mode to an entity, hashing with the given salt when needed
+    pub fn apply(self, entity: String, salt: &Uuid) -> String {
+        match self {
+            Self::Full => entity,
+            Self::Basename => entity
+                .rsplit(['/', '\\'])
+                .find(|part| !part.is_empty())
+                .map(str::to_string)
+                .unwrap_or(entity),
+            Self::Hashed => {
+                let mut hasher = Sha256::new();
+                hasher.update(salt.as_bytes());
+                hasher.update(entity.as_bytes());
+                format!("{:x}", hasher.finalize())
+            }
+            Self::Hidden => String::new(),
+        }
+    }
+}
+
+/// A user's entity privacy mode along with per-project overrides
+#[derive(Clone, Debug, Default)]
+pub struct EntityPrivacySettings {
+    pub default: EntityPrivacy,
+    pub salt: Uuid,
+    /// Overrides keyed by project name, aliases inherit from their target
+    pub projects: HashMap<String, EntityPrivacy>,
+}
+
+impl EntityPrivacySettings {
+    pub fn load(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        let (default, salt) = instrumented::first("EntityPrivacySettings::load", || {
+            users::table
+                .find(user_id)
+                .select((users::entity_privacy, users::entity_salt))
+                .first::<(i16, Uuid)>(conn)
+        })?;
+
+        let projects = Project::entity_privacy_overrides(conn, user_id)?
+            .into_iter()
+            .map(|(name, mode)| (name, EntityPrivacy::from_i16(mode)))
+            .collect();
+
+        Ok(Self {
+            default: EntityPrivacy::from_i16(default),
+            salt,
+            projects,
+        })
+    }
+
+    pub fn mode_for(&self, project: Option<&str>) -> EntityPrivacy {
+        project
+            .and_then(|name| self.projects.get(name))
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Per-user settings applied to incoming heartbeats before they are stored
+#[derive(Clone, Debug, Default)]
+pub struct IngestSettings {
+    pub project_rules: ProjectRuleSet,
+    pub languages: LanguageOverrides,
+    pub entity_privacy: EntityPrivacySettings,
+}
+
+impl IngestSettings {
+    pub fn load(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        Ok(Self {
+            project_rules: ProjectRuleSet::load(conn, user_id)?,
+            languages: LanguageOverrides::load(conn, user_id)?,
+            entity_privacy: EntityPrivacySettings::load(conn, user_id)?,
+        })
+    }
+
+    /// Apply project rules, language overrides and entity privacy, in that order
+    pub fn apply(
+        &self,
+        project: &mut Option<String>,
+        language: &mut Option<String>,
+        entity: &mut String,
+    ) {
+        if let Some(target) = self.project_rules.map(project.as_deref(), entity) {
+            *project = truncate_optional_string(Some(target.to_string()), MAX_PROJECT_LENGTH);
+        }
+
+        if !self.languages.is_empty() {
+            *language = truncate_optional_string(
+                self.languages.apply(language.take(), entity),
+                MAX_LANGUAGE_LENGTH,
+            );
+        }
+
+        let mode = self.entity_privacy.mode_for(project.as_deref());
+        *entity = mode.apply(std::mem::take(entity), &self.entity_privacy.salt);
+    }
+}
+
 #[derive(QueryableByName)]
 pub struct UserDurationRow {
     #[diesel(sql_type = Int4)]
@@ -184,6 +328,74 @@ struct NullableNameDurationRow {
     total_seconds: i64,
 }
 
+#[derive(QueryableByName, Debug, Clone)]
+pub struct TopCategoryRow {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = BigInt)]
+    pub total_seconds: i64,
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct TopLanguageRow {
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = BigInt)]
+    pub total_seconds: i64,
+}
+
+#[derive(QueryableByName)]
+struct LineStatsRow {
+    #[diesel(sql_type = Text)]
+    metric_type: String,
+    #[diesel(sql_type = SqlNullable<Text>)]
+    name: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    line_additions: i64,
+    #[diesel(sql_type = BigInt)]
+    line_deletions: i64,
+    #[diesel(sql_type = BigInt)]
+    files_written: i64,
+    #[diesel(sql_type = BigInt)]
+    write_seconds: i64,
+    #[diesel(sql_type = BigInt)]
+    total_seconds: i64,
+    #[diesel(sql_type = BigInt)]
+    ai_line_changes: i64,
+    #[diesel(sql_type = BigInt)]
+    human_line_changes: i64,
+}
+
+impl From<LineStatsRow> for LineChangeStat {
+    fn from(row: LineStatsRow) -> Self {
+        Self {
+            name: row.name.unwrap_or_default(),
+            line_additions: row.line_additions,
+            line_deletions: row.line_deletions,
+            files_written: row.files_written,
+            write_seconds: row.write_seconds,
+            total_seconds: row.total_seconds,
+            write_ratio: write_ratio(row.write_seconds, row.total_seconds),
+            ai_line_changes: row.ai_line_changes,
+            human_line_changes: row.human_line_changes,
+        }
+    }
+}
+
+#[derive(QueryableByName)]
+struct DependencyMetricRow {
+    #[diesel(sql_type = Text)]
+    scope: String,
+    #[diesel(sql_type = SqlNullable<Text>)]
+    scope_name: Option<String>,
+    #[diesel(sql_type = SqlNullable<Text>)]
+    name: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    total_seconds: i64,
+    #[diesel(sql_type = BigInt)]
+    total_time: i64,
+}
+
 #[derive(QueryableByName)]
 struct DashboardMetricRow {
     #[diesel(sql_type = Text)]
@@ -211,7 +423,66 @@ pub struct DashboardStats {
     pub top_languages: Vec<UsageStat>,
     pub top_oses: Vec<UsageStat>,
     pub top_editors: Vec<UsageStat>,
+    pub top_machines: Vec<UsageStat>,
+    pub top_categories: Vec<UsageStat>,
+    pub line_changes: LineChangeStats,
 }
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Default)]
+pub struct LineChangeStat {
+    pub name: String,
+    pub line_additions: i64,
+    pub line_deletions: i64,
+    pub files_written: i64,
+    pub write_seconds: i64,
+    pub total_seconds: i64,
+    /// Share of coded time spent writing, between 0 and 1
+    pub write_ratio: f32,
+    pub ai_line_changes: i64,
+    pub human_line_changes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema, Default)]
+pub struct LineChangeStats {
+    pub total: LineChangeStat,
+    pub daily: Vec<LineChangeStat>,
+    pub projects: Vec<LineChangeStat>,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DependencyGroup {
+    pub name: String,
+    pub dependencies: Vec<UsageStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct DependencyStats {
+    pub total_time: i64,
+    pub top_dependencies: Vec<UsageStat>,
+    pub by_project: Vec<DependencyGroup>,
+    pub by_language: Vec<DependencyGroup>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct DailyTotal {
+    pub date: NaiveDate,
+    pub total_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct ProjectStats {
+    pub total_time: i64,
+    pub daily: Vec<DailyTotal>,
+    pub weekdays: Vec<i64>,
+    pub hours: Vec<i64>,
+    pub top_branches: Vec<UsageStat>,
+    pub top_entities: Vec<UsageStat>,
+    pub top_languages: Vec<UsageStat>,
+    pub top_editors: Vec<UsageStat>,
+    pub top_categories: Vec<UsageStat>,
+    pub line_changes: LineChangeStats,
+}
+
 #[derive(QueryableByName, Debug, Clone, Serialize)]
 pub struct DailyActivity {
     #[diesel(sql_type = Date)]
@@ -239,6 +510,8 @@ pub struct HackatimeHeartbeat {
     pub user_agent: Option<String>,
     pub line_additions: Option<i32>,
     pub line_deletions: Option<i32>,
+    pub ai_line_changes: Option<i32>,
+    pub human_line_changes: Option<i32>,
     pub lineno: Option<i32>,
     pub lines: Option<i32>,
     pub cursorpos: Option<i32>,
@@ -259,7 +532,7 @@ pub struct HackatimeHeartbeat {
 }
 
 impl HackatimeHeartbeat {
-    pub fn to_new_heartbeat(&self, user_id: i32) -> NewHeartbeat {
+    pub fn to_new_heartbeat(&self, user_id: i32, settings: &IngestSettings) -> NewHeartbeat {
         let source_type_ = match self.source_type.as_deref() {
             Some("direct_entry") => SourceType::HackatimeImport,
             Some("wakapi_import") => SourceType::WakaTimeImport,
@@ -280,7 +553,13 @@ impl HackatimeHeartbeat {
                 .collect()
         });
 
-        NewHeartbeat {
+        let (plugin, plugin_version) = self
+            .user_agent
+            .as_deref()
+            .and_then(parse_plugin_from_user_agent)
+            .unzip();
+
+        let mut new_heartbeat = NewHeartbeat {
             user_id,
             project_id: None,
             branch: truncate_optional_string(self.branch.clone(), MAX_BRANCH_LENGTH),
@@ -293,7 +572,10 @@ impl HackatimeHeartbeat {
                 MAX_EDITOR_LENGTH,
             ),
             entity: truncate_string(self.entity.clone(), MAX_ENTITY_LENGTH),
-            language: truncate_optional_string(self.language.clone(), MAX_LANGUAGE_LENGTH),
+            language: truncate_optional_string(
+                normalize_language(self.language.clone()),
+                MAX_LANGUAGE_LENGTH,
+            ),
             machine: truncate_optional_string(self.machine.clone(), MAX_MACHINE_LENGTH),
             operating_system: truncate_optional_string(
                 self.operating_system
@@ -309,6 +591,8 @@ impl HackatimeHeartbeat {
             ),
             line_additions: self.line_additions,
             line_deletions: self.line_deletions,
+            ai_line_changes: self.ai_line_changes,
+            human_line_changes: self.human_line_changes,
             lineno: self.lineno,
             lines: self.lines,
             cursorpos: self.cursorpos,
@@ -322,10 +606,48 @@ impl HackatimeHeartbeat {
                 .parse()
                 .unwrap_or_else(|_| "127.0.0.1/32".parse().unwrap()),
             source_type: Some(source_type_ as i16),
-        }
+            plugin: truncate_optional_string(plugin, MAX_PLUGIN_LENGTH),
+            plugin_version: truncate_optional_string(
+                plugin_version.flatten(),
+                MAX_PLUGIN_VERSION_LENGTH,
+            ),
+        };
+        settings.apply(
+            &mut new_heartbeat.project,
+            &mut new_heartbeat.language,
+            &mut new_heartbeat.entity,
+        );
+        new_heartbeat
     }
 }
 
+/// Share of `total_seconds` spent writing, rounded to 4 decimals
+fn write_ratio(write_seconds: i64, total_seconds: i64) -> f32 {
+    if total_seconds > 0 {
+        ((write_seconds as f32 / total_seconds as f32) * 10000.0).round() / 10000.0
+    } else {
+        0.0
+    }
+}
+
+/// Expand sparse daily totals into one entry per day between `start` and `end`
+fn fill_daily_totals(
+    rows: Vec<(NaiveDate, i64)>,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<DailyTotal> {
+    let totals: HashMap<NaiveDate, i64> = rows.into_iter().collect();
+
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| DailyTotal {
+            date,
+            total_seconds: totals.get(&date).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
 fn deserialize_hackatime_time<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
@@ -428,9 +750,12 @@ pub struct HeartbeatRequest {
     pub lines: Option<i32>,
     pub line_additions: Option<i32>,
     pub line_deletions: Option<i32>,
+    pub ai_line_changes: Option<i32>,
+    pub human_line_changes: Option<i32>,
     pub lineno: Option<i32>,
     pub cursorpos: Option<i32>,
     pub is_write: Option<bool>,
+    pub machine: Option<String>,
     pub plugin: Option<String>,
     pub user_agent: Option<String>,
 }
@@ -498,6 +823,10 @@ pub struct Heartbeat {
     pub cursorpos: Option<i32>,
     pub source_type: Option<i16>,
     pub project_id: Option<i32>,
+    pub plugin: Option<String>,
+    pub plugin_version: Option<String>,
+    pub ai_line_changes: Option<i32>,
+    pub human_line_changes: Option<i32>,
 }
 
 #[derive(Insertable, Deserialize, Debug)]
@@ -526,6 +855,10 @@ pub struct NewHeartbeat {
     pub cursorpos: Option<i32>,
     pub source_type: Option<i16>,
     pub project_id: Option<i32>,
+    pub plugin: Option<String>,
+    pub plugin_version: Option<String>,
+    pub ai_line_changes: Option<i32>,
+    pub human_line_changes: Option<i32>,
 }
 
 #[derive(Serialize)]
@@ -543,10 +876,13 @@ pub struct SanitizedHeartbeatRequest {
     pub lines: Option<i32>,
     pub line_additions: Option<i32>,
     pub line_deletions: Option<i32>,
+    pub ai_line_changes: Option<i32>,
+    pub human_line_changes: Option<i32>,
     pub lineno: Option<i32>,
     pub cursorpos: Option<i32>,
     pub is_write: Option<bool>,
     pub source_type: Option<i16>,
+    pub machine: Option<String>,
     pub plugin: Option<String>,
     pub user_agent: Option<String>,
 }
@@ -594,20 +930,32 @@ impl SanitizedHeartbeatRequest {
             project: truncate_optional_string(request.project, MAX_PROJECT_LENGTH),
             project_root_count: request.project_root_count,
             branch: truncate_optional_string(request.branch, MAX_BRANCH_LENGTH),
-            language: truncate_optional_string(request.language, MAX_LANGUAGE_LENGTH),
+            language: truncate_optional_string(
+                normalize_language(request.language),
+                MAX_LANGUAGE_LENGTH,
+            ),
             dependencies,
             lines: request.lines,
             line_additions: request.line_additions,
             line_deletions: request.line_deletions,
+            ai_line_changes: request.ai_line_changes,
+            human_line_changes: request.human_line_changes,
             lineno: request.lineno,
             cursorpos: request.cursorpos,
             is_write: request.is_write,
             source_type: Some(source_type_ as i16),
+            machine: truncate_optional_string(request.machine, MAX_MACHINE_LENGTH),
             user_agent: request.user_agent,
             plugin: request.plugin,
         }
     }
 
+    /// Apply the user's project rules, language overrides and entity privacy, in that order
+    pub fn apply_ingest_settings(mut self, settings: &IngestSettings) -> Self {
+        settings.apply(&mut self.project, &mut self.language, &mut self.entity);
+        self
+    }
+
     pub fn into_new_heartbeat(
         mut self,
         user_id: i32,
@@ -624,14 +972,24 @@ impl SanitizedHeartbeatRequest {
                     .and_then(|value| value.to_str().ok())
                     .map(|value| value.to_string())
             })
-            .or_else(|| self.plugin.take())
+            .or_else(|| self.plugin.clone())
             .unwrap_or_default();
 
-        // Extract machine name from headers
-        let machine = headers
-            .get("x-machine-name")
-            .and_then(|value| value.to_str().ok())
-            .map(|s| s.to_string());
+        // Get machine name with fallbacks (body -> header)
+        let machine = self.machine.take().or_else(|| {
+            headers
+                .get("x-machine-name")
+                .and_then(|value| value.to_str().ok())
+                .map(|s| s.to_string())
+        });
+
+        // Get plugin identity with fallbacks (plugin -> user agent)
+        let (plugin, plugin_version) = self
+            .plugin
+            .as_deref()
+            .and_then(parse_plugin)
+            .or_else(|| parse_plugin_from_user_agent(&user_agent))
+            .unzip();
 
         let (operating_system, editor) = if user_agent.is_empty() {
             (None, None)
@@ -672,6 +1030,13 @@ impl SanitizedHeartbeatRequest {
             cursorpos: self.cursorpos,
             source_type: self.source_type,
             project_id: None,
+            ai_line_changes: self.ai_line_changes,
+            human_line_changes: self.human_line_changes,
+            plugin: truncate_optional_string(plugin, MAX_PLUGIN_LENGTH),
+            plugin_version: truncate_optional_string(
+                plugin_version.flatten(),
+                MAX_PLUGIN_VERSION_LENGTH,
+            ),
         }
     }
 }
@@ -708,6 +1073,10 @@ impl NewHeartbeat {
             cursorpos: None,
             source_type: None,
             project_id: None,
+            plugin: None,
+            plugin_version: None,
+            ai_line_changes: None,
+            human_line_changes: None,
         }
     }
 
@@ -716,9 +1085,11 @@ impl NewHeartbeat {
         user_id: i32,
         ip_address: IpNetwork,
         headers: &HeaderMap,
+        settings: &IngestSettings,
     ) -> Self {
-        let sanitized = SanitizedHeartbeatRequest::from_request(request);
-        sanitized.into_new_heartbeat(user_id, ip_address, headers)
+        SanitizedHeartbeatRequest::from_request(request)
+            .apply_ingest_settings(settings)
+            .into_new_heartbeat(user_id, ip_address, headers)
     }
 }
 
@@ -780,8 +1151,10 @@ impl Heartbeat {
         })
     }
 
+    /// Heartbeats per UTC day of the last week, of one user or everyone
     pub fn get_daily_activity_last_week(
         conn: &mut PgConnection,
+        user_id: Option<i32>,
     ) -> QueryResult<Vec<DailyActivity>> {
         let now = chrono::Utc::now();
 
@@ -789,12 +1162,16 @@ impl Heartbeat {
         let seven_days_ago = start_today - chrono::Duration::days(7);
 
         instrumented::load("Heartbeat::daily_activity_last_week", || {
-            heartbeats::table
+            let mut query = heartbeats::table
                 .filter(heartbeats::time.ge(seven_days_ago))
                 .select((sql::<Date>("DATE(time)"), sql::<BigInt>("COUNT(*)")))
                 .group_by(sql::<Date>("DATE(time)"))
                 .order_by(sql::<Date>("DATE(time)"))
-                .load::<(chrono::NaiveDate, i64)>(conn)
+                .into_boxed();
+            if let Some(user_id) = user_id {
+                query = query.filter(heartbeats::user_id.eq(user_id));
+            }
+            query.load::<(chrono::NaiveDate, i64)>(conn)
         })
         .map(|rows| {
             rows.into_iter()
@@ -803,6 +1180,34 @@ impl Heartbeat {
         })
     }
 
+    /// Time of the user's latest heartbeat
+    pub fn last_heartbeat_at(
+        conn: &mut PgConnection,
+        user_id: i32,
+    ) -> QueryResult<Option<DateTime<Utc>>> {
+        instrumented::first("Heartbeat::last_heartbeat_at", || {
+            heartbeats::table
+                .filter(heartbeats::user_id.eq(user_id))
+                .select(diesel::dsl::max(heartbeats::time))
+                .first(conn)
+        })
+    }
+
+    /// Heartbeat counts of a user per source type, most common first
+    pub fn count_by_source_type(
+        conn: &mut PgConnection,
+        user_id: i32,
+    ) -> QueryResult<Vec<(Option<i16>, i64)>> {
+        instrumented::load("Heartbeat::count_by_source_type", || {
+            heartbeats::table
+                .filter(heartbeats::user_id.eq(user_id))
+                .group_by(heartbeats::source_type)
+                .select((heartbeats::source_type, diesel::dsl::count_star()))
+                .order(diesel::dsl::count_star().desc())
+                .load(conn)
+        })
+    }
+
     /// Calculate total duration in seconds using database function
     pub fn get_user_duration_seconds(
         conn: &mut PgConnection,
@@ -873,6 +1278,203 @@ impl Heartbeat {
         }
     }
 
+    /// Get the top categories for a user between two timestamps
+    pub fn top_categories_by_range(
+        conn: &mut PgConnection,
+        user_id: i32,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: i32,
+    ) -> QueryResult<Vec<TopCategoryRow>> {
+        instrumented::load("Heartbeat::top_categories_by_range", || {
+            diesel::sql_query(
+                "SELECT name, total_seconds \
+                 FROM top_categories_by_range($1, $2, $3, $4, $5)",
+            )
+            .bind::<Int4, _>(user_id)
+            .bind::<Int4, _>(TIMEOUT_SECONDS)
+            .bind::<Timestamptz, _>(start_time)
+            .bind::<Timestamptz, _>(end_time)
+            .bind::<Int4, _>(limit)
+            .load(conn)
+        })
+    }
+
+    /// Get the top languages for a user between two timestamps
+    pub fn top_languages_by_range(
+        conn: &mut PgConnection,
+        user_id: i32,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+        limit: i32,
+    ) -> QueryResult<Vec<TopLanguageRow>> {
+        instrumented::load("Heartbeat::top_languages_by_range", || {
+            diesel::sql_query(
+                "SELECT name, total_seconds \
+                 FROM top_languages_by_range($1, $2, $3, $4, $5)",
+            )
+            .bind::<Int4, _>(user_id)
+            .bind::<Int4, _>(TIMEOUT_SECONDS)
+            .bind::<Timestamptz, _>(start_time)
+            .bind::<Timestamptz, _>(end_time)
+            .bind::<Int4, _>(limit)
+            .load(conn)
+        })
+    }
+
+    /// Get stats for a single project (and its aliases) filtered by time range
+    pub fn get_project_stats_by_range(
+        conn: &mut PgConnection,
+        user_id: i32,
+        project_id: i32,
+        range: TimeRange,
+        user_timezone: &str,
+    ) -> QueryResult<ProjectStats> {
+        let tz = parse_timezone(user_timezone);
+        let now = Utc::now();
+        let start_time = Self::start_boundary_utc(range, tz, now);
+
+        let rows: Vec<DashboardMetricRow> =
+            instrumented::load("Heartbeat::project_stats_by_range", || {
+                diesel::sql_query(
+                    "SELECT metric_type, name, total_seconds, total_time \
+                     FROM calculate_project_stats_by_range($1, $2, $3, $4, $5, $6)",
+                )
+                .bind::<Int4, _>(user_id)
+                .bind::<Int4, _>(project_id)
+                .bind::<SqlNullable<Timestamptz>, _>(start_time)
+                .bind::<Text, _>(tz.name())
+                .bind::<Int4, _>(TIMEOUT_SECONDS)
+                .bind::<Int4, _>(10)
+                .load(conn)
+            })?;
+
+        let mut total_time: i64 = 0;
+        let mut daily_rows = Vec::new();
+        let mut weekdays = vec![0; 7];
+        let mut hours = vec![0; 24];
+        let mut branch_rows = Vec::new();
+        let mut entity_rows = Vec::new();
+        let mut language_rows = Vec::new();
+        let mut editor_rows = Vec::new();
+        let mut category_rows = Vec::new();
+
+        for row in rows {
+            let bucket = row.name.as_deref().unwrap_or_default();
+            match row.metric_type.as_str() {
+                "total_time" => total_time = row.total_time,
+                "day" => {
+                    if let Ok(date) = NaiveDate::parse_from_str(bucket, "%Y-%m-%d") {
+                        daily_rows.push((date, row.total_seconds));
+                    }
+                }
+                "weekday" => {
+                    if let Some(slot) = bucket
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| weekdays.get_mut(i))
+                    {
+                        *slot = row.total_seconds;
+                    }
+                }
+                "hour" => {
+                    if let Some(slot) = bucket.parse::<usize>().ok().and_then(|i| hours.get_mut(i))
+                    {
+                        *slot = row.total_seconds;
+                    }
+                }
+                "branch" => branch_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                "entity" => entity_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                "language" => language_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                "editor" => editor_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                "category" => category_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                _ => {}
+            }
+        }
+
+        let line_changes =
+            Self::get_line_stats(conn, user_id, start_time, user_timezone, Some(project_id))?;
+
+        let today = now.with_timezone(&tz).date_naive();
+        let first_day = start_time
+            .map(|start| start.with_timezone(&tz).date_naive())
+            .or_else(|| daily_rows.iter().map(|(date, _)| *date).min())
+            .unwrap_or(today);
+
+        Ok(ProjectStats {
+            total_time,
+            daily: fill_daily_totals(daily_rows, first_day, today),
+            weekdays,
+            hours,
+            top_branches: Self::map_usage_stats(branch_rows, total_time),
+            top_entities: Self::map_usage_stats(entity_rows, total_time),
+            top_languages: Self::map_usage_stats(language_rows, total_time),
+            top_editors: Self::map_usage_stats(editor_rows, total_time),
+            top_categories: Self::map_usage_stats(category_rows, total_time),
+            line_changes,
+        })
+    }
+
+    /// Get line changes, files written and write time since an optional start time
+    pub fn get_line_stats(
+        conn: &mut PgConnection,
+        user_id: i32,
+        start_time: Option<DateTime<Utc>>,
+        user_timezone: &str,
+        project_id: Option<i32>,
+    ) -> QueryResult<LineChangeStats> {
+        let tz = parse_timezone(user_timezone);
+
+        let rows: Vec<LineStatsRow> = instrumented::load("Heartbeat::line_stats", || {
+            diesel::sql_query(
+                "SELECT metric_type, name, line_additions, line_deletions, files_written, \
+                 write_seconds, total_seconds, ai_line_changes, human_line_changes \
+                 FROM calculate_line_stats_by_range($1, $2, $3, $4, $5, $6)",
+            )
+            .bind::<Int4, _>(user_id)
+            .bind::<SqlNullable<Timestamptz>, _>(start_time)
+            .bind::<Text, _>(tz.name())
+            .bind::<Int4, _>(TIMEOUT_SECONDS)
+            .bind::<SqlNullable<Int4>, _>(project_id)
+            .bind::<Int4, _>(10)
+            .load(conn)
+        })?;
+
+        let mut stats = LineChangeStats::default();
+        for row in rows {
+            match row.metric_type.as_str() {
+                "total" => stats.total = row.into(),
+                "day" => stats.daily.push(row.into()),
+                "project" => stats.projects.push(row.into()),
+                _ => {}
+            }
+        }
+
+        stats.daily.sort_by(|a, b| a.name.cmp(&b.name));
+        stats.projects.sort_by(|a, b| {
+            (b.line_additions + b.line_deletions)
+                .cmp(&(a.line_additions + a.line_deletions))
+                .then(b.total_seconds.cmp(&a.total_seconds))
+        });
+
+        Ok(stats)
+    }
+
     fn map_usage_stats(rows: Vec<NullableNameDurationRow>, total_time: i64) -> Vec<UsageStat> {
         rows.into_iter()
             .map(|row| UsageStat {
@@ -890,7 +1492,7 @@ impl Heartbeat {
     }
 
     /// Compute the start boundary in UTC for a given time range based on user's timezone
-    fn start_boundary_utc(
+    pub fn start_boundary_utc(
         range: TimeRange,
         tz: Tz,
         now_utc: DateTime<Utc>,
@@ -952,6 +1554,8 @@ impl Heartbeat {
         let mut editor_rows = Vec::new();
         let mut os_rows = Vec::new();
         let mut language_rows = Vec::new();
+        let mut machine_rows = Vec::new();
+        let mut category_rows = Vec::new();
 
         for row in filtered_rows {
             match row.metric_type.as_str() {
@@ -972,18 +1576,113 @@ impl Heartbeat {
                     name: row.name,
                     total_seconds: row.total_seconds,
                 }),
+                "machine" => machine_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
+                "category" => category_rows.push(NullableNameDurationRow {
+                    name: row.name,
+                    total_seconds: row.total_seconds,
+                }),
                 _ => {}
             }
         }
 
+        let line_changes = Self::get_line_stats(
+            conn,
+            user_id,
+            Self::start_boundary_utc(range, tz, now),
+            user_timezone,
+            None,
+        )?;
+
         Ok(DashboardStats {
             total_time,
             top_projects: Self::map_usage_stats(project_rows, total_time),
             top_languages: Self::map_usage_stats(language_rows, total_time),
             top_oses: Self::map_usage_stats(os_rows, total_time),
             top_editors: Self::map_usage_stats(editor_rows, total_time),
+            top_machines: Self::map_usage_stats(machine_rows, total_time),
+            top_categories: Self::map_usage_stats(category_rows, total_time),
+            line_changes,
         })
     }
+
+    /// Get dependency usage weighted by coded time, optionally grouped by project and language
+    pub fn get_dependency_stats_by_range(
+        conn: &mut PgConnection,
+        user_id: i32,
+        range: TimeRange,
+        user_timezone: &str,
+        group_limit: i32,
+    ) -> QueryResult<DependencyStats> {
+        let tz = parse_timezone(user_timezone);
+        let start_time = Self::start_boundary_utc(range, tz, Utc::now());
+
+        let rows: Vec<DependencyMetricRow> =
+            instrumented::load("Heartbeat::dependency_stats_by_range", || {
+                diesel::sql_query(
+                    "SELECT scope, scope_name, name, total_seconds, total_time \
+                     FROM calculate_dependency_stats_by_range($1, $2, $3, $4, $5)",
+                )
+                .bind::<Int4, _>(user_id)
+                .bind::<SqlNullable<Timestamptz>, _>(start_time)
+                .bind::<Int4, _>(TIMEOUT_SECONDS)
+                .bind::<Int4, _>(10)
+                .bind::<Int4, _>(group_limit)
+                .load(conn)
+            })?;
+
+        Ok(Self::group_dependency_rows(rows))
+    }
+
+    /// Split dependency rows into overall, per project and per language stats
+    fn group_dependency_rows(rows: Vec<DependencyMetricRow>) -> DependencyStats {
+        let total_time = rows.first().map(|row| row.total_time).unwrap_or(0);
+        let mut user_rows = Vec::new();
+        let mut project_rows: Vec<(String, Vec<NullableNameDurationRow>)> = Vec::new();
+        let mut language_rows: Vec<(String, Vec<NullableNameDurationRow>)> = Vec::new();
+
+        for row in rows {
+            let entry = NullableNameDurationRow {
+                name: row.name,
+                total_seconds: row.total_seconds,
+            };
+            let groups = match row.scope.as_str() {
+                "user" => {
+                    user_rows.push(entry);
+                    continue;
+                }
+                "project" => &mut project_rows,
+                "language" => &mut language_rows,
+                _ => continue,
+            };
+
+            // rows arrive ordered by group so only the last group needs checking
+            let scope_name = row.scope_name.unwrap_or_else(|| "Unknown".to_string());
+            match groups.last_mut() {
+                Some((name, entries)) if *name == scope_name => entries.push(entry),
+                _ => groups.push((scope_name, vec![entry])),
+            }
+        }
+
+        let map_groups = |groups: Vec<(String, Vec<NullableNameDurationRow>)>| {
+            groups
+                .into_iter()
+                .map(|(name, entries)| DependencyGroup {
+                    name,
+                    dependencies: Self::map_usage_stats(entries, total_time),
+                })
+                .collect()
+        };
+
+        DependencyStats {
+            total_time,
+            top_dependencies: Self::map_usage_stats(user_rows, total_time),
+            by_project: map_groups(project_rows),
+            by_language: map_groups(language_rows),
+        }
+    }
 }
 
 #[cfg(test)]
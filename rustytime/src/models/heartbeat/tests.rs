@@ -131,9 +131,12 @@ fn sanitized_heartbeat_request_category_defaults_to_coding() {
         lines: None,
         line_additions: None,
         line_deletions: None,
+        ai_line_changes: None,
+        human_line_changes: None,
         lineno: None,
         cursorpos: None,
         is_write: None,
+        machine: None,
         plugin: None,
         user_agent: None,
     };
@@ -163,9 +166,12 @@ fn sanitized_heartbeat_request_all_truncations_applied() {
         lines: None,
         line_additions: None,
         line_deletions: None,
+        ai_line_changes: None,
+        human_line_changes: None,
         lineno: None,
         cursorpos: None,
         is_write: None,
+        machine: None,
         plugin: None,
         user_agent: None,
     };
@@ -211,6 +217,52 @@ fn sanitized_heartbeat_into_new_heartbeat_maps_headers() {
     assert_eq!(new_heartbeat.ip_address, "1.1.1.1".parse().unwrap());
 }
 
+#[test]
+fn sanitized_heartbeat_prefers_body_machine_over_header() {
+    let mut request = sample_request();
+    request.machine = Some("body-machine".to_string());
+    let sanitized = SanitizedHeartbeatRequest::from_request(request);
+    let mut headers = HeaderMap::new();
+    headers.insert("x-machine-name", "header-machine".parse().unwrap());
+    let new_heartbeat = sanitized.into_new_heartbeat(1, "1.1.1.1".parse().unwrap(), &headers);
+    assert_eq!(new_heartbeat.machine, Some("body-machine".to_string()));
+}
+
+#[test]
+fn sanitized_heartbeat_falls_back_to_machine_header() {
+    let sanitized = SanitizedHeartbeatRequest::from_request(sample_request());
+    let mut headers = HeaderMap::new();
+    headers.insert("x-machine-name", "header-machine".parse().unwrap());
+    let new_heartbeat = sanitized.into_new_heartbeat(1, "1.1.1.1".parse().unwrap(), &headers);
+    assert_eq!(new_heartbeat.machine, Some("header-machine".to_string()));
+}
+
+#[test]
+fn sanitized_heartbeat_parses_plugin_from_user_agent() {
+    let mut request = sample_request();
+    request.user_agent = Some(
+        "wakatime/v1.115.2 (linux-6.14.1) go1.24.2 vscode/1.100.0 vscode-wakatime/25.0.3"
+            .to_string(),
+    );
+    let sanitized = SanitizedHeartbeatRequest::from_request(request);
+    let new_heartbeat =
+        sanitized.into_new_heartbeat(1, "1.1.1.1".parse().unwrap(), &HeaderMap::new());
+    assert_eq!(new_heartbeat.plugin, Some("vscode-wakatime".to_string()));
+    assert_eq!(new_heartbeat.plugin_version, Some("25.0.3".to_string()));
+    assert_eq!(new_heartbeat.editor, Some("vscode".to_string()));
+}
+
+#[test]
+fn sanitized_heartbeat_prefers_plugin_field() {
+    let mut request = sample_request();
+    request.plugin = Some("vim/9.1 vim-wakatime/11.2.0".to_string());
+    let sanitized = SanitizedHeartbeatRequest::from_request(request);
+    let new_heartbeat =
+        sanitized.into_new_heartbeat(1, "1.1.1.1".parse().unwrap(), &HeaderMap::new());
+    assert_eq!(new_heartbeat.plugin, Some("vim-wakatime".to_string()));
+    assert_eq!(new_heartbeat.plugin_version, Some("11.2.0".to_string()));
+}
+
 // ============================================================================
 // String truncation tests
 // ============================================================================
@@ -229,9 +281,12 @@ fn sample_request() -> HeartbeatRequest {
         lines: Some(100),
         line_additions: Some(10),
         line_deletions: Some(5),
+        ai_line_changes: Some(8),
+        human_line_changes: Some(7),
         lineno: Some(42),
         cursorpos: Some(128),
         is_write: Some(true),
+        machine: None,
         plugin: None,
         user_agent: None,
     }
@@ -306,8 +361,13 @@ fn new_heartbeat_constructor_applies_truncation() {
 fn new_heartbeat_from_request_round_trips_request_payload() {
     let request = sample_request();
     let headers = HeaderMap::new();
-    let new_heartbeat =
-        NewHeartbeat::from_request(request, 1, "1.1.1.1".parse().unwrap(), &headers);
+    let new_heartbeat = NewHeartbeat::from_request(
+        request,
+        1,
+        "1.1.1.1".parse().unwrap(),
+        &headers,
+        &IngestSettings::default(),
+    );
     assert_eq!(new_heartbeat.entity, "example.txt".to_string());
     assert_eq!(new_heartbeat.type_, "file".to_string());
     assert_eq!(new_heartbeat.project.unwrap(), "ExampleProject".to_string());
@@ -315,6 +375,19 @@ fn new_heartbeat_from_request_round_trips_request_payload() {
     assert_eq!(new_heartbeat.lines.unwrap(), 100);
 }
 
+#[test]
+fn new_heartbeat_from_request_keeps_ai_line_changes() {
+    let new_heartbeat = NewHeartbeat::from_request(
+        sample_request(),
+        1,
+        "1.1.1.1".parse().unwrap(),
+        &HeaderMap::new(),
+        &IngestSettings::default(),
+    );
+    assert_eq!(new_heartbeat.ai_line_changes, Some(8));
+    assert_eq!(new_heartbeat.human_line_changes, Some(7));
+}
+
 // ============================================================================
 // HeartbeatResponse tests
 // ============================================================================
@@ -347,6 +420,10 @@ fn heartbeat_response_conversion_retains_entity_details() {
         user_agent: "".to_string(),
         lineno: None,
         source_type: None,
+        plugin: None,
+        plugin_version: None,
+        ai_line_changes: None,
+        human_line_changes: None,
     };
     let response = HeartbeatResponse::from(heartbeat.clone());
     assert_eq!(response.id, heartbeat.id.to_string());
@@ -374,6 +451,8 @@ fn hackatime_heartbeat_handles_missing_fields() {
         user_agent: None,
         line_additions: None,
         line_deletions: None,
+        ai_line_changes: None,
+        human_line_changes: None,
         lineno: None,
         lines: None,
         cursorpos: None,
@@ -391,7 +470,7 @@ fn hackatime_heartbeat_handles_missing_fields() {
         raw_heartbeat_upload_id: None,
     };
 
-    let new_heartbeat = heartbeat.to_new_heartbeat(99);
+    let new_heartbeat = heartbeat.to_new_heartbeat(99, &IngestSettings::default());
 
     assert_eq!(new_heartbeat.user_agent, "");
     assert_eq!(new_heartbeat.ip_address, "127.0.0.1/32".parse().unwrap());
@@ -420,6 +499,46 @@ fn hackatime_time_accepts_stringified_numbers() {
     assert!((parsed.time - 1_700_000_000.5).abs() < f64::EPSILON);
 }
 
+#[test]
+fn hackatime_heartbeat_maps_ai_line_changes() {
+    let mut payload = minimal_hackatime_payload();
+    payload["ai_line_changes"] = json!(12);
+    payload["human_line_changes"] = json!(3);
+
+    let parsed: HackatimeHeartbeat = serde_json::from_value(payload).unwrap();
+    let new_heartbeat = parsed.to_new_heartbeat(1, &IngestSettings::default());
+    assert_eq!(new_heartbeat.ai_line_changes, Some(12));
+    assert_eq!(new_heartbeat.human_line_changes, Some(3));
+}
+
+#[test]
+fn hackatime_heartbeat_applies_project_entity_privacy() {
+    let mut payload = minimal_hackatime_payload();
+    payload["entity"] = json!("/home/me/acme/src/main.rs");
+    payload["project"] = json!("acme");
+
+    let mut privacy = privacy_settings(EntityPrivacy::Full);
+    privacy
+        .projects
+        .insert("acme".to_string(), EntityPrivacy::Hashed);
+    let settings = IngestSettings {
+        entity_privacy: privacy,
+        ..IngestSettings::default()
+    };
+
+    let parsed: HackatimeHeartbeat = serde_json::from_value(payload).unwrap();
+    let new_heartbeat = parsed.to_new_heartbeat(1, &settings);
+
+    assert_eq!(
+        new_heartbeat.entity,
+        EntityPrivacy::Hashed.apply(
+            "/home/me/acme/src/main.rs".to_string(),
+            &settings.entity_privacy.salt
+        )
+    );
+    assert_eq!(new_heartbeat.project, Some("acme".to_string()));
+}
+
 #[test]
 fn hackatime_time_accepts_rfc3339_strings() {
     let mut payload = minimal_hackatime_payload();
@@ -428,3 +547,305 @@ fn hackatime_time_accepts_rfc3339_strings() {
     let parsed: HackatimeHeartbeat = serde_json::from_value(payload).expect("time as RFC3339");
     assert_eq!(datetime_to_f64(f64_to_datetime(parsed.time)), parsed.time);
 }
+
+// ============================================================================
+// fill_daily_totals tests
+// ============================================================================
+
+#[test]
+fn fill_daily_totals_adds_empty_days() {
+    let start = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    let end = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+    let rows = vec![
+        (NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(), 120),
+        (NaiveDate::from_ymd_opt(2026, 3, 4).unwrap(), 60),
+    ];
+
+    let daily = fill_daily_totals(rows, start, end);
+
+    let totals: Vec<i64> = daily.iter().map(|d| d.total_seconds).collect();
+    assert_eq!(totals, vec![0, 120, 0, 60]);
+    assert_eq!(daily.first().unwrap().date, start);
+    assert_eq!(daily.last().unwrap().date, end);
+}
+
+#[test]
+fn fill_daily_totals_is_empty_when_start_after_end() {
+    let start = NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+    let end = NaiveDate::from_ymd_opt(2026, 3, 4).unwrap();
+    assert!(fill_daily_totals(Vec::new(), start, end).is_empty());
+}
+
+// ============================================================================
+// write_ratio tests
+// ============================================================================
+
+#[test]
+fn write_ratio_is_share_of_total_time() {
+    assert_eq!(write_ratio(450, 900), 0.5);
+    assert_eq!(write_ratio(1, 3), 0.3333);
+}
+
+#[test]
+fn write_ratio_is_zero_without_time() {
+    assert_eq!(write_ratio(0, 0), 0.0);
+}
+
+// ============================================================================
+// Entity privacy tests
+// ============================================================================
+
+fn privacy_settings(default: EntityPrivacy) -> EntityPrivacySettings {
+    EntityPrivacySettings {
+        default,
+        salt: Uuid::from_u128(42),
+        projects: HashMap::new(),
+    }
+}
+
+#[test]
+fn entity_privacy_full_keeps_path() {
+    let entity = "/home/me/clients/acme/src/main.rs".to_string();
+    assert_eq!(
+        EntityPrivacy::Full.apply(entity.clone(), &Uuid::nil()),
+        entity
+    );
+}
+
+#[test]
+fn entity_privacy_basename_strips_directories() {
+    let salt = Uuid::nil();
+    assert_eq!(
+        EntityPrivacy::Basename.apply("/home/me/acme/src/main.rs".to_string(), &salt),
+        "main.rs"
+    );
+    assert_eq!(
+        EntityPrivacy::Basename.apply("C:\\Users\\me\\acme\\lib.rs".to_string(), &salt),
+        "lib.rs"
+    );
+    assert_eq!(
+        EntityPrivacy::Basename.apply("/home/me/acme/".to_string(), &salt),
+        "acme"
+    );
+}
+
+#[test]
+fn entity_privacy_hash_is_salted_and_stable() {
+    let entity = "/home/me/acme/src/main.rs".to_string();
+    let first = EntityPrivacy::Hashed.apply(entity.clone(), &Uuid::from_u128(1));
+    let second = EntityPrivacy::Hashed.apply(entity.clone(), &Uuid::from_u128(1));
+    let other_salt = EntityPrivacy::Hashed.apply(entity, &Uuid::from_u128(2));
+
+    assert_eq!(first, second);
+    assert_ne!(first, other_salt);
+    assert_eq!(first.len(), 64);
+    assert!(!first.contains("acme"));
+}
+
+#[test]
+fn entity_privacy_hidden_drops_entity() {
+    assert!(
+        EntityPrivacy::Hidden
+            .apply("/home/me/acme/src/main.rs".to_string(), &Uuid::nil())
+            .is_empty()
+    );
+}
+
+#[test]
+fn entity_privacy_round_trips_through_i16() {
+    for mode in [
+        EntityPrivacy::Full,
+        EntityPrivacy::Basename,
+        EntityPrivacy::Hashed,
+        EntityPrivacy::Hidden,
+    ] {
+        assert_eq!(EntityPrivacy::from_i16(mode as i16), mode);
+    }
+    assert_eq!(EntityPrivacy::from_i16(99), EntityPrivacy::Full);
+}
+
+#[test]
+fn entity_privacy_project_override_wins() {
+    let mut privacy = privacy_settings(EntityPrivacy::Basename);
+    privacy
+        .projects
+        .insert("ExampleProject".to_string(), EntityPrivacy::Hidden);
+
+    assert_eq!(
+        privacy.mode_for(Some("ExampleProject")),
+        EntityPrivacy::Hidden
+    );
+    assert_eq!(privacy.mode_for(Some("Other")), EntityPrivacy::Basename);
+    assert_eq!(privacy.mode_for(None), EntityPrivacy::Basename);
+}
+
+#[test]
+fn sanitized_heartbeat_applies_entity_privacy_and_keeps_stats_fields() {
+    let mut request = sample_request();
+    request.entity = "/home/me/acme/src/main.rs".to_string();
+
+    let new_heartbeat = SanitizedHeartbeatRequest::from_request(request)
+        .apply_ingest_settings(&IngestSettings {
+            entity_privacy: privacy_settings(EntityPrivacy::Basename),
+            ..IngestSettings::default()
+        })
+        .into_new_heartbeat(1, "1.1.1.1".parse().unwrap(), &HeaderMap::new());
+
+    assert_eq!(new_heartbeat.entity, "main.rs");
+    assert_eq!(new_heartbeat.project, Some("ExampleProject".to_string()));
+    assert_eq!(new_heartbeat.language, Some("Rust".to_string()));
+    assert_eq!(new_heartbeat.is_write, Some(true));
+}
+
+// ============================================================================
+// Language normalization tests
+// ============================================================================
+
+#[test]
+fn sanitized_heartbeat_normalizes_language() {
+    let mut request = sample_request();
+    request.language = Some("typescriptreact".to_string());
+
+    let sanitized = SanitizedHeartbeatRequest::from_request(request);
+    assert_eq!(sanitized.language, Some("TypeScript".to_string()));
+}
+
+#[test]
+fn hackatime_heartbeat_normalizes_language() {
+    let mut payload = minimal_hackatime_payload();
+    payload["language"] = json!("rust");
+
+    let parsed: HackatimeHeartbeat = serde_json::from_value(payload).unwrap();
+    assert_eq!(
+        parsed
+            .to_new_heartbeat(1, &IngestSettings::default())
+            .language,
+        Some("Rust".to_string())
+    );
+}
+
+#[test]
+fn ingest_settings_apply_language_overrides_before_privacy() {
+    use crate::models::language_mapping::{LanguageMapping, LanguageMappingKind};
+
+    let mut request = sample_request();
+    request.entity = "/home/me/acme/templates/page.tpl".to_string();
+    request.language = Some("plaintext".to_string());
+
+    let settings = IngestSettings {
+        languages: LanguageOverrides::from_mappings(vec![LanguageMapping {
+            id: 1,
+            user_id: 1,
+            kind: LanguageMappingKind::Extension.as_str().to_string(),
+            source: "tpl".to_string(),
+            target: "Go Template".to_string(),
+            created_at: Utc::now(),
+        }]),
+        entity_privacy: privacy_settings(EntityPrivacy::Hidden),
+        ..IngestSettings::default()
+    };
+
+    let new_heartbeat = NewHeartbeat::from_request(
+        request,
+        1,
+        "1.1.1.1".parse().unwrap(),
+        &HeaderMap::new(),
+        &settings,
+    );
+
+    assert_eq!(new_heartbeat.language, Some("Go Template".to_string()));
+    assert!(new_heartbeat.entity.is_empty());
+}
+
+// ============================================================================
+// SourceType tests
+// ============================================================================
+
+#[test]
+fn source_type_names_match_stored_values() {
+    assert_eq!(
+        SourceType::name(Some(SourceType::DirectEntry as i16)),
+        "direct_entry"
+    );
+    assert_eq!(
+        SourceType::name(Some(SourceType::WakaTimeImport as i16)),
+        "wakatime_import"
+    );
+    assert_eq!(SourceType::name(Some(42)), "unknown");
+    assert_eq!(SourceType::name(None), "unknown");
+}
+
+// ============================================================================
+// Dependency grouping tests
+// ============================================================================
+
+fn dependency_row(
+    scope: &str,
+    scope_name: Option<&str>,
+    name: &str,
+    seconds: i64,
+) -> DependencyMetricRow {
+    DependencyMetricRow {
+        scope: scope.to_string(),
+        scope_name: scope_name.map(str::to_string),
+        name: Some(name.to_string()),
+        total_seconds: seconds,
+        total_time: 400,
+    }
+}
+
+#[test]
+fn dependency_rows_weight_by_coded_time() {
+    let stats = Heartbeat::group_dependency_rows(vec![
+        dependency_row("user", None, "serde", 300),
+        dependency_row("user", None, "tokio", 100),
+    ]);
+
+    assert_eq!(stats.total_time, 400);
+    let top: Vec<(&str, i64, f32)> = stats
+        .top_dependencies
+        .iter()
+        .map(|d| (d.name.as_str(), d.total_seconds, d.percent))
+        .collect();
+    assert_eq!(top, vec![("serde", 300, 75.0), ("tokio", 100, 25.0)]);
+    assert!(stats.by_project.is_empty());
+    assert!(stats.by_language.is_empty());
+}
+
+#[test]
+fn dependency_rows_group_by_project_and_language() {
+    let stats = Heartbeat::group_dependency_rows(vec![
+        dependency_row("user", None, "serde", 300),
+        dependency_row("project", Some("api"), "serde", 200),
+        dependency_row("project", Some("api"), "tokio", 100),
+        dependency_row("project", Some("web"), "serde", 100),
+        dependency_row("language", Some("Rust"), "serde", 300),
+        dependency_row("language", None, "tokio", 50),
+    ]);
+
+    let projects: Vec<(&str, Vec<&str>)> = stats
+        .by_project
+        .iter()
+        .map(|g| {
+            (
+                g.name.as_str(),
+                g.dependencies.iter().map(|d| d.name.as_str()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        projects,
+        vec![("api", vec!["serde", "tokio"]), ("web", vec!["serde"])]
+    );
+
+    let languages: Vec<&str> = stats.by_language.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(languages, vec!["Rust", "Unknown"]);
+    assert_eq!(stats.by_project[0].dependencies[0].percent, 50.0);
+}
+
+#[test]
+fn dependency_rows_empty_when_no_time() {
+    let stats = Heartbeat::group_dependency_rows(Vec::new());
+    assert_eq!(stats.total_time, 0);
+    assert!(stats.top_dependencies.is_empty());
+}
@@ -104,6 +104,21 @@ impl ImportJob {
         .optional()
     }
 
+    /// The user's most recent import jobs, newest first
+    pub fn list_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<ImportJob>> {
+        instrumented::load("ImportJob::list_for_user", || {
+            import_jobs::table
+                .filter(import_jobs::user_id.eq(user_id))
+                .order(import_jobs::created_at.desc())
+                .limit(limit)
+                .load::<ImportJob>(conn)
+        })
+    }
+
     pub fn get_active_for_user(
         conn: &mut PgConnection,
         user_id: i32,
@@ -0,0 +1,252 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::schema::{heartbeats, language_mappings, pending_language_normalizations};
+use crate::utils::instrumented;
+use crate::utils::language::{file_extension, normalize_language};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LanguageMappingKind {
+    /// Maps a file extension to a language
+    Extension,
+    /// Maps one language name to another
+    Language,
+}
+
+impl LanguageMappingKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LanguageMappingKind::Extension => "extension",
+            LanguageMappingKind::Language => "language",
+        }
+    }
+}
+
+impl From<&str> for LanguageMappingKind {
+    fn from(s: &str) -> Self {
+        match s {
+            "extension" => LanguageMappingKind::Extension,
+            _ => LanguageMappingKind::Language,
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone, JsonSchema)]
+#[diesel(table_name = language_mappings)]
+pub struct LanguageMapping {
+    pub id: i32,
+    pub user_id: i32,
+    pub kind: String,
+    pub source: String,
+    pub target: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = language_mappings)]
+pub struct NewLanguageMapping {
+    pub user_id: i32,
+    pub kind: String,
+    pub source: String,
+    pub target: String,
+}
+
+impl LanguageMapping {
+    pub fn list_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+    ) -> QueryResult<Vec<LanguageMapping>> {
+        instrumented::load("LanguageMapping::list_for_user", || {
+            language_mappings::table
+                .filter(language_mappings::user_id.eq(user_id))
+                .order((
+                    language_mappings::kind.asc(),
+                    language_mappings::source.asc(),
+                ))
+                .load::<LanguageMapping>(conn)
+        })
+    }
+
+    pub fn count_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<i64> {
+        instrumented::first("LanguageMapping::count_for_user", || {
+            language_mappings::table
+                .filter(language_mappings::user_id.eq(user_id))
+                .count()
+                .get_result(conn)
+        })
+    }
+
+    /// Create a mapping, replacing the target of an existing one with the same source
+    pub fn upsert(
+        conn: &mut PgConnection,
+        new_mapping: &NewLanguageMapping,
+    ) -> QueryResult<LanguageMapping> {
+        instrumented::first("LanguageMapping::upsert", || {
+            diesel::insert_into(language_mappings::table)
+                .values(new_mapping)
+                .on_conflict((
+                    language_mappings::user_id,
+                    language_mappings::kind,
+                    language_mappings::source,
+                ))
+                .do_update()
+                .set(language_mappings::target.eq(&new_mapping.target))
+                .get_result(conn)
+        })
+    }
+
+    pub fn delete(conn: &mut PgConnection, user_id: i32, mapping_id: i32) -> QueryResult<usize> {
+        instrumented::execute("LanguageMapping::delete", || {
+            diesel::delete(
+                language_mappings::table
+                    .filter(language_mappings::user_id.eq(user_id))
+                    .filter(language_mappings::id.eq(mapping_id)),
+            )
+            .execute(conn)
+        })
+    }
+}
+
+/// A user's language overrides keyed by lowercased extension and language
+#[derive(Clone, Debug, Default)]
+pub struct LanguageOverrides {
+    extensions: HashMap<String, String>,
+    languages: HashMap<String, String>,
+}
+
+impl LanguageOverrides {
+    pub fn from_mappings(mappings: Vec<LanguageMapping>) -> Self {
+        let mut overrides = Self::default();
+        for mapping in mappings {
+            let source = mapping.source.to_lowercase();
+            match LanguageMappingKind::from(mapping.kind.as_str()) {
+                LanguageMappingKind::Extension => {
+                    overrides.extensions.insert(source, mapping.target);
+                }
+                LanguageMappingKind::Language => {
+                    overrides.languages.insert(source, mapping.target);
+                }
+            }
+        }
+        overrides
+    }
+
+    pub fn load(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        Ok(Self::from_mappings(LanguageMapping::list_for_user(
+            conn, user_id,
+        )?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty() && self.languages.is_empty()
+    }
+
+    /// Resolve the language of a heartbeat, extension overrides taking precedence
+    pub fn apply(&self, language: Option<String>, entity: &str) -> Option<String> {
+        if !self.extensions.is_empty()
+            && let Some(target) = file_extension(entity).and_then(|ext| self.extensions.get(&ext))
+        {
+            return Some(target.clone());
+        }
+
+        match language {
+            Some(name) => match self.languages.get(&name.to_lowercase()) {
+                Some(target) => Some(target.clone()),
+                None => Some(name),
+            },
+            None => None,
+        }
+    }
+
+    /// Normalize a user's stored languages and re-apply their overrides, returning how many were changed
+    pub fn apply_to_history(&self, conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let languages: Vec<Option<String>> =
+                instrumented::load("LanguageOverrides::load_history_languages", || {
+                    heartbeats::table
+                        .filter(heartbeats::user_id.eq(user_id))
+                        .filter(heartbeats::language.is_not_null())
+                        .select(heartbeats::language)
+                        .distinct()
+                        .load(conn)
+                })?;
+
+            let mut updated = 0usize;
+
+            // canonical names first, then language overrides on top of them
+            for language in languages.into_iter().flatten() {
+                let mut target = normalize_language(Some(language.clone()));
+                if let Some(name) = target.as_deref()
+                    && let Some(mapped) = self.languages.get(&name.to_lowercase())
+                {
+                    target = Some(mapped.clone());
+                }
+
+                if target.as_deref() == Some(language.as_str()) {
+                    continue;
+                }
+
+                updated += instrumented::execute("LanguageOverrides::rename_language", || {
+                    diesel::update(
+                        heartbeats::table
+                            .filter(heartbeats::user_id.eq(user_id))
+                            .filter(heartbeats::language.eq(&language)),
+                    )
+                    .set(heartbeats::language.eq(&target))
+                    .execute(conn)
+                })?;
+            }
+
+            // extensions only contain [a-z0-9+-] so they are safe inside a LIKE pattern
+            for (extension, target) in &self.extensions {
+                updated += instrumented::execute("LanguageOverrides::apply_extension", || {
+                    diesel::update(
+                        heartbeats::table
+                            .filter(heartbeats::user_id.eq(user_id))
+                            .filter(heartbeats::entity.ilike(format!("%_.{extension}")))
+                            .filter(heartbeats::language.is_distinct_from(target)),
+                    )
+                    .set(heartbeats::language.eq(target))
+                    .execute(conn)
+                })?;
+            }
+
+            Ok(updated)
+        })
+    }
+}
+
+/// Users flagged by a migration whose stored languages still have to be normalized
+pub fn pending_normalizations(conn: &mut PgConnection) -> QueryResult<Vec<i32>> {
+    instrumented::load("LanguageOverrides::pending_normalizations", || {
+        pending_language_normalizations::table
+            .select(pending_language_normalizations::user_id)
+            .order(pending_language_normalizations::user_id)
+            .load(conn)
+    })
+}
+
+pub fn clear_pending_normalization(conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
+    instrumented::execute("LanguageOverrides::clear_pending_normalization", || {
+        diesel::delete(
+            pending_language_normalizations::table
+                .filter(pending_language_normalizations::user_id.eq(user_id)),
+        )
+        .execute(conn)
+    })
+}
+
+/// Whether an extension is safe to store and match, e.g. `rs` or `c++`
+pub fn is_valid_extension(extension: &str) -> bool {
+    !extension.is_empty()
+        && extension
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-')
+}
+
+#[cfg(test)]
+mod tests;
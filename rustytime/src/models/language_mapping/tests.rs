@@ -0,0 +1,98 @@
+use super::*;
+
+fn mapping(kind: LanguageMappingKind, source: &str, target: &str) -> LanguageMapping {
+    LanguageMapping {
+        id: 0,
+        user_id: 1,
+        kind: kind.as_str().to_string(),
+        source: source.to_string(),
+        target: target.to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+// ==================== LanguageOverrides tests ====================
+
+#[test]
+fn overrides_map_extension_to_language() {
+    let overrides = LanguageOverrides::from_mappings(vec![mapping(
+        LanguageMappingKind::Extension,
+        "tpl",
+        "Go Template",
+    )]);
+
+    assert_eq!(
+        overrides.apply(Some("Text".to_string()), "/src/page.TPL"),
+        Some("Go Template".to_string())
+    );
+    assert_eq!(
+        overrides.apply(None, "/src/page.tpl"),
+        Some("Go Template".to_string())
+    );
+}
+
+#[test]
+fn overrides_map_language_to_language_case_insensitively() {
+    let overrides = LanguageOverrides::from_mappings(vec![mapping(
+        LanguageMappingKind::Language,
+        "Text",
+        "Markdown",
+    )]);
+
+    assert_eq!(
+        overrides.apply(Some("text".to_string()), "/notes/todo"),
+        Some("Markdown".to_string())
+    );
+}
+
+#[test]
+fn extension_overrides_take_precedence() {
+    let overrides = LanguageOverrides::from_mappings(vec![
+        mapping(LanguageMappingKind::Language, "typescript", "JavaScript"),
+        mapping(LanguageMappingKind::Extension, "tsx", "TSX"),
+    ]);
+
+    assert_eq!(
+        overrides.apply(Some("TypeScript".to_string()), "/app/App.tsx"),
+        Some("TSX".to_string())
+    );
+    assert_eq!(
+        overrides.apply(Some("TypeScript".to_string()), "/app/main.ts"),
+        Some("JavaScript".to_string())
+    );
+}
+
+#[test]
+fn overrides_leave_unmatched_languages_alone() {
+    let overrides = LanguageOverrides::default();
+
+    assert!(overrides.is_empty());
+    assert_eq!(
+        overrides.apply(Some("Rust".to_string()), "/src/main.rs"),
+        Some("Rust".to_string())
+    );
+    assert_eq!(overrides.apply(None, "/src/main.rs"), None);
+}
+
+// ==================== validation tests ====================
+
+#[test]
+fn valid_extensions() {
+    assert!(is_valid_extension("rs"));
+    assert!(is_valid_extension("c++"));
+    assert!(is_valid_extension("d-ts"));
+    assert!(!is_valid_extension(""));
+    assert!(!is_valid_extension("r%"));
+    assert!(!is_valid_extension("a_b"));
+    assert!(!is_valid_extension("tar.gz"));
+}
+
+#[test]
+fn mapping_kind_round_trip() {
+    for kind in [
+        LanguageMappingKind::Extension,
+        LanguageMappingKind::Language,
+    ] {
+        assert_eq!(LanguageMappingKind::from(kind.as_str()), kind);
+    }
+}
@@ -17,6 +17,8 @@ pub struct Leaderboard {
     pub rank: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Rank last sent in a rank change webhook
+    pub notified_rank: i32,
 }
 
 #[derive(Insertable, Debug)]
@@ -27,6 +29,8 @@ pub struct NewLeaderboard {
     pub period_date: NaiveDate,
     pub total_seconds: i64,
     pub rank: i32,
+    /// Set to `rank` when the user first appears on the board, kept on later upserts
+    pub notified_rank: i32,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
@@ -38,6 +42,29 @@ pub struct LeaderboardEntry {
     pub rank: i32,
 }
 
+/// A user whose rank moved since the last rank change notification for a leaderboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankChange {
+    pub user_id: i32,
+    pub previous_rank: i32,
+    pub rank: i32,
+    pub total_seconds: i64,
+}
+
+/// Entries whose rank differs from the rank last notified
+pub fn rank_changes(entries: &[Leaderboard]) -> Vec<RankChange> {
+    entries
+        .iter()
+        .filter(|entry| entry.notified_rank != entry.rank)
+        .map(|entry| RankChange {
+            user_id: entry.user_id,
+            previous_rank: entry.notified_rank,
+            rank: entry.rank,
+            total_seconds: entry.total_seconds,
+        })
+        .collect()
+}
+
 impl Leaderboard {
     pub fn get_by_period(
         conn: &mut PgConnection,
@@ -79,6 +106,24 @@ impl Leaderboard {
         })
     }
 
+    /// Record the current ranks of a leaderboard as notified
+    pub fn mark_notified(
+        conn: &mut PgConnection,
+        period_type: &str,
+        period_date: NaiveDate,
+    ) -> QueryResult<usize> {
+        instrumented::execute("Leaderboard::mark_notified", || {
+            diesel::update(
+                leaderboards::table
+                    .filter(leaderboards::period_type.eq(period_type))
+                    .filter(leaderboards::period_date.eq(period_date))
+                    .filter(leaderboards::notified_rank.ne(leaderboards::rank)),
+            )
+            .set(leaderboards::notified_rank.eq(leaderboards::rank))
+            .execute(conn)
+        })
+    }
+
     pub fn delete_old_daily(conn: &mut PgConnection, cutoff_date: NaiveDate) -> QueryResult<usize> {
         instrumented::execute("Leaderboard::delete_old_daily", || {
             diesel::delete(
@@ -1,7 +1,15 @@
+pub mod audit_log;
+pub mod daily_total;
+pub mod goal;
 pub mod heartbeat;
 pub mod import_job;
+pub mod language_mapping;
 pub mod leaderboard;
 pub mod project;
 pub mod project_alias;
+pub mod project_rule;
+pub mod role;
 pub mod session;
 pub mod user;
+pub mod user_identity;
+pub mod webhook;
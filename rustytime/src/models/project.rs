@@ -1,15 +1,18 @@
 use std::sync::Arc;
 
 use crate::models::heartbeat::TIMEOUT_SECONDS;
+use crate::models::project_rule::ProjectRule;
 use crate::utils::cache::HeartbeatProjectCacheKey;
 use diesel::QueryableByName;
 use diesel::insert_into;
 use diesel::prelude::*;
-use diesel::sql_types::{BigInt, Bool, Int4, Nullable as SqlNullable, Text, Timestamptz};
+use diesel::sql_types::{BigInt, Bool, Int4, Nullable as SqlNullable, SmallInt, Text, Timestamptz};
 use moka::sync::Cache;
 use once_cell::sync::Lazy;
 
-use crate::schema::projects;
+use crate::schema::{
+    goals, heartbeats, project_alias_resolutions, project_aliases, project_rules, projects,
+};
 use crate::utils::instrumented;
 
 static PROJECT_CACHE: Lazy<Arc<Cache<HeartbeatProjectCacheKey, i32>>> = Lazy::new(|| {
@@ -17,6 +20,7 @@ static PROJECT_CACHE: Lazy<Arc<Cache<HeartbeatProjectCacheKey, i32>>> = Lazy::ne
         Cache::builder()
             .max_capacity(1_000)
             .time_to_live(std::time::Duration::from_secs(600)) // 10 minute TTL
+            .support_invalidation_closures()
             .build(),
     )
 });
@@ -57,6 +61,7 @@ pub struct Project {
     pub updated_at: Option<chrono::DateTime<chrono::Utc>>,
     pub hidden: bool,
     pub project_url: Option<String>,
+    pub entity_privacy: Option<i16>,
 }
 
 #[derive(QueryableByName)]
@@ -79,6 +84,16 @@ struct ProjectWithTimeRow {
     hidden: bool,
     #[diesel(sql_type = SqlNullable<Text>)]
     project_url: Option<String>,
+    #[diesel(sql_type = SqlNullable<SmallInt>)]
+    entity_privacy: Option<i16>,
+}
+
+#[derive(QueryableByName)]
+struct EntityPrivacyOverrideRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = SmallInt)]
+    entity_privacy: i16,
 }
 
 #[derive(QueryableByName, Debug, Clone)]
@@ -136,7 +151,168 @@ pub fn get_or_create_project_id(
     Ok(inserted_id)
 }
 
+/// Forget the cached project ids of a user after their projects were renamed or merged
+pub fn invalidate_project_id_cache(user_id_param: i32) {
+    let _ = PROJECT_CACHE.invalidate_entries_if(move |key, _| key.user_id == user_id_param);
+}
+
 impl Project {
+    pub fn find_user_project(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        project_id_param: i32,
+    ) -> QueryResult<Option<Project>> {
+        instrumented::first("Project::find_user_project", || {
+            projects::table
+                .filter(projects::id.eq(project_id_param))
+                .filter(projects::user_id.eq(user_id_param))
+                .first::<Project>(conn)
+                .optional()
+        })
+    }
+
+    pub fn find_by_name(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        name_param: &str,
+    ) -> QueryResult<Option<Project>> {
+        instrumented::first("Project::find_by_name", || {
+            projects::table
+                .filter(projects::user_id.eq(user_id_param))
+                .filter(projects::name.eq(name_param))
+                .first::<Project>(conn)
+                .optional()
+        })
+    }
+
+    /// Rename a project and rewrite the name on all of its heartbeats, returning how many were updated
+    pub fn rename(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        project_id_param: i32,
+        new_name: &str,
+    ) -> QueryResult<usize> {
+        conn.transaction(|conn| {
+            let project = Self::find_user_project(conn, user_id_param, project_id_param)?
+                .ok_or(diesel::result::Error::NotFound)?;
+            if project.name == new_name {
+                return Ok(0);
+            }
+
+            instrumented::execute("Project::rename", || {
+                diesel::update(projects::table.filter(projects::id.eq(project.id)))
+                    .set(projects::name.eq(new_name))
+                    .execute(conn)
+            })?;
+
+            let updated =
+                Self::move_heartbeats(conn, user_id_param, &project, project.id, new_name)?;
+            Self::retarget_by_name(conn, user_id_param, &project.name, new_name)?;
+            ProjectRule::redirect(conn, user_id_param, &project.name, new_name)?;
+
+            Ok(updated)
+        })
+    }
+
+    /// Move all heartbeats of a project into another one, then delete the emptied project
+    pub fn merge_into(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        source_id: i32,
+        target_id: i32,
+    ) -> QueryResult<usize> {
+        conn.transaction(|conn| {
+            let source = Self::find_user_project(conn, user_id_param, source_id)?
+                .ok_or(diesel::result::Error::NotFound)?;
+            let target = Self::find_user_project(conn, user_id_param, target_id)?
+                .ok_or(diesel::result::Error::NotFound)?;
+
+            let updated =
+                Self::move_heartbeats(conn, user_id_param, &source, target.id, &target.name)?;
+
+            // aliases pointing at the source now point at the target, the rest cascade away
+            instrumented::execute("Project::merge_aliases", || {
+                diesel::update(
+                    project_aliases::table
+                        .filter(project_aliases::user_id.eq(user_id_param))
+                        .filter(project_aliases::alias_to.eq(source.id))
+                        .filter(project_aliases::project_id.ne(target.id)),
+                )
+                .set(project_aliases::alias_to.eq(target.id))
+                .execute(conn)
+            })?;
+
+            Self::retarget_by_name(conn, user_id_param, &source.name, &target.name)?;
+            ProjectRule::redirect(conn, user_id_param, &source.name, &target.name)?;
+
+            instrumented::execute("Project::delete", || {
+                diesel::delete(
+                    projects::table
+                        .filter(projects::id.eq(source.id))
+                        .filter(projects::user_id.eq(user_id_param)),
+                )
+                .execute(conn)
+            })?;
+
+            Ok(updated)
+        })
+    }
+
+    fn move_heartbeats(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        from: &Project,
+        to_id: i32,
+        to_name: &str,
+    ) -> QueryResult<usize> {
+        instrumented::execute("Project::move_heartbeats", || {
+            diesel::update(
+                heartbeats::table
+                    .filter(heartbeats::user_id.eq(user_id_param))
+                    .filter(
+                        heartbeats::project_id.eq(from.id).or(heartbeats::project_id
+                            .is_null()
+                            .and(heartbeats::project.eq(&from.name))),
+                    ),
+            )
+            .set((
+                heartbeats::project_id.eq(to_id),
+                heartbeats::project.eq(to_name),
+            ))
+            .execute(conn)
+        })
+    }
+
+    /// Point project rules and goals that refer to a project by name at its new name
+    fn retarget_by_name(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        from_name: &str,
+        to_name: &str,
+    ) -> QueryResult<()> {
+        instrumented::execute("Project::retarget_rules", || {
+            diesel::update(
+                project_rules::table
+                    .filter(project_rules::user_id.eq(user_id_param))
+                    .filter(project_rules::target_project.eq(from_name)),
+            )
+            .set(project_rules::target_project.eq(to_name))
+            .execute(conn)
+        })?;
+
+        instrumented::execute("Project::retarget_goals", || {
+            diesel::update(
+                goals::table
+                    .filter(goals::user_id.eq(user_id_param))
+                    .filter(goals::project.eq(from_name)),
+            )
+            .set(goals::project.eq(to_name))
+            .execute(conn)
+        })?;
+
+        Ok(())
+    }
+
     pub fn list_user_projects(
         conn: &mut PgConnection,
         user_id_param: i32,
@@ -151,6 +327,25 @@ impl Project {
         })
     }
 
+    /// Find a user's project, following aliases to the project it resolves to
+    pub fn find_resolved_user_project(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+        project_id_param: i32,
+    ) -> QueryResult<Option<Project>> {
+        let resolved_id = instrumented::first("Project::resolve_alias", || {
+            project_alias_resolutions::table
+                .filter(project_alias_resolutions::user_id.eq(user_id_param))
+                .filter(project_alias_resolutions::project_id.eq(project_id_param))
+                .select(project_alias_resolutions::resolved_project_id)
+                .first::<i32>(conn)
+                .optional()
+        })?
+        .unwrap_or(project_id_param);
+
+        Self::find_user_project(conn, user_id_param, resolved_id)
+    }
+
     pub fn set_project_url(
         conn: &mut PgConnection,
         project_id_param: i32,
@@ -180,7 +375,7 @@ impl Project {
             "Project::list_projects_with_time",
             || {
                 diesel::sql_query(
-                "SELECT id, user_id, name, repo_url, created_at, updated_at, total_seconds, hidden, project_url \
+                "SELECT id, user_id, name, repo_url, created_at, updated_at, total_seconds, hidden, project_url, entity_privacy \
                  FROM list_projects_with_time($1, $2)",
             )
             .bind::<Int4, _>(user_id_param)
@@ -202,6 +397,7 @@ impl Project {
                         updated_at: row.updated_at,
                         hidden: row.hidden,
                         project_url: row.project_url,
+                        entity_privacy: row.entity_privacy,
                     },
                     row.total_seconds,
                 )
@@ -230,6 +426,55 @@ impl Project {
         })
     }
 
+    pub fn set_entity_privacy(
+        conn: &mut PgConnection,
+        project_id_param: i32,
+        user_id_param: i32,
+        new_entity_privacy: Option<i16>,
+    ) -> QueryResult<()> {
+        use crate::schema::projects::dsl::*;
+
+        instrumented::execute("Project::set_entity_privacy", || {
+            diesel::update(
+                projects
+                    .filter(id.eq(project_id_param))
+                    .filter(user_id.eq(user_id_param)),
+            )
+            .set(entity_privacy.eq(new_entity_privacy))
+            .execute(conn)
+        })?;
+
+        Ok(())
+    }
+
+    /// Project names with an entity privacy override, aliases inherit from their target
+    pub fn entity_privacy_overrides(
+        conn: &mut PgConnection,
+        user_id_param: i32,
+    ) -> QueryResult<Vec<(String, i16)>> {
+        let rows: Vec<EntityPrivacyOverrideRow> = instrumented::load(
+            "Project::entity_privacy_overrides",
+            || {
+                diesel::sql_query(
+                    "SELECT p.name, COALESCE(p.entity_privacy, rp.entity_privacy) AS entity_privacy \
+                     FROM projects p \
+                     LEFT JOIN project_alias_resolutions par \
+                         ON par.user_id = p.user_id AND par.project_id = p.id \
+                     LEFT JOIN projects rp ON rp.id = par.resolved_project_id \
+                     WHERE p.user_id = $1 \
+                       AND COALESCE(p.entity_privacy, rp.entity_privacy) IS NOT NULL",
+                )
+                .bind::<Int4, _>(user_id_param)
+                .load(conn)
+            },
+        )?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.name, row.entity_privacy))
+            .collect())
+    }
+
     pub fn set_hidden(
         conn: &mut PgConnection,
         project_id_param: i32,
@@ -0,0 +1,258 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use regex::{Regex, RegexBuilder};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::project::get_or_create_project_id;
+use crate::schema::{heartbeats, project_rules};
+use crate::utils::instrumented;
+
+const MAX_PATTERN_SIZE: usize = 1 << 16;
+const HISTORY_UPDATE_BATCH_SIZE: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectRuleField {
+    Project,
+    Entity,
+}
+
+impl ProjectRuleField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectRuleField::Project => "project",
+            ProjectRuleField::Entity => "entity",
+        }
+    }
+}
+
+impl From<&str> for ProjectRuleField {
+    fn from(s: &str) -> Self {
+        match s {
+            "entity" => ProjectRuleField::Entity,
+            _ => ProjectRuleField::Project,
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone, JsonSchema)]
+#[diesel(table_name = project_rules)]
+pub struct ProjectRule {
+    pub id: i32,
+    pub user_id: i32,
+    pub match_field: String,
+    pub pattern: String,
+    pub target_project: String,
+    pub priority: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = project_rules)]
+pub struct NewProjectRule {
+    pub user_id: i32,
+    pub match_field: String,
+    pub pattern: String,
+    pub target_project: String,
+    pub priority: i32,
+}
+
+/// Compile a user supplied pattern with a bounded program size
+pub fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(MAX_PATTERN_SIZE)
+        .build()
+}
+
+/// Pattern matching exactly one project name
+pub fn exact_name_pattern(name: &str) -> String {
+    format!("^{}$", regex::escape(name))
+}
+
+impl ProjectRule {
+    pub fn list_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<ProjectRule>> {
+        instrumented::load("ProjectRule::list_for_user", || {
+            project_rules::table
+                .filter(project_rules::user_id.eq(user_id))
+                .order((project_rules::priority.asc(), project_rules::id.asc()))
+                .load::<ProjectRule>(conn)
+        })
+    }
+
+    pub fn count_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<i64> {
+        instrumented::first("ProjectRule::count_for_user", || {
+            project_rules::table
+                .filter(project_rules::user_id.eq(user_id))
+                .count()
+                .get_result(conn)
+        })
+    }
+
+    pub fn create(conn: &mut PgConnection, new_rule: &NewProjectRule) -> QueryResult<ProjectRule> {
+        instrumented::first("ProjectRule::create", || {
+            diesel::insert_into(project_rules::table)
+                .values(new_rule)
+                .get_result(conn)
+        })
+    }
+
+    /// Send heartbeats that still use a renamed or merged project's old name to the new one
+    pub fn redirect(
+        conn: &mut PgConnection,
+        user_id: i32,
+        from_name: &str,
+        to_name: &str,
+    ) -> QueryResult<ProjectRule> {
+        let pattern = exact_name_pattern(from_name);
+
+        // the new name is a real project again and the old one gets a single redirect
+        instrumented::execute("ProjectRule::clear_redirects", || {
+            diesel::delete(
+                project_rules::table
+                    .filter(project_rules::user_id.eq(user_id))
+                    .filter(project_rules::match_field.eq(ProjectRuleField::Project.as_str()))
+                    .filter(
+                        project_rules::pattern
+                            .eq(&pattern)
+                            .or(project_rules::pattern.eq(exact_name_pattern(to_name))),
+                    ),
+            )
+            .execute(conn)
+        })?;
+
+        Self::create(
+            conn,
+            &NewProjectRule {
+                user_id,
+                match_field: ProjectRuleField::Project.as_str().to_string(),
+                pattern,
+                target_project: to_name.to_string(),
+                priority: 0,
+            },
+        )
+    }
+
+    pub fn delete(conn: &mut PgConnection, user_id: i32, rule_id: i32) -> QueryResult<usize> {
+        instrumented::execute("ProjectRule::delete", || {
+            diesel::delete(
+                project_rules::table
+                    .filter(project_rules::user_id.eq(user_id))
+                    .filter(project_rules::id.eq(rule_id)),
+            )
+            .execute(conn)
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CompiledProjectRule {
+    field: ProjectRuleField,
+    regex: Regex,
+    target_project: String,
+}
+
+/// A user's project rules compiled and ready to evaluate, first match wins
+#[derive(Clone, Debug, Default)]
+pub struct ProjectRuleSet {
+    rules: Vec<CompiledProjectRule>,
+}
+
+impl ProjectRuleSet {
+    pub fn compile(rules: Vec<ProjectRule>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|rule| {
+                // patterns are validated on creation, skip anything that no longer compiles
+                let regex = compile_pattern(&rule.pattern).ok()?;
+                Some(CompiledProjectRule {
+                    field: ProjectRuleField::from(rule.match_field.as_str()),
+                    regex,
+                    target_project: rule.target_project,
+                })
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    pub fn load(conn: &mut PgConnection, user_id: i32) -> QueryResult<Self> {
+        Ok(Self::compile(ProjectRule::list_for_user(conn, user_id)?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Target project of the first rule matching this heartbeat, if any
+    pub fn map(&self, project: Option<&str>, entity: &str) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| match rule.field {
+                ProjectRuleField::Project => project.is_some_and(|name| rule.regex.is_match(name)),
+                ProjectRuleField::Entity => rule.regex.is_match(entity),
+            })
+            .map(|rule| rule.target_project.as_str())
+    }
+
+    /// Re-apply the rules to all of a user's heartbeats, returning how many were moved
+    pub fn apply_to_history(&self, conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
+        if self.is_empty() {
+            return Ok(0);
+        }
+
+        let pairs: Vec<(Option<String>, String)> =
+            instrumented::load("ProjectRuleSet::load_history_pairs", || {
+                heartbeats::table
+                    .filter(heartbeats::user_id.eq(user_id))
+                    .select((heartbeats::project, heartbeats::entity))
+                    .distinct()
+                    .load(conn)
+            })?;
+
+        // group entities by the project move they need
+        let mut moves: HashMap<(Option<String>, String), Vec<String>> = HashMap::new();
+        for (project, entity) in pairs {
+            if let Some(target) = self.map(project.as_deref(), &entity)
+                && project.as_deref() != Some(target)
+            {
+                moves
+                    .entry((project, target.to_string()))
+                    .or_default()
+                    .push(entity);
+            }
+        }
+
+        let mut updated = 0usize;
+        for ((from_project, to_project), entities) in moves {
+            updated += conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                let to_project_id = get_or_create_project_id(conn, user_id, &to_project, None)?;
+                let mut moved = 0usize;
+
+                for chunk in entities.chunks(HISTORY_UPDATE_BATCH_SIZE) {
+                    moved += instrumented::execute("ProjectRuleSet::move_heartbeats", || {
+                        diesel::update(
+                            heartbeats::table
+                                .filter(heartbeats::user_id.eq(user_id))
+                                .filter(heartbeats::project.is_not_distinct_from(&from_project))
+                                .filter(heartbeats::entity.eq_any(chunk)),
+                        )
+                        .set((
+                            heartbeats::project.eq(&to_project),
+                            heartbeats::project_id.eq(to_project_id),
+                        ))
+                        .execute(conn)
+                    })?;
+                }
+
+                Ok(moved)
+            })?;
+        }
+
+        Ok(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,83 @@
+use super::*;
+
+fn rule(field: &str, pattern: &str, target: &str) -> ProjectRule {
+    ProjectRule {
+        id: 0,
+        user_id: 1,
+        match_field: field.to_string(),
+        pattern: pattern.to_string(),
+        target_project: target.to_string(),
+        priority: 0,
+        created_at: Utc::now(),
+    }
+}
+
+// ============================================================================
+// ProjectRuleSet tests
+// ============================================================================
+
+#[test]
+fn maps_project_names_by_pattern() {
+    let rules = ProjectRuleSet::compile(vec![rule("project", "(?i)^foo(-main)?$", "foo")]);
+
+    assert_eq!(rules.map(Some("foo-main"), "src/lib.rs"), Some("foo"));
+    assert_eq!(rules.map(Some("Foo"), "src/lib.rs"), Some("foo"));
+    assert_eq!(rules.map(Some("foobar"), "src/lib.rs"), None);
+    assert_eq!(rules.map(None, "src/lib.rs"), None);
+}
+
+#[test]
+fn maps_entity_paths_without_a_project() {
+    let rules = ProjectRuleSet::compile(vec![rule("entity", r"^/work/clients/acme/", "acme")]);
+
+    assert_eq!(rules.map(None, "/work/clients/acme/app.py"), Some("acme"));
+    assert_eq!(rules.map(Some("scratch"), "/home/me/app.py"), None);
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let rules = ProjectRuleSet::compile(vec![
+        rule("entity", r"\.md$", "notes"),
+        rule("project", ".*", "everything"),
+    ]);
+
+    assert_eq!(rules.map(Some("foo"), "README.md"), Some("notes"));
+    assert_eq!(rules.map(Some("foo"), "main.rs"), Some("everything"));
+}
+
+#[test]
+fn skips_patterns_that_do_not_compile() {
+    let rules = ProjectRuleSet::compile(vec![rule("project", "(unclosed", "broken")]);
+
+    assert!(rules.is_empty());
+    assert_eq!(rules.map(Some("(unclosed"), "main.rs"), None);
+}
+
+#[test]
+fn compile_pattern_rejects_invalid_regex() {
+    assert!(compile_pattern("[a-").is_err());
+    assert!(compile_pattern(r"^\w+-main$").is_ok());
+}
+
+#[test]
+fn project_rule_field_round_trips() {
+    for field in [ProjectRuleField::Project, ProjectRuleField::Entity] {
+        assert_eq!(ProjectRuleField::from(field.as_str()), field);
+    }
+}
+
+#[test]
+fn exact_name_pattern_only_matches_the_name() {
+    let rules = ProjectRuleSet::compile(vec![rule(
+        "project",
+        &exact_name_pattern("old.name (v2)"),
+        "new-name",
+    )]);
+
+    assert_eq!(
+        rules.map(Some("old.name (v2)"), "main.rs"),
+        Some("new-name")
+    );
+    assert_eq!(rules.map(Some("oldxname (v2)"), "main.rs"), None);
+    assert_eq!(rules.map(Some("old.name (v2)-fork"), "main.rs"), None);
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::roles;
+use crate::utils::instrumented;
+
+/// Individual admin capabilities, granted through roles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    ViewUsers,
+    Impersonate,
+    /// Make changes while impersonating instead of only looking
+    ImpersonateWrite,
+    Ban,
+    ManageImports,
+    ViewApiKeys,
+    ManageRoles,
+    ViewAuditLog,
+    GlobalWebhooks,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::ViewUsers,
+        Permission::Impersonate,
+        Permission::ImpersonateWrite,
+        Permission::Ban,
+        Permission::ManageImports,
+        Permission::ViewApiKeys,
+        Permission::ManageRoles,
+        Permission::ViewAuditLog,
+        Permission::GlobalWebhooks,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ViewUsers => "view_users",
+            Permission::Impersonate => "impersonate",
+            Permission::ImpersonateWrite => "impersonate_write",
+            Permission::Ban => "ban",
+            Permission::ManageImports => "manage_imports",
+            Permission::ViewApiKeys => "view_api_keys",
+            Permission::ManageRoles => "manage_roles",
+            Permission::ViewAuditLog => "view_audit_log",
+            Permission::GlobalWebhooks => "global_webhooks",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|permission| permission.as_str() == value)
+    }
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone, JsonSchema)]
+#[diesel(table_name = roles)]
+pub struct Role {
+    pub name: String,
+    /// Roles can only manage users and roles ranked below them
+    pub rank: i16,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Role {
+    pub const USER: &'static str = "user";
+    pub const OWNER: &'static str = "owner";
+
+    pub fn has(&self, permission: Permission) -> bool {
+        self.permissions
+            .iter()
+            .any(|granted| granted == permission.as_str())
+    }
+
+    /// Known permissions of the role, unknown names left behind by older versions are skipped
+    pub fn permission_set(&self) -> Vec<Permission> {
+        self.permissions
+            .iter()
+            .filter_map(|name| Permission::parse(name))
+            .collect()
+    }
+
+    /// Whether users of this role may act on users of `other`
+    pub fn outranks(&self, other: &Role) -> bool {
+        self.rank > other.rank
+    }
+
+    pub fn find(conn: &mut PgConnection, name: &str) -> QueryResult<Option<Role>> {
+        instrumented::first("Role::find", || {
+            roles::table
+                .find(name)
+                .select(Role::as_select())
+                .first::<Role>(conn)
+        })
+        .optional()
+    }
+
+    /// All roles, highest ranked first
+    pub fn list(conn: &mut PgConnection) -> QueryResult<Vec<Role>> {
+        instrumented::load("Role::list", || {
+            roles::table
+                .order((roles::rank.desc(), roles::name.asc()))
+                .select(Role::as_select())
+                .load::<Role>(conn)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
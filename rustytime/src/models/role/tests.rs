@@ -0,0 +1,52 @@
+use super::*;
+
+fn role(rank: i16, permissions: &[&str]) -> Role {
+    Role {
+        name: "test".to_string(),
+        rank,
+        permissions: permissions.iter().map(|p| p.to_string()).collect(),
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn permission_names_round_trip() {
+    for permission in Permission::ALL {
+        assert_eq!(Permission::parse(permission.as_str()), Some(permission));
+    }
+    assert_eq!(Permission::parse("launch_rockets"), None);
+}
+
+#[test]
+fn permission_serializes_to_its_name() {
+    for permission in Permission::ALL {
+        assert_eq!(
+            serde_json::to_value(permission).unwrap(),
+            serde_json::Value::from(permission.as_str())
+        );
+    }
+}
+
+#[test]
+fn role_grants_only_listed_permissions() {
+    let admin = role(1, &["view_users", "impersonate"]);
+    assert!(admin.has(Permission::ViewUsers));
+    assert!(admin.has(Permission::Impersonate));
+    assert!(!admin.has(Permission::ImpersonateWrite));
+    assert!(!admin.has(Permission::ViewApiKeys));
+}
+
+#[test]
+fn unknown_permissions_are_skipped() {
+    let role = role(1, &["ban", "retired_permission"]);
+    assert_eq!(role.permission_set(), vec![Permission::Ban]);
+}
+
+#[test]
+fn only_higher_ranks_outrank() {
+    let owner = role(2, &[]);
+    let admin = role(1, &[]);
+    assert!(owner.outranks(&admin));
+    assert!(!admin.outranks(&owner));
+    assert!(!admin.outranks(&admin));
+}
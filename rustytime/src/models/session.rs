@@ -7,6 +7,7 @@ use uuid::Uuid;
 use crate::models::user::User;
 use crate::schema::sessions;
 use crate::schema::sessions::dsl;
+use crate::schema::users;
 use crate::utils::instrumented;
 
 #[derive(Queryable, Selectable, Debug, Clone)]
@@ -15,34 +16,50 @@ use crate::utils::instrumented;
 pub struct Session {
     pub id: Uuid,
     pub user_id: i32,
-    pub github_user_id: i64,
-    pub github_access_token: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub expires_at: DateTime<Utc>,
     pub impersonated_by: Option<i32>,
+    /// Browser and OS the session was created from
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Whether the impersonating owner may make changes as the user
+    pub impersonation_write: bool,
 }
 
 #[derive(Insertable, Deserialize, Debug)]
 #[diesel(table_name = sessions)]
 pub struct NewSession {
     pub user_id: i32,
-    pub github_access_token: String,
-    pub github_user_id: i64,
     pub impersonated_by: Option<i32>,
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
 }
 
+/// Only sessions last seen before this are touched again, to avoid a write on every request
+pub const LAST_SEEN_INTERVAL_MINUTES: i64 = 5;
+
 impl Session {
-    #[allow(dead_code)]
-    pub fn find_by_user_id(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<Session>> {
-        instrumented::first("Session::find_by_user_id", || {
+    /// Active sessions of a user, including the ones they use to impersonate someone,
+    /// paired with the name of the user each session is signed in as
+    pub fn list_for_owner(
+        conn: &mut PgConnection,
+        owner_id: i32,
+    ) -> QueryResult<Vec<(Session, String)>> {
+        instrumented::load("Session::list_for_owner", || {
             dsl::sessions
-                .filter(dsl::user_id.eq(user_id))
+                .inner_join(users::table.on(users::id.eq(dsl::user_id)))
+                .filter(
+                    dsl::impersonated_by.eq(owner_id).or(dsl::impersonated_by
+                        .is_null()
+                        .and(dsl::user_id.eq(owner_id))),
+                )
                 .filter(dsl::expires_at.gt(now))
-                .filter(dsl::impersonated_by.is_null())
-                .first::<Session>(conn)
+                .order(dsl::last_seen_at.desc())
+                .select((Session::as_select(), users::name))
+                .load::<(Session, String)>(conn)
         })
-        .optional()
     }
 
     pub fn create(conn: &mut PgConnection, new_session: &NewSession) -> QueryResult<Session> {
@@ -53,22 +70,44 @@ impl Session {
         })
     }
 
-    #[allow(dead_code)]
-    #[inline(always)]
-    pub fn delete_by_user_id(conn: &mut PgConnection, user_id: i32) -> QueryResult<usize> {
-        instrumented::execute("Session::delete_by_user_id", || {
-            diesel::delete(sessions::table.filter(dsl::user_id.eq(user_id))).execute(conn)
+    /// Revoke one of the owner's sessions
+    pub fn revoke(conn: &mut PgConnection, owner_id: i32, session_id: Uuid) -> QueryResult<usize> {
+        instrumented::execute("Session::revoke", || {
+            diesel::delete(
+                sessions::table.filter(dsl::id.eq(session_id)).filter(
+                    dsl::impersonated_by.eq(owner_id).or(dsl::impersonated_by
+                        .is_null()
+                        .and(dsl::user_id.eq(owner_id))),
+                ),
+            )
+            .execute(conn)
         })
     }
 
-    pub fn scrub_expired(conn: &mut PgConnection) -> QueryResult<usize> {
-        instrumented::execute("Session::scrub_expired", || {
+    /// Revoke every session of the owner except `keep`
+    pub fn revoke_others(conn: &mut PgConnection, owner_id: i32, keep: Uuid) -> QueryResult<usize> {
+        instrumented::execute("Session::revoke_others", || {
+            diesel::delete(
+                sessions::table.filter(dsl::id.ne(keep)).filter(
+                    dsl::impersonated_by.eq(owner_id).or(dsl::impersonated_by
+                        .is_null()
+                        .and(dsl::user_id.eq(owner_id))),
+                ),
+            )
+            .execute(conn)
+        })
+    }
+
+    /// Record that the session was just used
+    pub fn touch(conn: &mut PgConnection, session_id: Uuid) -> QueryResult<usize> {
+        instrumented::execute("Session::touch", || {
             diesel::update(
-                sessions::table
-                    .filter(dsl::expires_at.lt(diesel::dsl::now))
-                    .filter(dsl::github_access_token.ne("")),
+                sessions::table.filter(dsl::id.eq(session_id)).filter(
+                    dsl::last_seen_at
+                        .lt(Utc::now() - chrono::Duration::minutes(LAST_SEEN_INTERVAL_MINUTES)),
+                ),
             )
-            .set(dsl::github_access_token.eq(""))
+            .set(dsl::last_seen_at.eq(now))
             .execute(conn)
         })
     }
@@ -89,6 +128,7 @@ impl Session {
         session_id: Uuid,
         target_user: &User,
         acting_admin_id: i32,
+        write: bool,
     ) -> QueryResult<Session> {
         instrumented::first("Session::impersonate", || {
             diesel::update(
@@ -99,8 +139,8 @@ impl Session {
             )
             .set((
                 dsl::user_id.eq(target_user.id),
-                dsl::github_user_id.eq(target_user.github_id),
                 dsl::impersonated_by.eq(Some(acting_admin_id)),
+                dsl::impersonation_write.eq(write),
             ))
             .get_result(conn)
         })
@@ -119,8 +159,8 @@ impl Session {
             )
             .set((
                 dsl::user_id.eq(admin_user.id),
-                dsl::github_user_id.eq(admin_user.github_id),
                 dsl::impersonated_by.eq::<Option<i32>>(None),
+                dsl::impersonation_write.eq(false),
             ))
             .get_result(conn)
         })
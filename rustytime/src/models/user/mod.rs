@@ -0,0 +1,595 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::handlers::page::profile::{
+    ProfileUser, UserProfile, UserProfileActivity, UserProfileCategory, UserProfileProject,
+    UserProfileTime,
+};
+use crate::models::daily_total::{DailyTotalRow, summarize_activity};
+use crate::models::heartbeat::{DurationInput, Heartbeat};
+use crate::models::project::Project;
+use crate::models::role::Role;
+use crate::schema::users::{self};
+use crate::schema::{heartbeats, import_jobs, roles, user_identities};
+use crate::utils::instrumented;
+use crate::utils::time::{
+    get_day_start_utc, get_month_start_date, get_week_start_date, parse_timezone,
+};
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = users)]
+pub struct User {
+    pub id: i32,
+    pub github_id: Option<i64>,
+    pub name: String,
+    pub avatar_url: String,
+    pub api_key: Uuid,
+    pub is_banned: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub timezone: String,
+    pub public_line_stats: bool,
+    pub entity_privacy: i16,
+    pub entity_salt: Uuid,
+    pub private_profile: bool,
+    pub email: Option<String>,
+    pub weekly_digest: bool,
+    pub last_digest_sent_on: Option<NaiveDate>,
+    /// Name of the user's role in `roles`
+    pub role: String,
+}
+
+type UserListQuery<'a> = diesel::helper_types::IntoBoxed<
+    'a,
+    diesel::helper_types::InnerJoin<users::table, roles::table>,
+    diesel::pg::Pg,
+>;
+
+#[derive(Debug, Default)]
+pub struct UserFilter {
+    /// Part of the name, or an exact GitHub id
+    pub search: Option<String>,
+    pub banned: Option<bool>,
+    /// Whether the user has any role above the default one
+    pub admin: Option<bool>,
+    pub has_imports: Option<bool>,
+    /// Only users with heartbeats in the last this many days
+    pub active_days: Option<i64>,
+}
+
+#[derive(Deserialize, JsonSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSort {
+    /// Highest ranked role first
+    #[default]
+    Role,
+    /// Most recently joined first
+    Newest,
+    Oldest,
+    /// Most tracked time first
+    TotalTime,
+}
+
+/// Escape the wildcards of a `LIKE` pattern
+pub fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// The page of `ids` with the most tracked time, ties broken by id
+pub fn rank_by_total_time(
+    mut ids: Vec<i32>,
+    totals: &HashMap<i32, i64>,
+    limit: i64,
+    offset: i64,
+) -> Vec<i32> {
+    ids.sort_by_key(|id| (std::cmp::Reverse(totals.get(id).copied().unwrap_or(0)), *id));
+    ids.into_iter()
+        .skip(offset.max(0) as usize)
+        .take(limit.max(0) as usize)
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct PartialUser {
+    pub id: i32,
+    pub github_id: Option<i64>,
+    pub name: String,
+    pub avatar_url: String,
+    #[schemars(with = "Option<String>")]
+    pub api_key: Option<Uuid>,
+    pub role: String,
+    pub is_banned: bool,
+    /// All time tracked seconds
+    pub total_seconds: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Deserialize, Debug)]
+#[diesel(table_name = users)]
+pub struct NewUser {
+    pub github_id: Option<i64>,
+    pub name: String,
+    pub avatar_url: String,
+    pub role: String,
+    pub is_banned: bool,
+    pub timezone: String,
+    pub email: Option<String>,
+}
+
+impl User {
+    #[allow(dead_code)]
+    pub fn find_by_github_id(conn: &mut PgConnection, github_id: i64) -> QueryResult<Option<User>> {
+        instrumented::first("User::find_by_github_id", || {
+            users::table
+                .filter(users::github_id.eq(github_id))
+                .first::<User>(conn)
+        })
+        .optional()
+    }
+
+    pub fn get_by_id(conn: &mut PgConnection, user_id: i32) -> QueryResult<Option<User>> {
+        instrumented::first("User::get_by_id", || {
+            users::table.find(user_id).first::<User>(conn)
+        })
+        .optional()
+    }
+
+    pub fn create(conn: &mut PgConnection, new_user: &NewUser) -> QueryResult<User> {
+        instrumented::first("User::create", || {
+            diesel::insert_into(users::table)
+                .values(new_user)
+                .get_result(conn)
+        })
+    }
+
+    /// Create a user for a newly seen provider account, the first real user becomes the owner
+    pub fn create_for_identity(
+        conn: &mut PgConnection,
+        github_id: Option<i64>,
+        username: &str,
+        avatar_url: &str,
+        email: Option<&str>,
+    ) -> QueryResult<User> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let total_users = Self::count_total_users(conn, true)?;
+
+            let new_user = NewUser {
+                github_id,
+                name: Self::available_name(conn, username)?,
+                avatar_url: avatar_url.to_string(),
+                // make the first real user an owner
+                role: if total_users == 0 {
+                    Role::OWNER
+                } else {
+                    Role::USER
+                }
+                .to_string(),
+                is_banned: false,
+                timezone: "UTC".to_string(),
+                email: email.map(str::to_string),
+            };
+            Self::create(conn, &new_user)
+        })
+    }
+
+    /// Refresh the profile from a provider account, keeping the name if another user has it
+    pub fn update_profile(
+        conn: &mut PgConnection,
+        existing_user: User,
+        username: &str,
+        avatar_url: &str,
+        email: Option<&str>,
+    ) -> QueryResult<User> {
+        // a missing email keeps the stored one, the provider may just be hiding it
+        let email = email.or(existing_user.email.as_deref());
+        let username = if existing_user.name != username
+            && Self::name_taken(conn, username, Some(existing_user.id))?
+        {
+            existing_user.name.as_str()
+        } else {
+            username
+        };
+
+        // update user info if it has changed
+        if existing_user.avatar_url != avatar_url
+            || existing_user.name != username
+            || existing_user.email.as_deref() != email
+        {
+            instrumented::first("User::update_info", || {
+                diesel::update(users::table.find(existing_user.id))
+                    .set((
+                        users::avatar_url.eq(avatar_url),
+                        users::name.eq(username),
+                        users::email.eq(email),
+                    ))
+                    .get_result(conn)
+            })
+        } else {
+            Ok(existing_user)
+        }
+    }
+
+    pub fn set_github_id(
+        conn: &mut PgConnection,
+        user_id: i32,
+        github_id: i64,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_github_id", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::github_id.eq(github_id))
+                .get_result(conn)
+        })
+    }
+
+    pub fn name_taken(
+        conn: &mut PgConnection,
+        name: &str,
+        except: Option<i32>,
+    ) -> QueryResult<bool> {
+        instrumented::first("User::name_taken", || {
+            diesel::select(diesel::dsl::exists(
+                users::table
+                    .filter(users::name.ilike(escape_like(name)))
+                    .filter(users::id.ne(except.unwrap_or(0))),
+            ))
+            .get_result(conn)
+        })
+    }
+
+    /// The name itself, or with a numeric suffix when accounts from different providers share it
+    fn available_name(conn: &mut PgConnection, name: &str) -> QueryResult<String> {
+        if !Self::name_taken(conn, name, None)? {
+            return Ok(name.to_string());
+        }
+
+        let base: String = name.chars().take(90).collect();
+        for suffix in 2..100 {
+            let candidate = format!("{base}-{suffix}");
+            if !Self::name_taken(conn, &candidate, None)? {
+                return Ok(candidate);
+            }
+        }
+
+        Ok(format!(
+            "{base}-{}",
+            &Uuid::new_v4().simple().to_string()[..8]
+        ))
+    }
+
+    /// The user's role with its permissions
+    pub fn role(&self, conn: &mut PgConnection) -> QueryResult<Role> {
+        Role::find(conn, &self.role)?.ok_or(diesel::result::Error::NotFound)
+    }
+
+    fn filtered(filter: &UserFilter) -> UserListQuery<'static> {
+        let mut query = users::table.inner_join(roles::table).into_boxed();
+        if let Some(search) = &filter.search {
+            let pattern = format!("%{}%", escape_like(search));
+            query = match search.parse::<i64>() {
+                Ok(github_id) => query.filter(
+                    users::name
+                        .ilike(pattern)
+                        .or(users::github_id.eq(github_id)),
+                ),
+                Err(_) => query.filter(users::name.ilike(pattern)),
+            };
+        }
+        if let Some(banned) = filter.banned {
+            query = query.filter(users::is_banned.eq(banned));
+        }
+        if let Some(admin) = filter.admin {
+            query = if admin {
+                query.filter(users::role.ne(Role::USER))
+            } else {
+                query.filter(users::role.eq(Role::USER))
+            };
+        }
+        if let Some(has_imports) = filter.has_imports {
+            let imports =
+                diesel::dsl::exists(import_jobs::table.filter(import_jobs::user_id.eq(users::id)));
+            query = if has_imports {
+                query.filter(imports)
+            } else {
+                query.filter(diesel::dsl::not(imports))
+            };
+        }
+        if let Some(days) = filter.active_days {
+            let since = Utc::now() - chrono::Duration::days(days);
+            query = query.filter(diesel::dsl::exists(
+                heartbeats::table
+                    .filter(heartbeats::user_id.eq(users::id))
+                    .filter(heartbeats::time.ge(since)),
+            ));
+        }
+        query
+    }
+
+    pub fn count_filtered(conn: &mut PgConnection, filter: &UserFilter) -> QueryResult<i64> {
+        instrumented::first("User::count_filtered", || {
+            Self::filtered(filter).count().get_result(conn)
+        })
+    }
+
+    /// A page of the users matching `filter`. Sorting by total time ranks by `totals`,
+    /// which maps user ids to their tracked seconds.
+    pub fn list_filtered(
+        conn: &mut PgConnection,
+        filter: &UserFilter,
+        sort: UserSort,
+        totals: &HashMap<i32, i64>,
+        limit: i64,
+        offset: i64,
+    ) -> QueryResult<Vec<User>> {
+        let query = Self::filtered(filter);
+        let query = match sort {
+            UserSort::Role => query
+                .order(roles::rank.desc())
+                .then_order_by(users::id.asc()),
+            UserSort::Newest => query
+                .order(users::created_at.desc())
+                .then_order_by(users::id.desc()),
+            UserSort::Oldest => query
+                .order(users::created_at.asc())
+                .then_order_by(users::id.asc()),
+            UserSort::TotalTime => {
+                let ids = instrumented::load("User::list_filtered_ids", || {
+                    query.select(users::id).load::<i32>(conn)
+                })?;
+                let page = rank_by_total_time(ids, totals, limit, offset);
+
+                let mut users = instrumented::load("User::list_by_ids", || {
+                    users::table
+                        .filter(users::id.eq_any(&page))
+                        .load::<User>(conn)
+                })?;
+                users.sort_by_key(|user| page.iter().position(|id| *id == user.id));
+                return Ok(users);
+            }
+        };
+
+        instrumented::load("User::list_filtered", || {
+            query
+                .limit(limit)
+                .offset(offset)
+                .select(User::as_select())
+                .load::<User>(conn)
+        })
+    }
+
+    pub fn count_total_users(conn: &mut PgConnection, only_real: bool) -> QueryResult<i64> {
+        if only_real {
+            // users without a linked login, like the seeded one, are not real
+            instrumented::first("User::count_total_users_real", || {
+                users::table
+                    .count()
+                    .filter(diesel::dsl::exists(
+                        user_identities::table.filter(user_identities::user_id.eq(users::id)),
+                    ))
+                    .get_result(conn)
+            })
+        } else {
+            instrumented::first("User::count_total_users", || {
+                users::table.count().get_result(conn)
+            })
+        }
+    }
+
+    pub fn set_role(conn: &mut PgConnection, user_id: i32, role: &str) -> QueryResult<usize> {
+        instrumented::execute("User::set_role", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::role.eq(role))
+                .execute(conn)
+        })
+    }
+
+    pub fn set_banned(conn: &mut PgConnection, user_id: i32, banned: bool) -> QueryResult<usize> {
+        instrumented::execute("User::set_banned", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::is_banned.eq(banned))
+                .execute(conn)
+        })
+    }
+
+    pub fn set_timezone(
+        conn: &mut PgConnection,
+        user_id: i32,
+        timezone: &str,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_timezone", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::timezone.eq(timezone))
+                .get_result(conn)
+        })
+    }
+
+    pub fn set_public_line_stats(
+        conn: &mut PgConnection,
+        user_id: i32,
+        public_line_stats: bool,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_public_line_stats", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::public_line_stats.eq(public_line_stats))
+                .get_result(conn)
+        })
+    }
+
+    pub fn set_private_profile(
+        conn: &mut PgConnection,
+        user_id: i32,
+        private_profile: bool,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_private_profile", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::private_profile.eq(private_profile))
+                .get_result(conn)
+        })
+    }
+
+    pub fn set_entity_privacy(
+        conn: &mut PgConnection,
+        user_id: i32,
+        entity_privacy: i16,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_entity_privacy", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::entity_privacy.eq(entity_privacy))
+                .get_result(conn)
+        })
+    }
+
+    pub fn set_weekly_digest(
+        conn: &mut PgConnection,
+        user_id: i32,
+        weekly_digest: bool,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_weekly_digest", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::weekly_digest.eq(weekly_digest))
+                .get_result(conn)
+        })
+    }
+
+    pub fn set_email(
+        conn: &mut PgConnection,
+        user_id: i32,
+        email: Option<&str>,
+    ) -> QueryResult<User> {
+        instrumented::first("User::set_email", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::email.eq(email))
+                .get_result(conn)
+        })
+    }
+
+    /// Users that opted into the weekly digest and have an email address
+    pub fn list_digest_recipients(conn: &mut PgConnection) -> QueryResult<Vec<User>> {
+        instrumented::load("User::list_digest_recipients", || {
+            users::table
+                .filter(users::weekly_digest.eq(true))
+                .filter(users::email.is_not_null())
+                .filter(users::is_banned.eq(false))
+                .load::<User>(conn)
+        })
+    }
+
+    pub fn mark_digest_sent(
+        conn: &mut PgConnection,
+        user_id: i32,
+        sent_on: NaiveDate,
+    ) -> QueryResult<usize> {
+        instrumented::execute("User::mark_digest_sent", || {
+            diesel::update(users::table.find(user_id))
+                .set(users::last_digest_sent_on.eq(sent_on))
+                .execute(conn)
+        })
+    }
+
+    /// Find a user by name for public pages, treating private profiles as missing
+    pub fn find_public_by_name(
+        conn: &mut PgConnection,
+        username: &str,
+    ) -> QueryResult<Option<User>> {
+        instrumented::first("User::find_public_by_name", || {
+            users::table
+                .filter(users::name.ilike(escape_like(username)))
+                .filter(users::private_profile.eq(false))
+                .first::<User>(conn)
+        })
+        .optional()
+    }
+
+    pub fn get_user_profile(
+        conn: &mut PgConnection,
+        username: &str,
+    ) -> QueryResult<Option<UserProfile>> {
+        let Some(user) = Self::find_public_by_name(conn, username)? else {
+            return Ok(None);
+        };
+
+        let tz: Tz = parse_timezone(&user.timezone);
+        let now = Utc::now();
+        let today_local = now.with_timezone(&tz).date_naive();
+
+        let today_start = get_day_start_utc(today_local, tz);
+        let week_start = get_day_start_utc(get_week_start_date(today_local), tz);
+
+        let make_input = |start: Option<DateTime<Utc>>| DurationInput {
+            user_id: Some(user.id),
+            start_date: start,
+            end_date: Some(now),
+            project: None,
+            language: None,
+            entity: None,
+            type_filter: None,
+        };
+
+        let today_seconds =
+            Heartbeat::get_user_duration_seconds(conn, make_input(Some(today_start)))?;
+        let week_seconds =
+            Heartbeat::get_user_duration_seconds(conn, make_input(Some(week_start)))?;
+        let all_time_seconds = Heartbeat::get_user_duration_seconds(conn, make_input(None))?;
+
+        let month_start = get_day_start_utc(get_month_start_date(today_local), tz);
+        let top_projects = Project::top_projects_by_range(conn, user.id, month_start, now, 6)?;
+        let profile_projects: Vec<UserProfileProject> = top_projects
+            .into_iter()
+            .map(|p| UserProfileProject {
+                name: p.name,
+                project_url: p.project_url,
+                total_seconds: p.total_seconds,
+            })
+            .collect();
+
+        let top_categories =
+            Heartbeat::top_categories_by_range(conn, user.id, month_start, now, 6)?;
+        let profile_categories: Vec<UserProfileCategory> = top_categories
+            .into_iter()
+            .map(|c| UserProfileCategory {
+                name: c.name,
+                total_seconds: c.total_seconds,
+            })
+            .collect();
+
+        let line_changes = if user.public_line_stats {
+            Some(
+                Heartbeat::get_line_stats(conn, user.id, Some(month_start), &user.timezone, None)?
+                    .total,
+            )
+        } else {
+            None
+        };
+
+        let days = DailyTotalRow::last_year_for_user(conn, user.id, today_local)?;
+        let summary = summarize_activity(&days);
+
+        Ok(Some(UserProfile {
+            user: ProfileUser {
+                username: user.name,
+                avatar_url: user.avatar_url,
+                role: user.role,
+            },
+            projects: profile_projects,
+            categories: profile_categories,
+            line_changes,
+            time: UserProfileTime {
+                today: today_seconds,
+                week: week_seconds,
+                all_time: all_time_seconds,
+            },
+            activity: UserProfileActivity { days, summary },
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests;
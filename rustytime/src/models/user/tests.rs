@@ -0,0 +1,36 @@
+use super::*;
+
+#[test]
+fn escape_like_escapes_wildcards() {
+    assert_eq!(escape_like("plain"), "plain");
+    assert_eq!(escape_like("100%_done"), "100\\%\\_done");
+    assert_eq!(escape_like("back\\slash"), "back\\\\slash");
+}
+
+#[test]
+fn rank_by_total_time_orders_most_tracked_first() {
+    let totals = HashMap::from([(1, 60), (2, 3600), (3, 600)]);
+    assert_eq!(
+        rank_by_total_time(vec![1, 2, 3], &totals, 10, 0),
+        vec![2, 3, 1]
+    );
+}
+
+#[test]
+fn rank_by_total_time_puts_untracked_users_last_by_id() {
+    let totals = HashMap::from([(5, 60)]);
+    assert_eq!(
+        rank_by_total_time(vec![9, 5, 2], &totals, 10, 0),
+        vec![5, 2, 9]
+    );
+}
+
+#[test]
+fn rank_by_total_time_pages_after_sorting() {
+    let totals = HashMap::from([(1, 10), (2, 20), (3, 30), (4, 40)]);
+    assert_eq!(
+        rank_by_total_time(vec![1, 2, 3, 4], &totals, 2, 1),
+        vec![3, 2]
+    );
+    assert!(rank_by_total_time(vec![1, 2], &totals, 2, 5).is_empty());
+}
@@ -0,0 +1,281 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::models::role::Role;
+use crate::models::user::User;
+use crate::schema::user_identities;
+use crate::utils::instrumented;
+use crate::utils::local_auth::{LOCAL_PROVIDER, local_subject};
+use crate::utils::oauth::{ProviderKind, ProviderUser};
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = user_identities)]
+pub struct UserIdentity {
+    pub id: i32,
+    pub user_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Only set for local accounts
+    pub password_hash: Option<String>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = user_identities)]
+pub struct NewUserIdentity {
+    pub user_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub username: String,
+    pub email: Option<String>,
+    pub password_hash: Option<String>,
+}
+
+/// Result of signing in with a provider account
+#[derive(Debug)]
+pub enum SignInOutcome {
+    /// Signed in as the account's user, created if the account was new
+    SignedIn(Box<User>),
+    /// The account was linked to the already signed in user
+    Linked,
+    /// The account is already linked to a different user
+    AlreadyLinked,
+    /// The account is new but no new users can sign up
+    SignUpClosed,
+}
+
+impl UserIdentity {
+    pub fn find(
+        conn: &mut PgConnection,
+        provider: &str,
+        subject: &str,
+    ) -> QueryResult<Option<UserIdentity>> {
+        instrumented::first("UserIdentity::find", || {
+            user_identities::table
+                .filter(user_identities::provider.eq(provider))
+                .filter(user_identities::subject.eq(subject))
+                .select(UserIdentity::as_select())
+                .first::<UserIdentity>(conn)
+        })
+        .optional()
+    }
+
+    pub fn list_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<UserIdentity>> {
+        instrumented::load("UserIdentity::list_for_user", || {
+            user_identities::table
+                .filter(user_identities::user_id.eq(user_id))
+                .order(user_identities::created_at.asc())
+                .then_order_by(user_identities::id.asc())
+                .select(UserIdentity::as_select())
+                .load::<UserIdentity>(conn)
+        })
+    }
+
+    /// Whether the user's profile comes from a local account rather than a login provider
+    pub fn profile_is_local(conn: &mut PgConnection, user_id: i32) -> QueryResult<bool> {
+        let primary = Self::list_for_user(conn, user_id)?.into_iter().next();
+        Ok(primary.is_some_and(|identity| identity.provider == LOCAL_PROVIDER))
+    }
+
+    pub fn create(
+        conn: &mut PgConnection,
+        new_identity: &NewUserIdentity,
+    ) -> QueryResult<UserIdentity> {
+        instrumented::first("UserIdentity::create", || {
+            diesel::insert_into(user_identities::table)
+                .values(new_identity)
+                .returning(UserIdentity::as_returning())
+                .get_result(conn)
+        })
+    }
+
+    fn update_account(
+        conn: &mut PgConnection,
+        identity_id: i32,
+        account: &ProviderUser,
+    ) -> QueryResult<UserIdentity> {
+        instrumented::first("UserIdentity::update_account", || {
+            diesel::update(user_identities::table.find(identity_id))
+                .set((
+                    user_identities::username.eq(&account.username),
+                    user_identities::email.eq(&account.email),
+                ))
+                .returning(UserIdentity::as_returning())
+                .get_result(conn)
+        })
+    }
+
+    /// Unlink an identity, refusing to remove the last way the user can sign in
+    pub fn delete(conn: &mut PgConnection, user_id: i32, identity_id: i32) -> QueryResult<usize> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let identities = Self::list_for_user(conn, user_id)?;
+            if identities.len() <= 1 {
+                return Ok(0);
+            }
+
+            instrumented::execute("UserIdentity::delete", || {
+                diesel::delete(
+                    user_identities::table
+                        .filter(user_identities::id.eq(identity_id))
+                        .filter(user_identities::user_id.eq(user_id)),
+                )
+                .execute(conn)
+            })
+        })
+    }
+
+    /// Sign in with a provider account, or link it to `link_to` when a user is already signed in
+    pub fn sign_in(
+        conn: &mut PgConnection,
+        kind: ProviderKind,
+        account: &ProviderUser,
+        link_to: Option<i32>,
+        allow_sign_up: bool,
+    ) -> QueryResult<SignInOutcome> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let github_id = match kind {
+                ProviderKind::Github => account.subject.parse::<i64>().ok(),
+                _ => None,
+            };
+
+            let user_id = match (Self::find(conn, kind.id(), &account.subject)?, link_to) {
+                (Some(identity), Some(user_id)) if identity.user_id != user_id => {
+                    return Ok(SignInOutcome::AlreadyLinked);
+                }
+                (Some(identity), _) => {
+                    Self::update_account(conn, identity.id, account)?;
+                    identity.user_id
+                }
+                (None, Some(user_id)) => {
+                    Self::create(conn, &Self::new_for(user_id, kind, account))?;
+                    user_id
+                }
+                (None, None) if !allow_sign_up => return Ok(SignInOutcome::SignUpClosed),
+                (None, None) => {
+                    let user = User::create_for_identity(
+                        conn,
+                        github_id,
+                        &account.username,
+                        &account.avatar_url,
+                        account.email.as_deref(),
+                    )?;
+                    Self::create(conn, &Self::new_for(user.id, kind, account))?;
+                    return Ok(SignInOutcome::SignedIn(Box::new(user)));
+                }
+            };
+
+            let mut user =
+                User::get_by_id(conn, user_id)?.ok_or(diesel::result::Error::NotFound)?;
+
+            if let Some(github_id) = github_id
+                && user.github_id.is_none()
+            {
+                user = User::set_github_id(conn, user.id, github_id)?;
+            }
+
+            // the profile follows the account the user first signed up with
+            let primary = Self::list_for_user(conn, user.id)?.into_iter().next();
+            if primary.is_some_and(|p| p.provider == kind.id() && p.subject == account.subject) {
+                user = User::update_profile(
+                    conn,
+                    user,
+                    &account.username,
+                    &account.avatar_url,
+                    account.email.as_deref(),
+                )?;
+            }
+
+            Ok(match link_to {
+                Some(_) => SignInOutcome::Linked,
+                None => SignInOutcome::SignedIn(Box::new(user)),
+            })
+        })
+    }
+
+    fn new_for(user_id: i32, kind: ProviderKind, account: &ProviderUser) -> NewUserIdentity {
+        NewUserIdentity {
+            user_id,
+            provider: kind.id().to_string(),
+            subject: account.subject.clone(),
+            username: account.username.clone(),
+            email: account.email.clone(),
+            password_hash: None,
+        }
+    }
+
+    /// Find the local account with the given username
+    pub fn find_local(
+        conn: &mut PgConnection,
+        username: &str,
+    ) -> QueryResult<Option<UserIdentity>> {
+        Self::find(conn, LOCAL_PROVIDER, &local_subject(username))
+    }
+
+    pub fn set_password_hash(
+        conn: &mut PgConnection,
+        identity_id: i32,
+        password_hash: &str,
+    ) -> QueryResult<usize> {
+        instrumented::execute("UserIdentity::set_password_hash", || {
+            diesel::update(user_identities::table.find(identity_id))
+                .set(user_identities::password_hash.eq(password_hash))
+                .execute(conn)
+        })
+    }
+
+    /// Create a user with a local account, none when the username is already taken
+    pub fn register_local(
+        conn: &mut PgConnection,
+        username: &str,
+        password_hash: &str,
+    ) -> QueryResult<Option<User>> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            if Self::find_local(conn, username)?.is_some()
+                || User::name_taken(conn, username, None)?
+            {
+                return Ok(None);
+            }
+
+            let user = User::create_for_identity(conn, None, username, "", None)?;
+            Self::create(conn, &Self::new_local(user.id, username, password_hash))?;
+            Ok(Some(user))
+        })
+    }
+
+    /// Create or update the owner account of single-user mode
+    pub fn provision_local_owner(
+        conn: &mut PgConnection,
+        username: &str,
+        password_hash: &str,
+    ) -> QueryResult<User> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let user_id = match Self::find_local(conn, username)? {
+                Some(identity) => {
+                    Self::set_password_hash(conn, identity.id, password_hash)?;
+                    identity.user_id
+                }
+                None => {
+                    let user = User::create_for_identity(conn, None, username, "", None)?;
+                    Self::create(conn, &Self::new_local(user.id, username, password_hash))?;
+                    user.id
+                }
+            };
+
+            User::set_role(conn, user_id, Role::OWNER)?;
+            User::get_by_id(conn, user_id)?.ok_or(diesel::result::Error::NotFound)
+        })
+    }
+
+    fn new_local(user_id: i32, username: &str, password_hash: &str) -> NewUserIdentity {
+        NewUserIdentity {
+            user_id,
+            provider: LOCAL_PROVIDER.to_string(),
+            subject: local_subject(username),
+            username: username.to_string(),
+            email: None,
+            password_hash: Some(password_hash.to_string()),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use std::net::{IpAddr, Ipv4Addr};
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use hmac::{Hmac, Mac};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use url::{Host, Url};
+use uuid::Uuid;
+
+use crate::models::role::Permission;
+use crate::schema::{roles, users, webhook_deliveries, webhooks};
+use crate::utils::instrumented;
+
+pub const MAX_URL_LENGTH: usize = 2048;
+const SECRET_PREFIX: &str = "whsec_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum WebhookEvent {
+    #[serde(rename = "import.completed")]
+    ImportCompleted,
+    #[serde(rename = "import.failed")]
+    ImportFailed,
+    #[serde(rename = "goal.reached")]
+    GoalReached,
+    #[serde(rename = "leaderboard.rank_changed")]
+    LeaderboardRankChanged,
+    #[serde(rename = "summary.daily")]
+    DailySummary,
+    /// Test event sent on request, delivered regardless of the subscribed events
+    #[serde(rename = "ping")]
+    Ping,
+}
+
+impl WebhookEvent {
+    /// Events a webhook can subscribe to
+    pub const SUBSCRIBABLE: [WebhookEvent; 5] = [
+        WebhookEvent::ImportCompleted,
+        WebhookEvent::ImportFailed,
+        WebhookEvent::GoalReached,
+        WebhookEvent::LeaderboardRankChanged,
+        WebhookEvent::DailySummary,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ImportCompleted => "import.completed",
+            WebhookEvent::ImportFailed => "import.failed",
+            WebhookEvent::GoalReached => "goal.reached",
+            WebhookEvent::LeaderboardRankChanged => "leaderboard.rank_changed",
+            WebhookEvent::DailySummary => "summary.daily",
+            WebhookEvent::Ping => "ping",
+        }
+    }
+}
+
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = webhooks)]
+pub struct Webhook {
+    pub id: i32,
+    pub user_id: i32,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    /// Owner-configured webhook receiving the events of every user
+    pub global: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhooks)]
+pub struct NewWebhook {
+    pub user_id: i32,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+    pub global: bool,
+}
+
+#[derive(Queryable, Selectable, Serialize, Debug, Clone, JsonSchema)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i32,
+    pub event: String,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub webhook_id: i32,
+    pub event: String,
+    pub attempt: i32,
+    pub status_code: Option<i32>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub duration_ms: i32,
+}
+
+/// Generate a random signing secret for a new webhook
+pub fn generate_secret() -> String {
+    format!(
+        "{SECRET_PREFIX}{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    )
+}
+
+/// Signature sent in the `X-Rustytime-Signature` header: HMAC-SHA256 of `{timestamp}.{body}`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    format!(
+        "sha256={}",
+        digest
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect::<String>()
+    )
+}
+
+/// Whether an address belongs to a local, private or otherwise non-public network
+pub fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_multicast()
+                // "this network", 0.0.0.0/8
+                || octets[0] == 0
+                // carrier-grade NAT, 100.64.0.0/10
+                || (octets[0] == 100 && (octets[1] & 0xc0) == 64)
+                // IETF protocol assignments, 192.0.0.0/24
+                || octets[..3] == [192, 0, 0]
+                // benchmarking, 198.18.0.0/15
+                || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+                // reserved 240.0.0.0/4, including the broadcast address
+                || octets[0] >= 240
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            let [.., a, b, c, d] = ip.octets();
+            // IPv4-mapped ::ffff:0:0/96, IPv4-compatible ::/96 (covering :: and ::1)
+            // and NAT64 64:ff9b::/96 addresses embed an IPv4 address in their last 32 bits
+            if (segments[..5] == [0, 0, 0, 0, 0] && matches!(segments[5], 0 | 0xffff))
+                || segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0]
+            {
+                return is_private_ip(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+            }
+            // 6to4 addresses embed the IPv4 address of their relay, 2002::/16
+            if segments[0] == 0x2002 {
+                let [_, _, a, b, c, d, ..] = ip.octets();
+                return is_private_ip(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+            }
+            ip.is_multicast()
+                // unique local fc00::/7, link local fe80::/10 and site local fec0::/10
+                || (segments[0] & 0xfe00) == 0xfc00
+                || (segments[0] & 0xffc0) == 0xfe80
+                || (segments[0] & 0xffc0) == 0xfec0
+        }
+    }
+}
+
+/// Validate a webhook URL, rejecting local and private network targets unless allowed
+pub fn validate_webhook_url(url: &str, allow_private: bool) -> Result<Url, String> {
+    let url = url.trim();
+    if url.len() > MAX_URL_LENGTH {
+        return Err(format!("URL must be at most {MAX_URL_LENGTH} characters"));
+    }
+
+    let parsed = Url::parse(url).map_err(|_| "Invalid URL".to_string())?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("URL must use http or https".to_string());
+    }
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return Err("URL must not contain credentials".to_string());
+    }
+
+    let private = match parsed.host() {
+        None => return Err("URL must have a host".to_string()),
+        Some(Host::Ipv4(ip)) => is_private_ip(IpAddr::V4(ip)),
+        Some(Host::Ipv6(ip)) => is_private_ip(IpAddr::V6(ip)),
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost"
+                || [".localhost", ".local", ".internal"]
+                    .iter()
+                    .any(|suffix| domain.ends_with(suffix))
+        }
+    };
+    if private && !allow_private {
+        return Err("URL must not point to a local or private address".to_string());
+    }
+
+    Ok(parsed)
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        event == WebhookEvent::Ping || self.events.iter().any(|e| e == event.as_str())
+    }
+
+    /// Whether an event about `user_id` should be sent to this webhook
+    pub fn receives(&self, event: WebhookEvent, user_id: i32) -> bool {
+        self.enabled && self.subscribes_to(event) && (self.global || self.user_id == user_id)
+    }
+
+    pub fn list_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<Vec<Webhook>> {
+        instrumented::load("Webhook::list_for_user", || {
+            webhooks::table
+                .filter(webhooks::user_id.eq(user_id))
+                .order(webhooks::id.asc())
+                .load::<Webhook>(conn)
+        })
+    }
+
+    pub fn count_for_user(conn: &mut PgConnection, user_id: i32) -> QueryResult<i64> {
+        instrumented::first("Webhook::count_for_user", || {
+            webhooks::table
+                .filter(webhooks::user_id.eq(user_id))
+                .count()
+                .get_result(conn)
+        })
+    }
+
+    pub fn find(conn: &mut PgConnection, webhook_id: i32) -> QueryResult<Option<Webhook>> {
+        instrumented::first("Webhook::find", || {
+            webhooks::table
+                .filter(webhooks::id.eq(webhook_id))
+                .first::<Webhook>(conn)
+                .optional()
+        })
+    }
+
+    pub fn find_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        webhook_id: i32,
+    ) -> QueryResult<Option<Webhook>> {
+        instrumented::first("Webhook::find_for_user", || {
+            webhooks::table
+                .filter(webhooks::user_id.eq(user_id))
+                .filter(webhooks::id.eq(webhook_id))
+                .first::<Webhook>(conn)
+                .optional()
+        })
+    }
+
+    /// Enabled webhooks subscribed to an event, of any user. Global webhooks are only
+    /// included while their owner's role still grants `global_webhooks`
+    pub fn subscribed_to(
+        conn: &mut PgConnection,
+        event: WebhookEvent,
+    ) -> QueryResult<Vec<Webhook>> {
+        instrumented::load("Webhook::subscribed_to", || {
+            webhooks::table
+                .inner_join(users::table.inner_join(roles::table))
+                .filter(webhooks::enabled.eq(true))
+                .filter(webhooks::events.contains(vec![event.as_str().to_string()]))
+                .filter(
+                    webhooks::global.eq(false).or(roles::permissions
+                        .contains(vec![Permission::GlobalWebhooks.as_str().to_string()])),
+                )
+                .order(webhooks::id.asc())
+                .select(Webhook::as_select())
+                .load(conn)
+        })
+    }
+
+    pub fn create(conn: &mut PgConnection, new_webhook: &NewWebhook) -> QueryResult<Webhook> {
+        instrumented::first("Webhook::create", || {
+            diesel::insert_into(webhooks::table)
+                .values(new_webhook)
+                .get_result(conn)
+        })
+    }
+
+    pub fn delete(conn: &mut PgConnection, user_id: i32, webhook_id: i32) -> QueryResult<usize> {
+        instrumented::execute("Webhook::delete", || {
+            diesel::delete(
+                webhooks::table
+                    .filter(webhooks::user_id.eq(user_id))
+                    .filter(webhooks::id.eq(webhook_id)),
+            )
+            .execute(conn)
+        })
+    }
+}
+
+impl WebhookDelivery {
+    pub fn record(
+        conn: &mut PgConnection,
+        delivery: &NewWebhookDelivery,
+    ) -> QueryResult<WebhookDelivery> {
+        instrumented::first("WebhookDelivery::record", || {
+            diesel::insert_into(webhook_deliveries::table)
+                .values(delivery)
+                .get_result(conn)
+        })
+    }
+
+    /// Latest delivery attempts of all of a user's webhooks, newest first
+    pub fn recent_for_user(
+        conn: &mut PgConnection,
+        user_id: i32,
+        limit: i64,
+    ) -> QueryResult<Vec<WebhookDelivery>> {
+        instrumented::load("WebhookDelivery::recent_for_user", || {
+            webhook_deliveries::table
+                .inner_join(webhooks::table)
+                .filter(webhooks::user_id.eq(user_id))
+                .order(webhook_deliveries::created_at.desc())
+                .limit(limit)
+                .select(WebhookDelivery::as_select())
+                .load(conn)
+        })
+    }
+
+    pub fn delete_older_than(conn: &mut PgConnection, days: i64) -> QueryResult<usize> {
+        let cutoff = Utc::now() - Duration::days(days);
+        instrumented::execute("WebhookDelivery::delete_older_than", || {
+            diesel::delete(
+                webhook_deliveries::table.filter(webhook_deliveries::created_at.lt(cutoff)),
+            )
+            .execute(conn)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,23 +1,43 @@
 use std::sync::Arc;
 
-use crate::handlers::admin::change_user_admin_level;
+use crate::handlers::admin::{change_user_role, set_user_banned};
+use crate::handlers::api::goals::get_goals;
 use crate::handlers::api::user::{create_heartbeats, get_statusbar_today};
+use crate::handlers::auth::{
+    callback, local_login, local_register, login, logout, providers, verify_session,
+};
+use crate::handlers::badge::{language_badge, project_badge, stats_card, time_badge};
+use crate::handlers::data::dependencies::dependency_stats;
+use crate::handlers::data::goals::{create_goal, delete_goal, goals};
+use crate::handlers::data::identities::{delete_identity, identities};
 use crate::handlers::data::import::{import_heartbeats, import_status};
+use crate::handlers::data::language_mappings::{
+    apply_language_mappings, create_language_mapping, delete_language_mapping, language_mappings,
+};
 use crate::handlers::data::project_aliases::{
     add_project_alias, delete_project_alias, project_aliases,
 };
-use crate::handlers::data::projects::{projects_list, update_project};
-use crate::handlers::github::{callback, login, logout, verify_session};
+use crate::handlers::data::project_rules::{
+    apply_project_rules, create_project_rule, delete_project_rule, project_rules,
+};
+use crate::handlers::data::projects::{
+    merge_project, projects_list, rename_project, update_project,
+};
+use crate::handlers::data::sessions::{revoke_other_sessions, revoke_session, sessions};
+use crate::handlers::data::webhooks::{create_webhook, delete_webhook, test_webhook, webhooks};
 use crate::handlers::homepage::home_page;
 use crate::handlers::info::info;
 use crate::handlers::page::admin::admin_dashboard;
+use crate::handlers::page::admin::admin_user_detail;
 use crate::handlers::page::admin::impersonate_user;
+use crate::handlers::page::audit_log::audit_log;
 use crate::handlers::page::dashboard::dashboard;
 use crate::handlers::page::imports::admin_imports;
 use crate::handlers::page::leaderboard::leaderboard_page;
 use crate::handlers::page::profile::profile_handler;
-use crate::handlers::page::projects::projects_dashboard;
+use crate::handlers::page::projects::{project_detail, projects_dashboard};
 use crate::handlers::page::settings::{settings_page, update_settings};
+use crate::models::role::Permission;
 use crate::state::AppState;
 use crate::utils::middleware;
 use aide::axum::routing::{delete_with, get_with, post_with, put_with};
@@ -71,25 +91,79 @@ pub fn create_app_router(
                         .tag("Pages")
                 })),
         )
+        .nest(
+            "/badge/{username}",
+            ApiRouter::new()
+                .api_route("/time", get_with(time_badge, |op| {
+                    op.id("time_badge")
+                        .summary("Coding Time Badge")
+                        .description("SVG badge with the user's coding time in the given range.")
+                        .tag("Badges")
+                }))
+                .api_route("/language", get_with(language_badge, |op| {
+                    op.id("language_badge")
+                        .summary("Top Language Badge")
+                        .description("SVG badge with the user's most used language in the given range.")
+                        .tag("Badges")
+                }))
+                .api_route("/project/{project}", get_with(project_badge, |op| {
+                    op.id("project_badge")
+                        .summary("Project Badge")
+                        .description(
+                            "SVG badge with the coding time of one of the user's projects. Hidden projects are reported as not found.",
+                        )
+                        .tag("Badges")
+                })),
+        )
+        .api_route("/card/{username}", get_with(stats_card, |op| {
+            op.id("stats_card")
+                .summary("Stats Card")
+                .description(
+                    "Themed SVG card with the user's top languages and total time in the given range, optionally listing the top projects.",
+                )
+                .tag("Badges")
+        }))
         // auth routes
         .merge(
             ApiRouter::new().nest(
-                "/auth/github",
+                "/auth",
                 ApiRouter::new()
-                    .api_route("/login", get_with(login, |op| {
-                        op.id("github_login")
-                            .summary("GitHub OAuth Login")
-                            .description("Initiates the GitHub OAuth login process.")
+                    .api_route("/providers", get_with(providers, |op| {
+                        op.id("auth_providers")
+                            .summary("Login Providers")
+                            .description("Lists the login providers configured on this server.")
+                            .tag("Authentication")
+                    }))
+                    .api_route("/local/login", post_with(local_login, |op| {
+                        op.id("local_login")
+                            .summary("Local Account Login")
+                            .description("Logs in with a username and password when local accounts are enabled.")
+                            .tag("Authentication")
+                    }))
+                    .api_route("/local/register", post_with(local_register, |op| {
+                        op.id("local_register")
+                            .summary("Create Local Account")
+                            .description(
+                                "Creates a local account and logs in with it. Only available when registration is open.",
+                            )
                             .tag("Authentication")
                     }))
-                    .api_route("/callback", get_with(callback, |op| {
-                        op.id("github_callback")
-                            .summary("GitHub OAuth Callback")
-                            .description("Handles the callback from GitHub after OAuth authentication.")
+                    .api_route("/{provider}/login", get_with(login, |op| {
+                        op.id("oauth_login")
+                            .summary("OAuth Login")
+                            .description("Initiates the OAuth login process with the given provider.")
                             .tag("Authentication")
                     }))
-                    .api_route("/logout", get_with(logout, |op| {
-                        op.id("github_logout")
+                    .api_route("/{provider}/callback", get_with(callback, |op| {
+                        op.id("oauth_callback")
+                            .summary("OAuth Callback")
+                            .description(
+                                "Handles the callback from the provider after OAuth authentication. Links the account to the current user when already signed in.",
+                            )
+                            .tag("Authentication")
+                    }))
+                    .api_route("/logout", post_with(logout, |op| {
+                        op.id("logout")
                             .summary("Logout User")
                             .description("Logs out the currently authenticated user.")
                             .tag("Authentication")
@@ -123,6 +197,13 @@ pub fn create_app_router(
                                 .tag("Pages")
                                 .security_requirement("Authenticated")
                         }))
+                        .api_route("/projects/{id}", get_with(project_detail, |op| {
+                            op.id("project_detail")
+                                .summary("Project Detail Page")
+                                .description("Data for a single project's detail page.")
+                                .tag("Pages")
+                                .security_requirement("Authenticated")
+                        }))
                         .api_route("/settings", get_with(settings_page, |op| {
                             op.id("settings_page")
                                 .summary("User Settings Page")
@@ -180,6 +261,211 @@ pub fn create_app_router(
                                 .tag("Data")
                                 .security_requirement("Authenticated")
                         }))
+                        .api_route(
+                            "/project_rules",
+                            get_with(project_rules, |op| {
+                                op.id("list_project_rules")
+                                    .summary("List Project Rules")
+                                    .description(
+                                        "Lists the regex rules used to map heartbeats to projects.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            })
+                            .post_with(create_project_rule, |op| {
+                                op.id("create_project_rule")
+                                    .summary("Create Project Rule")
+                                    .description(
+                                        "Creates a rule that maps heartbeats whose project or file path matches a regex to a target project.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            }),
+                        )
+                        .api_route("/project_rules/apply", post_with(apply_project_rules, |op| {
+                            op.id("apply_project_rules")
+                                .summary("Apply Project Rules")
+                                .description(
+                                    "Starts a background job that re-applies the project rules to all existing heartbeats.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/project_rules/{id}", delete_with(delete_project_rule, |op| {
+                            op.id("delete_project_rule")
+                                .summary("Delete Project Rule")
+                                .description("Deletes the specified project rule.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route(
+                            "/language_mappings",
+                            get_with(language_mappings, |op| {
+                                op.id("list_language_mappings")
+                                    .summary("List Language Mappings")
+                                    .description(
+                                        "Lists the user's extension-to-language and language-to-language overrides.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            })
+                            .post_with(create_language_mapping, |op| {
+                                op.id("create_language_mapping")
+                                    .summary("Create Language Mapping")
+                                    .description(
+                                        "Creates a language override, replacing any existing override for the same source.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            }),
+                        )
+                        .api_route("/language_mappings/apply", post_with(apply_language_mappings, |op| {
+                            op.id("apply_language_mappings")
+                                .summary("Apply Language Mappings")
+                                .description(
+                                    "Starts a background job that normalizes the languages of all existing heartbeats.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/language_mappings/{id}", delete_with(delete_language_mapping, |op| {
+                            op.id("delete_language_mapping")
+                                .summary("Delete Language Mapping")
+                                .description("Deletes the specified language mapping.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route(
+                            "/goals",
+                            get_with(goals, |op| {
+                                op.id("list_goals")
+                                    .summary("List Goals")
+                                    .description(
+                                        "Lists the user's coding goals with their recent progress and current streak.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            })
+                            .post_with(create_goal, |op| {
+                                op.id("create_goal")
+                                    .summary("Create Goal")
+                                    .description(
+                                        "Creates a daily or weekly coding goal, optionally scoped to a project or language.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            }),
+                        )
+                        .api_route("/goals/{id}", delete_with(delete_goal, |op| {
+                            op.id("delete_goal")
+                                .summary("Delete Goal")
+                                .description("Deletes the specified goal.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route(
+                            "/webhooks",
+                            get_with(webhooks, |op| {
+                                op.id("list_webhooks")
+                                    .summary("List Webhooks")
+                                    .description(
+                                        "Lists the user's webhooks together with their latest delivery attempts.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            })
+                            .post_with(create_webhook, |op| {
+                                op.id("create_webhook")
+                                    .summary("Create Webhook")
+                                    .description(
+                                        "Creates a webhook for the selected events and returns its signing secret. Owners can create global webhooks receiving every user's events.",
+                                    )
+                                    .tag("Data")
+                                    .security_requirement("Authenticated")
+                            }),
+                        )
+                        .api_route("/webhooks/{id}", delete_with(delete_webhook, |op| {
+                            op.id("delete_webhook")
+                                .summary("Delete Webhook")
+                                .description("Deletes the specified webhook and its delivery log.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/identities", get_with(identities, |op| {
+                            op.id("list_identities")
+                                .summary("List Linked Accounts")
+                                .description(
+                                    "Lists the login provider accounts linked to the user and the providers available for linking.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/identities/{id}", delete_with(delete_identity, |op| {
+                            op.id("delete_identity")
+                                .summary("Unlink Account")
+                                .description(
+                                    "Unlinks a login provider account. The last linked account cannot be removed.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/sessions", get_with(sessions, |op| {
+                            op.id("list_sessions")
+                                .summary("List Sessions")
+                                .description(
+                                    "Lists the active login sessions of the user with their device, IP address and last activity, including impersonation sessions.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/sessions/revoke-others", post_with(revoke_other_sessions, |op| {
+                            op.id("revoke_other_sessions")
+                                .summary("Revoke Other Sessions")
+                                .description("Signs out every session except the current one.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/sessions/{id}", delete_with(revoke_session, |op| {
+                            op.id("revoke_session")
+                                .summary("Revoke Session")
+                                .description("Signs out the specified session.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/webhooks/{id}/test", post_with(test_webhook, |op| {
+                            op.id("test_webhook")
+                                .summary("Test Webhook")
+                                .description("Queues a signed ping event for the specified webhook.")
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/projects/{id}/rename", post_with(rename_project, |op| {
+                            op.id("rename_project")
+                                .summary("Rename Project")
+                                .description(
+                                    "Starts a background job that renames the project and rewrites the name on its heartbeats.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/projects/{id}/merge", post_with(merge_project, |op| {
+                            op.id("merge_project")
+                                .summary("Merge Project")
+                                .description(
+                                    "Starts a background job that moves all of the project's heartbeats into the target project and deletes it.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
+                        .api_route("/dependencies", get_with(dependency_stats, |op| {
+                            op.id("dependency_stats")
+                                .summary("Dependency Usage Stats")
+                                .description(
+                                    "Retrieves the most used dependencies weighted by coded time, overall and per project and language.",
+                                )
+                                .tag("Data")
+                                .security_requirement("Authenticated")
+                        }))
                         .api_route("/settings", put_with(update_settings, |op| {
                             op.id("update_settings")
                                 .summary("Update User Settings")
@@ -215,51 +501,81 @@ pub fn create_app_router(
                     middleware::require_auth,
                 )),
         )
-        // admin routes
+        // admin routes, each gated by the permission it needs
         .merge(
             ApiRouter::new()
                 .api_route("/page/admin", get_with(admin_dashboard, |op| {
                     op.id("admin_dashboard")
                         .summary("Admin Dashboard Page")
-                        .description("Data for the admin page.")
+                        .description("Data for the admin page. Users can be searched by name or GitHub id, filtered by ban, admin, import and recent activity status, and sorted by role, join date or total time.")
                         .tag("Pages")
                         .security_requirement("Authenticated")
                 }))
-                .nest(
-                    "/admin",
-                    ApiRouter::new()
-                        .api_route("/impersonate/{user_id}", get_with(impersonate_user, |op| {
-                            op.id("impersonate_user")
-                                .summary("Impersonate User")
-                                .description(
-                                    "Allows an admin to impersonate another user.",
-                                )
-                                .tag("Admin")
-                                .security_requirement("Authenticated")
-                        }))
-                        .api_route(
-                            "/admin_level/{user_id}/{admin_level}",
-                             put_with(change_user_admin_level, |op| {
-                                op.id("change_user_admin_level")
-                                    .summary("Change User Admin Level")
-                                    .description(
-                                        "Allows an admin to change another user's admin level.",
-                                    )
-                                    .tag("Admin")
-                                    .security_requirement("Authenticated")
-                            }
-                        ))
-                        .layer(axum_middleware::from_fn_with_state(
-                            app_state.clone(),
-                            middleware::require_admin,
-                        )),
+                .api_route("/page/admin/users/{user_id}", get_with(admin_user_detail, |op| {
+                    op.id("admin_user_detail")
+                        .summary("Admin User Detail Page")
+                        .description("Heartbeat counts, recent activity, sessions, import jobs, projects and source types of a single user.")
+                        .tag("Pages")
+                        .security_requirement("Authenticated")
+                }))
+                .layer(axum_middleware::from_fn_with_state(
+                    (app_state.clone(), Permission::ViewUsers),
+                    middleware::require_permission,
+                )),
+        )
+        .merge(
+            ApiRouter::new()
+                .api_route(
+                    "/admin/role/{user_id}/{role}",
+                    put_with(change_user_role, |op| {
+                        op.id("change_user_role")
+                            .summary("Change User Role")
+                            .description(
+                                "Assigns a role to another user. Both the user's current role and the new one have to rank below your own.",
+                            )
+                            .tag("Admin")
+                            .security_requirement("Authenticated")
+                    }),
                 )
+                .layer(axum_middleware::from_fn_with_state(
+                    (app_state.clone(), Permission::ManageRoles),
+                    middleware::require_permission,
+                )),
+        )
+        .merge(
+            ApiRouter::new()
+                .api_route(
+                    "/admin/ban/{user_id}/{banned}",
+                    put_with(set_user_banned, |op| {
+                        op.id("set_user_banned")
+                            .summary("Ban User")
+                            .description("Bans or unbans a user ranked below you.")
+                            .tag("Admin")
+                            .security_requirement("Authenticated")
+                    }),
+                )
+                .layer(axum_middleware::from_fn_with_state(
+                    (app_state.clone(), Permission::Ban),
+                    middleware::require_permission,
+                )),
+        )
+        // impersonation has to stay writable while impersonating to be able to end it
+        .merge(
+            ApiRouter::new()
+                .api_route("/admin/impersonate/{user_id}", post_with(impersonate_user, |op| {
+                    op.id("impersonate_user")
+                        .summary("Impersonate User")
+                        .description(
+                            "Allows an admin to impersonate another user, read-only unless `write=true` is passed with the `impersonate_write` permission. Impersonating yourself ends impersonation.",
+                        )
+                        .tag("Admin")
+                        .security_requirement("Authenticated")
+                }))
                 .layer(axum_middleware::from_fn_with_state(
                     app_state.clone(),
-                    middleware::require_admin,
+                    middleware::require_impersonate,
                 )),
         )
-        // owner routes
         .merge(
             ApiRouter::new()
                 .api_route("/page/imports", get_with(admin_imports, |op| {
@@ -270,8 +586,22 @@ pub fn create_app_router(
                         .security_requirement("Authenticated")
                 }))
                 .layer(axum_middleware::from_fn_with_state(
-                    app_state.clone(),
-                    middleware::require_owner,
+                    (app_state.clone(), Permission::ManageImports),
+                    middleware::require_permission,
+                )),
+        )
+        .merge(
+            ApiRouter::new()
+                .api_route("/page/audit_log", get_with(audit_log, |op| {
+                    op.id("audit_log")
+                        .summary("Audit Log Page")
+                        .description("Paginated admin audit log, filterable by action, actor, effective user and target.")
+                        .tag("Pages")
+                        .security_requirement("Authenticated")
+                }))
+                .layer(axum_middleware::from_fn_with_state(
+                    (app_state.clone(), Permission::ViewAuditLog),
+                    middleware::require_permission,
                 )),
         )
         // API routes
@@ -323,6 +653,18 @@ pub fn create_app_router(
                                         .tag("WakaTime Compatibility")
                                         .security_requirement("ApiKey")
                                 }),
+                            )
+                            .api_route(
+                                "/goals",
+                                get_with(get_goals, |op| {
+                                    op.id("goals")
+                                        .summary("Goals")
+                                        .description(
+                                            "Returns the user's goals with their progress over the last periods.",
+                                        )
+                                        .tag("WakaTime Compatibility")
+                                        .security_requirement("ApiKey")
+                                }),
                             ),
                     ),
                 ),
@@ -356,6 +698,8 @@ pub fn create_app_router(
         .fallback(not_found)
         // inject application state
         .with_state(app_state.clone())
+        // block cross-site requests riding on the session cookie
+        .layer(axum_middleware::from_fn(middleware::csrf_protection))
         // add metrics tracking middleware
         .layer(axum_middleware::from_fn_with_state(
             app_state,
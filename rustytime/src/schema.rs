@@ -11,6 +11,35 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    audit_log (id) {
+        id -> Int8,
+        actor_id -> Int4,
+        effective_user_id -> Int4,
+        #[max_length = 64]
+        action -> Varchar,
+        #[max_length = 255]
+        target -> Nullable<Varchar>,
+        metadata -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    goals (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 10]
+        period -> Varchar,
+        target_seconds -> Int4,
+        project -> Nullable<Text>,
+        language -> Nullable<Text>,
+        ignored_weekdays -> Int2,
+        created_at -> Timestamptz,
+        last_reached_on -> Nullable<Date>,
+    }
+}
+
 diesel::table! {
     heartbeats (user_id, time) {
         id -> Int8,
@@ -39,6 +68,10 @@ diesel::table! {
         cursorpos -> Nullable<Int4>,
         source_type -> Nullable<Int2>,
         project_id -> Nullable<Int4>,
+        plugin -> Nullable<Text>,
+        plugin_version -> Nullable<Text>,
+        ai_line_changes -> Nullable<Int4>,
+        human_line_changes -> Nullable<Int4>,
     }
 }
 
@@ -59,6 +92,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    language_mappings (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 20]
+        kind -> Varchar,
+        source -> Text,
+        target -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     leaderboards (id) {
         id -> Int4,
@@ -70,6 +115,14 @@ diesel::table! {
         rank -> Int4,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        notified_rank -> Int4,
+    }
+}
+
+diesel::table! {
+    pending_language_normalizations (user_id) {
+        user_id -> Int4,
+        created_at -> Timestamptz,
     }
 }
 
@@ -92,6 +145,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    project_rules (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 20]
+        match_field -> Varchar,
+        pattern -> Text,
+        target_project -> Text,
+        priority -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     projects (id) {
         id -> Int4,
@@ -102,6 +168,17 @@ diesel::table! {
         updated_at -> Nullable<Timestamptz>,
         hidden -> Bool,
         project_url -> Nullable<Text>,
+        entity_privacy -> Nullable<Int2>,
+    }
+}
+
+diesel::table! {
+    roles (name) {
+        #[max_length = 50]
+        name -> Varchar,
+        rank -> Int2,
+        permissions -> Array<Text>,
+        created_at -> Timestamptz,
     }
 }
 
@@ -109,48 +186,136 @@ diesel::table! {
     sessions (id) {
         id -> Uuid,
         user_id -> Int4,
-        github_user_id -> Int8,
-        github_access_token -> Text,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         expires_at -> Timestamptz,
         impersonated_by -> Nullable<Int4>,
+        #[max_length = 255]
+        device -> Nullable<Varchar>,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
+        last_seen_at -> Timestamptz,
+        impersonation_write -> Bool,
+    }
+}
+
+diesel::table! {
+    user_daily_totals (user_id, day) {
+        user_id -> Int4,
+        day -> Date,
+        total_seconds -> Int8,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    user_identities (id) {
+        id -> Int4,
+        user_id -> Int4,
+        #[max_length = 50]
+        provider -> Varchar,
+        #[max_length = 255]
+        subject -> Varchar,
+        #[max_length = 100]
+        username -> Varchar,
+        #[max_length = 255]
+        email -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        #[max_length = 255]
+        password_hash -> Nullable<Varchar>,
     }
 }
 
 diesel::table! {
     users (id) {
         id -> Int4,
-        github_id -> Int8,
+        github_id -> Nullable<Int8>,
         #[max_length = 100]
         name -> Varchar,
         #[max_length = 200]
         avatar_url -> Varchar,
         api_key -> Uuid,
-        admin_level -> Int2,
         is_banned -> Bool,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
         #[max_length = 50]
         timezone -> Varchar,
+        public_line_stats -> Bool,
+        entity_privacy -> Int2,
+        entity_salt -> Uuid,
+        private_profile -> Bool,
+        #[max_length = 255]
+        email -> Nullable<Varchar>,
+        weekly_digest -> Bool,
+        last_digest_sent_on -> Nullable<Date>,
+        #[max_length = 50]
+        role -> Varchar,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Int8,
+        webhook_id -> Int4,
+        #[max_length = 50]
+        event -> Varchar,
+        attempt -> Int4,
+        status_code -> Nullable<Int4>,
+        success -> Bool,
+        error -> Nullable<Text>,
+        duration_ms -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    webhooks (id) {
+        id -> Int4,
+        user_id -> Int4,
+        url -> Text,
+        secret -> Text,
+        events -> Array<Text>,
+        global -> Bool,
+        enabled -> Bool,
+        created_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(goals -> users (user_id));
 diesel::joinable!(heartbeats -> users (user_id));
 diesel::joinable!(import_jobs -> users (user_id));
+diesel::joinable!(language_mappings -> users (user_id));
 diesel::joinable!(leaderboards -> users (user_id));
+diesel::joinable!(pending_language_normalizations -> users (user_id));
 diesel::joinable!(project_alias_resolutions -> users (user_id));
 diesel::joinable!(project_aliases -> users (user_id));
+diesel::joinable!(project_rules -> users (user_id));
 diesel::joinable!(projects -> users (user_id));
+diesel::joinable!(user_daily_totals -> users (user_id));
+diesel::joinable!(user_identities -> users (user_id));
+diesel::joinable!(users -> roles (role));
+diesel::joinable!(webhook_deliveries -> webhooks (webhook_id));
+diesel::joinable!(webhooks -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     _sqlx_migrations,
+    audit_log,
+    goals,
     heartbeats,
     import_jobs,
+    language_mappings,
     leaderboards,
+    pending_language_normalizations,
     project_alias_resolutions,
     project_aliases,
+    project_rules,
     projects,
+    roles,
     sessions,
+    user_daily_totals,
+    user_identities,
     users,
+    webhook_deliveries,
+    webhooks,
 );
@@ -1,8 +1,11 @@
 use crate::db::connection::DbPool;
+use crate::jobs::history::HistoryStore;
 use crate::jobs::import::ImportStore;
+use crate::jobs::webhooks::WebhookStore;
 use crate::utils::cache::AppCache;
+use crate::utils::local_auth::LocalAuth;
 use crate::utils::metrics::MetricsTracker;
-use oauth2::{EndpointNotSet, EndpointSet, basic::BasicClient};
+use crate::utils::oauth::AuthProviders;
 use reqwest::Client;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -11,31 +14,27 @@ use tokio::sync::RwLock;
 #[derive(Clone)]
 pub struct AppState {
     pub db_pool: DbPool,
-    pub github_client:
-        BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>,
+    pub auth_providers: AuthProviders,
+    pub local_auth: LocalAuth,
     pub http_client: reqwest::Client,
     pub metrics: MetricsTracker,
     pub import_store: Arc<RwLock<Option<ImportStore>>>,
+    pub history_store: Arc<RwLock<Option<HistoryStore>>>,
+    pub webhook_store: Arc<RwLock<Option<WebhookStore>>>,
     pub cache: AppCache,
 }
 
 impl AppState {
-    pub fn new(
-        db_pool: DbPool,
-        github_client: BasicClient<
-            EndpointSet,
-            EndpointNotSet,
-            EndpointNotSet,
-            EndpointNotSet,
-            EndpointSet,
-        >,
-    ) -> Self {
+    pub fn new(db_pool: DbPool, auth_providers: AuthProviders, local_auth: LocalAuth) -> Self {
         Self {
             db_pool,
-            github_client,
+            auth_providers,
+            local_auth,
             http_client: Client::new(),
             metrics: MetricsTracker::new(),
             import_store: Arc::new(RwLock::new(None)),
+            history_store: Arc::new(RwLock::new(None)),
+            webhook_store: Arc::new(RwLock::new(None)),
             cache: AppCache::new(),
         }
     }
@@ -47,4 +46,20 @@ impl AppState {
             *guard = Some(store);
         });
     }
+
+    pub fn set_history_store(&self, store: HistoryStore) {
+        let history_store = self.history_store.clone();
+        tokio::spawn(async move {
+            let mut guard = history_store.write().await;
+            *guard = Some(store);
+        });
+    }
+
+    pub fn set_webhook_store(&self, store: WebhookStore) {
+        let webhook_store = self.webhook_store.clone();
+        tokio::spawn(async move {
+            let mut guard = webhook_store.write().await;
+            *guard = Some(store);
+        });
+    }
 }
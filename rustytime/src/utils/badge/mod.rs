@@ -0,0 +1,88 @@
+/// Default color of the message side of a badge
+pub const DEFAULT_COLOR: &str = "#007ec6";
+/// Color used for badges of missing or private data
+pub const INACTIVE_COLOR: &str = "#9f9f9f";
+
+const MAX_TEXT_LENGTH: usize = 50;
+
+const NAMED_COLORS: &[(&str, &str)] = &[
+    ("brightgreen", "#4c1"),
+    ("green", "#97ca00"),
+    ("yellow", "#dfb317"),
+    ("orange", "#fe7d37"),
+    ("red", "#e05d44"),
+    ("blue", "#007ec6"),
+    ("lightgrey", "#9f9f9f"),
+    ("grey", "#555"),
+    ("purple", "#8839ef"),
+    ("pink", "#ea76cb"),
+];
+
+/// Resolve a named or hex color (without the leading `#`) for use in a badge
+pub fn parse_color(color: &str) -> Option<String> {
+    let color = color.trim().trim_start_matches('#').to_lowercase();
+
+    if let Some((_, hex)) = NAMED_COLORS.iter().find(|(name, _)| *name == color) {
+        return Some(hex.to_string());
+    }
+
+    let is_hex = matches!(color.len(), 3 | 6) && color.chars().all(|c| c.is_ascii_hexdigit());
+    is_hex.then(|| format!("#{color}"))
+}
+
+/// Approximate width of a character in 11px Verdana
+fn char_width(c: char) -> f32 {
+    match c {
+        'i' | 'j' | 'l' | '|' | '!' | '.' | ',' | ':' | ';' | '\'' => 3.5,
+        'f' | 'r' | 't' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' | '/' => 5.0,
+        'm' | 'w' | 'M' | 'W' | '%' => 10.5,
+        c if c.is_ascii_uppercase() => 7.5,
+        c if c.is_ascii_digit() => 7.0,
+        c if c.is_ascii() => 6.5,
+        _ => 8.0,
+    }
+}
+
+pub fn text_width(text: &str) -> u32 {
+    text.chars().map(char_width).sum::<f32>().ceil() as u32
+}
+
+/// Escape text for use in an SVG, truncating it to the maximum badge text length
+pub fn escape_xml(text: &str) -> String {
+    text.chars()
+        .take(MAX_TEXT_LENGTH)
+        .fold(String::with_capacity(text.len()), |mut out, c| {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&apos;"),
+                _ => out.push(c),
+            }
+            out
+        })
+}
+
+/// Render a flat, shields.io style badge
+pub fn render_badge(label: &str, message: &str, color: &str) -> String {
+    let label: String = label.chars().take(MAX_TEXT_LENGTH).collect();
+    let message: String = message.chars().take(MAX_TEXT_LENGTH).collect();
+
+    let label_width = text_width(&label) + 10;
+    let message_width = text_width(&message) + 10;
+    let width = label_width + message_width;
+    let label_x = label_width as f32 / 2.0;
+    let message_x = label_width as f32 + message_width as f32 / 2.0;
+
+    let label = escape_xml(&label);
+    let message = escape_xml(&message);
+    let color = escape_xml(color);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,54 @@
+use super::*;
+
+// ==================== parse_color tests ====================
+
+#[test]
+fn parse_color_accepts_named_colors() {
+    assert_eq!(parse_color("green"), Some("#97ca00".to_string()));
+    assert_eq!(parse_color("Blue"), Some("#007ec6".to_string()));
+}
+
+#[test]
+fn parse_color_accepts_hex_colors() {
+    assert_eq!(parse_color("ff0000"), Some("#ff0000".to_string()));
+    assert_eq!(parse_color("#ABC"), Some("#abc".to_string()));
+}
+
+#[test]
+fn parse_color_rejects_invalid_colors() {
+    assert_eq!(parse_color("notacolor"), None);
+    assert_eq!(parse_color("12345"), None);
+    assert_eq!(parse_color("\"/><script>"), None);
+}
+
+// ==================== render_badge tests ====================
+
+#[test]
+fn text_width_grows_with_text() {
+    assert!(text_width("rustytime") > text_width("rust"));
+    assert_eq!(text_width(""), 0);
+}
+
+#[test]
+fn render_badge_contains_label_and_message() {
+    let svg = render_badge("coding time", "12h 30m", DEFAULT_COLOR);
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(">coding time</text>"));
+    assert!(svg.contains(">12h 30m</text>"));
+    assert!(svg.contains(r##"fill="#007ec6""##));
+}
+
+#[test]
+fn render_badge_escapes_text() {
+    let svg = render_badge("<b>", "a & \"b\"", DEFAULT_COLOR);
+    assert!(svg.contains("&lt;b&gt;"));
+    assert!(svg.contains("a &amp; &quot;b&quot;"));
+    assert!(!svg.contains("<b>"));
+}
+
+#[test]
+fn render_badge_truncates_long_text() {
+    let long = "a".repeat(200);
+    let svg = render_badge(&long, "1h", DEFAULT_COLOR);
+    assert!(!svg.contains(&"a".repeat(MAX_TEXT_LENGTH + 1)));
+}
@@ -1,13 +1,19 @@
 use chrono::NaiveDate;
 use moka::sync::Cache;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::handlers::page::profile::UserProfile;
 use crate::handlers::page::projects::Project;
-use crate::models::heartbeat::{DailyActivity, DashboardStats, TimeRange};
+use crate::models::goal::GoalProgress;
+use crate::models::heartbeat::{
+    DailyActivity, DashboardStats, EntityPrivacy, IngestSettings, ProjectStats, TimeRange,
+    UsageStat,
+};
 use crate::models::leaderboard::Leaderboard;
 use crate::models::user::User;
+use crate::utils::card::CardData;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct DashboardCacheKey {
@@ -19,9 +25,18 @@ pub struct DashboardCacheKey {
 #[derive(Clone)]
 pub struct CachedDashboardStats {
     pub stats: DashboardStats,
+    pub dependencies: Vec<UsageStat>,
     pub heartbeat_count: i64,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProjectDetailCacheKey {
+    pub user_id: i32,
+    pub project_id: i32,
+    pub range: TimeRange,
+    pub timezone: String,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct LeaderboardCacheKey {
     pub period_type: String,
@@ -37,6 +52,8 @@ pub struct CachedLeaderboard {
 #[derive(Clone)]
 pub struct CachedAdminStats {
     pub daily_activity: Vec<DailyActivity>,
+    /// All time tracked seconds by user id
+    pub user_totals: Arc<HashMap<i32, i64>>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -50,13 +67,36 @@ pub struct ProjectsCacheKey {
     pub user_id: i32,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BadgeCacheKey {
+    /// Lowercased username
+    pub username: String,
+    /// Badge kind, including the project name for project badges
+    pub kind: String,
+    pub range: TimeRange,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CardCacheKey {
+    /// Lowercased username
+    pub username: String,
+    pub range: TimeRange,
+}
+
 #[derive(Clone)]
 pub struct AppCache {
     pub dashboard: Arc<Cache<DashboardCacheKey, CachedDashboardStats>>,
     pub projects: Arc<Cache<ProjectsCacheKey, Vec<Project>>>,
+    pub project_detail: Arc<Cache<ProjectDetailCacheKey, ProjectStats>>,
     pub leaderboard: Arc<Cache<LeaderboardCacheKey, CachedLeaderboard>>,
     pub admin: Arc<Cache<(), CachedAdminStats>>,
     pub profile: Arc<Cache<String, UserProfile>>,
+    pub ingest_settings: Arc<Cache<i32, IngestSettings>>,
+    pub goals: Arc<Cache<i32, Vec<GoalProgress>>>,
+    /// Badge messages, `None` when the data is missing or private
+    pub badges: Arc<Cache<BadgeCacheKey, Option<String>>>,
+    /// Stats card data, `None` when the user is missing or private
+    pub cards: Arc<Cache<CardCacheKey, Option<CardData>>>,
 }
 
 impl AppCache {
@@ -75,6 +115,13 @@ impl AppCache {
                     .time_to_live(Duration::from_secs(300)) // 5 minute TTL
                     .build(),
             ),
+            project_detail: Arc::new(
+                Cache::builder()
+                    .max_capacity(1_000)
+                    .time_to_live(Duration::from_secs(300)) // 5 minute TTL
+                    .support_invalidation_closures()
+                    .build(),
+            ),
             leaderboard: Arc::new(
                 Cache::builder()
                     .max_capacity(100)
@@ -91,6 +138,33 @@ impl AppCache {
                 Cache::builder()
                     .max_capacity(100)
                     .time_to_live(Duration::from_secs(300)) // 5 minute TTL
+                    .support_invalidation_closures()
+                    .build(),
+            ),
+            ingest_settings: Arc::new(
+                Cache::builder()
+                    .max_capacity(1_000)
+                    .time_to_live(Duration::from_secs(300)) // 5 minute TTL
+                    .build(),
+            ),
+            goals: Arc::new(
+                Cache::builder()
+                    .max_capacity(1_000)
+                    .time_to_live(Duration::from_secs(60)) // 1 minute TTL
+                    .build(),
+            ),
+            badges: Arc::new(
+                Cache::builder()
+                    .max_capacity(10_000)
+                    .time_to_live(Duration::from_secs(300)) // 5 minute TTL
+                    .support_invalidation_closures()
+                    .build(),
+            ),
+            cards: Arc::new(
+                Cache::builder()
+                    .max_capacity(1_000)
+                    .time_to_live(Duration::from_secs(300)) // 5 minute TTL
+                    .support_invalidation_closures()
                     .build(),
             ),
         }
@@ -100,6 +174,36 @@ impl AppCache {
         let _ = self
             .dashboard
             .invalidate_entries_if(move |key, _| key.user_id == user_id);
+        let _ = self
+            .project_detail
+            .invalidate_entries_if(move |key, _| key.user_id == user_id);
+    }
+
+    pub fn invalidate_user_profile(&self, username: &str) {
+        // profiles are cached under the username as requested, in any casing
+        let username = username.to_lowercase();
+        let _ = self
+            .profile
+            .invalidate_entries_if(move |key, _| key.to_lowercase() == username);
+    }
+
+    pub fn invalidate_user_badges(&self, username: &str) {
+        let username = username.to_lowercase();
+        let _ = self.badges.invalidate_entries_if({
+            let username = username.clone();
+            move |key, _| key.username == username
+        });
+        let _ = self
+            .cards
+            .invalidate_entries_if(move |key, _| key.username == username);
+    }
+
+    pub fn invalidate_ingest_settings(&self, user_id: i32) {
+        self.ingest_settings.invalidate(&user_id);
+    }
+
+    pub fn invalidate_user_goals(&self, user_id: i32) {
+        self.goals.invalidate(&user_id);
     }
 
     pub fn invalidate_user_projects(&self, user_id: i32) {
@@ -112,6 +216,7 @@ impl AppCache {
         project_id: i32,
         hidden: Option<bool>,
         project_url: Option<Option<String>>,
+        entity_privacy: Option<Option<EntityPrivacy>>,
     ) {
         let cache_key = ProjectsCacheKey { user_id };
 
@@ -123,6 +228,9 @@ impl AppCache {
                 if let Some(url) = project_url {
                     project.project_url = url;
                 }
+                if let Some(mode) = entity_privacy {
+                    project.entity_privacy = mode;
+                }
             }
             self.projects.insert(cache_key, projects);
         }
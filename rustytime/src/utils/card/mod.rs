@@ -0,0 +1,308 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::utils::badge::escape_xml;
+use crate::utils::time::format_hour_minute;
+
+const MAX_NAME_LENGTH: usize = 24;
+const NORMAL_LANGUAGES: usize = 5;
+const COMPACT_LANGUAGES: usize = 6;
+const MAX_PROJECTS: usize = 5;
+
+const FONT_FAMILY: &str = "'Segoe UI', Ubuntu, 'Helvetica Neue', sans-serif";
+
+const LANGUAGE_COLORS: &[(&str, &str)] = &[
+    ("rust", "#dea584"),
+    ("typescript", "#3178c6"),
+    ("javascript", "#f1e05a"),
+    ("python", "#3572a5"),
+    ("go", "#00add8"),
+    ("java", "#b07219"),
+    ("kotlin", "#a97bff"),
+    ("c", "#555555"),
+    ("c++", "#f34b7d"),
+    ("c#", "#178600"),
+    ("ruby", "#701516"),
+    ("php", "#4f5d95"),
+    ("swift", "#f05138"),
+    ("svelte", "#ff3e00"),
+    ("vue", "#41b883"),
+    ("html", "#e34c26"),
+    ("css", "#663399"),
+    ("scss", "#c6538c"),
+    ("markdown", "#083fa1"),
+    ("json", "#292929"),
+    ("yaml", "#cb171e"),
+    ("toml", "#9c4221"),
+    ("bash", "#89e051"),
+    ("lua", "#000080"),
+    ("nix", "#7e7eff"),
+    ("zig", "#ec915c"),
+    ("dart", "#00b4ab"),
+    ("elixir", "#6e4a7e"),
+    ("haskell", "#5e5086"),
+    ("sql", "#e38c00"),
+];
+
+/// Colors for languages without a known color, picked by name
+const FALLBACK_COLORS: &[&str] = &[
+    "#8aadf4", "#a6da95", "#eed49f", "#f5a97f", "#c6a0f6", "#91d7e3", "#f0c6c6", "#ee99a0",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CardTheme {
+    Light,
+    Dark,
+    #[default]
+    Mocha,
+    Latte,
+}
+
+struct ThemeColors {
+    background: &'static str,
+    border: &'static str,
+    title: &'static str,
+    text: &'static str,
+    muted: &'static str,
+    track: &'static str,
+}
+
+impl CardTheme {
+    fn colors(self) -> ThemeColors {
+        match self {
+            CardTheme::Light => ThemeColors {
+                background: "#fffefe",
+                border: "#e4e2e2",
+                title: "#2f80ed",
+                text: "#434d58",
+                muted: "#6a737d",
+                track: "#e1e4e8",
+            },
+            CardTheme::Dark => ThemeColors {
+                background: "#151515",
+                border: "#2d2d2d",
+                title: "#ffffff",
+                text: "#c9d1d9",
+                muted: "#8b949e",
+                track: "#30363d",
+            },
+            CardTheme::Mocha => ThemeColors {
+                background: "#1e1e2e",
+                border: "#313244",
+                title: "#cba6f7",
+                text: "#cdd6f4",
+                muted: "#a6adc8",
+                track: "#313244",
+            },
+            CardTheme::Latte => ThemeColors {
+                background: "#eff1f5",
+                border: "#ccd0da",
+                title: "#8839ef",
+                text: "#4c4f69",
+                muted: "#6c6f85",
+                track: "#ccd0da",
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CardLayout {
+    #[default]
+    Normal,
+    Compact,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardStat {
+    pub name: String,
+    pub total_seconds: i64,
+}
+
+/// Data shown on a stats card
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardData {
+    pub username: String,
+    pub total_seconds: i64,
+    pub languages: Vec<CardStat>,
+    pub projects: Vec<CardStat>,
+}
+
+pub struct CardOptions<'a> {
+    pub range_label: &'a str,
+    pub theme: CardTheme,
+    pub layout: CardLayout,
+    /// Lowercased names of languages to leave out
+    pub hide: &'a [String],
+    pub show_projects: bool,
+}
+
+pub fn language_color(name: &str) -> &'static str {
+    let lower = name.to_lowercase();
+    LANGUAGE_COLORS
+        .iter()
+        .find(|(language, _)| *language == lower)
+        .map(|(_, color)| *color)
+        .unwrap_or_else(|| {
+            let hash = lower.bytes().fold(0usize, |acc, b| {
+                acc.wrapping_mul(31).wrapping_add(b as usize)
+            });
+            FALLBACK_COLORS[hash % FALLBACK_COLORS.len()]
+        })
+}
+
+fn truncate_name(name: &str) -> String {
+    if name.chars().count() > MAX_NAME_LENGTH {
+        let truncated: String = name.chars().take(MAX_NAME_LENGTH - 1).collect();
+        format!("{truncated}…")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Visible languages with their share of the visible time, in percent
+pub fn language_shares(
+    languages: &[CardStat],
+    hide: &[String],
+    limit: usize,
+) -> Vec<(String, f64)> {
+    let visible: Vec<&CardStat> = languages
+        .iter()
+        .filter(|l| l.total_seconds > 0 && !hide.contains(&l.name.to_lowercase()))
+        .take(limit)
+        .collect();
+    let total: i64 = visible.iter().map(|l| l.total_seconds).sum();
+
+    visible
+        .into_iter()
+        .map(|l| {
+            let share = if total > 0 {
+                l.total_seconds as f64 / total as f64 * 100.0
+            } else {
+                0.0
+            };
+            (l.name.clone(), (share * 10.0).round() / 10.0)
+        })
+        .collect()
+}
+
+/// Render a stats card with the top languages and optionally the top projects
+pub fn render_card(data: &CardData, options: &CardOptions) -> String {
+    let colors = options.theme.colors();
+    let (width, language_limit) = match options.layout {
+        CardLayout::Normal => (350, NORMAL_LANGUAGES),
+        CardLayout::Compact => (300, COMPACT_LANGUAGES),
+    };
+    let inner_width = width - 50;
+    let languages = language_shares(&data.languages, options.hide, language_limit);
+
+    let mut body = String::new();
+    let mut y = 85;
+
+    if languages.is_empty() {
+        body.push_str(&format!(
+            r#"<text x="25" y="{y}" class="muted">No activity yet</text>"#
+        ));
+        y += 25;
+    } else {
+        match options.layout {
+            CardLayout::Normal => {
+                for (name, share) in &languages {
+                    let bar = (inner_width as f64 * share / 100.0).max(2.0);
+                    body.push_str(&format!(
+                        r#"<text x="25" y="{y}" class="text">{name}</text><text x="{right}" y="{y}" class="muted" text-anchor="end">{share:.1}%</text><rect x="25" y="{bar_y}" width="{inner_width}" height="6" rx="3" fill="{track}"/><rect x="25" y="{bar_y}" width="{bar:.1}" height="6" rx="3" fill="{color}"/>"#,
+                        name = escape_xml(&truncate_name(name)),
+                        right = width - 25,
+                        bar_y = y + 8,
+                        track = colors.track,
+                        color = language_color(name),
+                    ));
+                    y += 36;
+                }
+            }
+            CardLayout::Compact => {
+                let bar_y = y - 15;
+                body.push_str(&format!(
+                    r#"<clipPath id="bar"><rect x="25" y="{bar_y}" width="{inner_width}" height="8" rx="4"/></clipPath><g clip-path="url(#bar)"><rect x="25" y="{bar_y}" width="{inner_width}" height="8" fill="{track}"/>"#,
+                    track = colors.track,
+                ));
+                let mut x = 25.0;
+                for (name, share) in &languages {
+                    let segment = inner_width as f64 * share / 100.0;
+                    body.push_str(&format!(
+                        r#"<rect x="{x:.1}" y="{bar_y}" width="{segment:.1}" height="8" fill="{color}"/>"#,
+                        color = language_color(name),
+                    ));
+                    x += segment;
+                }
+                body.push_str("</g>");
+
+                y += 15;
+                for (index, (name, share)) in languages.iter().enumerate() {
+                    let column_x = if index % 2 == 0 {
+                        25
+                    } else {
+                        25 + inner_width / 2
+                    };
+                    let row_y = y + (index / 2) as i32 * 22;
+                    body.push_str(&format!(
+                        r#"<circle cx="{cx}" cy="{cy}" r="5" fill="{color}"/><text x="{tx}" y="{row_y}" class="text">{name} <tspan class="muted">{share:.1}%</tspan></text>"#,
+                        cx = column_x + 5,
+                        cy = row_y - 4,
+                        tx = column_x + 15,
+                        color = language_color(name),
+                        name = escape_xml(&truncate_name(name)),
+                    ));
+                }
+                y += languages.len().div_ceil(2) as i32 * 22;
+            }
+        }
+    }
+
+    let projects: Vec<&CardStat> = data
+        .projects
+        .iter()
+        .filter(|p| p.total_seconds > 0)
+        .take(MAX_PROJECTS)
+        .collect();
+    if options.show_projects && !projects.is_empty() {
+        y += 5;
+        body.push_str(&format!(
+            r#"<text x="25" y="{y}" class="heading">Top projects</text>"#
+        ));
+        y += 25;
+        for project in projects {
+            body.push_str(&format!(
+                r#"<text x="25" y="{y}" class="text">{name}</text><text x="{right}" y="{y}" class="muted" text-anchor="end">{time}</text>"#,
+                name = escape_xml(&truncate_name(&project.name)),
+                right = width - 25,
+                time = escape_xml(&format_hour_minute(project.total_seconds)),
+            ));
+            y += 22;
+        }
+    }
+
+    let height = y + 5;
+    let title = escape_xml(&format!("{}'s coding stats", truncate_name(&data.username)));
+    let subtitle = escape_xml(&format!(
+        "{}: {}",
+        options.range_label,
+        format_hour_minute(data.total_seconds)
+    ));
+
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" role="img" aria-label="{title}"><title>{title}</title><style>text{{font-family:{FONT_FAMILY};font-size:13px}}.title{{font-size:18px;font-weight:600;fill:{title_color}}}.heading{{font-size:14px;font-weight:600;fill:{title_color}}}.text{{fill:{text}}}.muted{{fill:{muted}}}</style><rect x="0.5" y="0.5" width="{rect_width}" height="{rect_height}" rx="6" fill="{background}" stroke="{border}"/><text x="25" y="35" class="title">{title}</text><text x="25" y="57" class="muted">{subtitle}</text>{body}</svg>"#,
+        rect_width = width - 1,
+        rect_height = height - 1,
+        title_color = colors.title,
+        text = colors.text,
+        muted = colors.muted,
+        background = colors.background,
+        border = colors.border,
+    )
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,129 @@
+use super::*;
+
+fn stat(name: &str, total_seconds: i64) -> CardStat {
+    CardStat {
+        name: name.to_string(),
+        total_seconds,
+    }
+}
+
+fn sample_data() -> CardData {
+    CardData {
+        username: "alice".to_string(),
+        total_seconds: 36_000,
+        languages: vec![
+            stat("Rust", 18_000),
+            stat("Python", 12_000),
+            stat("Go", 6_000),
+        ],
+        projects: vec![stat("rustytime", 30_000), stat("dotfiles", 6_000)],
+    }
+}
+
+fn options<'a>(hide: &'a [String], layout: CardLayout, show_projects: bool) -> CardOptions<'a> {
+    CardOptions {
+        range_label: "All time",
+        theme: CardTheme::default(),
+        layout,
+        hide,
+        show_projects,
+    }
+}
+
+// ==================== language_shares tests ====================
+
+#[test]
+fn language_shares_sum_to_one_hundred() {
+    let shares = language_shares(&sample_data().languages, &[], 5);
+    assert_eq!(shares.len(), 3);
+    assert_eq!(shares[0], ("Rust".to_string(), 50.0));
+    let total: f64 = shares.iter().map(|(_, share)| share).sum();
+    assert!((total - 100.0).abs() < 0.5);
+}
+
+#[test]
+fn language_shares_are_recomputed_after_hiding() {
+    let hide = vec!["rust".to_string()];
+    let shares = language_shares(&sample_data().languages, &hide, 5);
+    assert_eq!(
+        shares,
+        vec![("Python".to_string(), 66.7), ("Go".to_string(), 33.3)]
+    );
+}
+
+#[test]
+fn language_shares_respect_limit() {
+    let shares = language_shares(&sample_data().languages, &[], 2);
+    assert_eq!(shares.len(), 2);
+}
+
+// ==================== language_color tests ====================
+
+#[test]
+fn language_color_uses_known_colors_case_insensitively() {
+    assert_eq!(language_color("Rust"), "#dea584");
+    assert_eq!(language_color("rust"), "#dea584");
+}
+
+#[test]
+fn language_color_falls_back_deterministically() {
+    let color = language_color("SomeObscureLanguage");
+    assert!(FALLBACK_COLORS.contains(&color));
+    assert_eq!(color, language_color("SomeObscureLanguage"));
+}
+
+// ==================== render_card tests ====================
+
+#[test]
+fn render_card_shows_languages_and_total() {
+    let svg = render_card(&sample_data(), &options(&[], CardLayout::Normal, false));
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("alice&apos;s coding stats"));
+    assert!(svg.contains(">Rust</text>"));
+    assert!(svg.contains("50.0%"));
+    assert!(svg.contains("All time: 10h 0m"));
+    assert!(!svg.contains("Top projects"));
+}
+
+#[test]
+fn render_card_hides_languages() {
+    let hide = vec!["python".to_string()];
+    let svg = render_card(&sample_data(), &options(&hide, CardLayout::Normal, false));
+    assert!(!svg.contains("Python"));
+    assert!(svg.contains("75.0%"));
+}
+
+#[test]
+fn render_card_shows_projects_when_requested() {
+    let svg = render_card(&sample_data(), &options(&[], CardLayout::Normal, true));
+    assert!(svg.contains("Top projects"));
+    assert!(svg.contains(">rustytime</text>"));
+}
+
+#[test]
+fn render_card_compact_layout_is_narrower() {
+    let svg = render_card(&sample_data(), &options(&[], CardLayout::Compact, false));
+    assert!(svg.contains(r#"width="300""#));
+    assert!(svg.contains("<clipPath"));
+}
+
+#[test]
+fn render_card_without_activity() {
+    let data = CardData {
+        username: "bob".to_string(),
+        total_seconds: 0,
+        languages: vec![],
+        projects: vec![],
+    };
+    let svg = render_card(&data, &options(&[], CardLayout::Normal, true));
+    assert!(svg.contains("No activity yet"));
+}
+
+#[test]
+fn render_card_escapes_names() {
+    let mut data = sample_data();
+    data.languages = vec![stat("<script>", 100)];
+    let svg = render_card(&data, &options(&[], CardLayout::Normal, false));
+    assert!(svg.contains("&lt;script&gt;"));
+    assert!(!svg.contains("<script>"));
+}
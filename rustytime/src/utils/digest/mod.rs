@@ -0,0 +1,178 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::utils::time::format_hour_minute;
+
+pub const MAX_DIGEST_ITEMS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigestStat {
+    pub name: String,
+    pub total_seconds: i64,
+}
+
+/// Coding activity of one week compared with the week before it
+#[derive(Debug, Clone)]
+pub struct WeeklyDigest {
+    pub username: String,
+    /// First day of the reported week, in the user's timezone
+    pub week_start: NaiveDate,
+    pub total_seconds: i64,
+    pub previous_total_seconds: i64,
+    pub projects: Vec<DigestStat>,
+    pub languages: Vec<DigestStat>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedDigest {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut out, c| {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                '\'' => out.push_str("&#39;"),
+                _ => out.push(c),
+            }
+            out
+        })
+}
+
+impl WeeklyDigest {
+    /// Whether there is anything worth sending
+    pub fn has_activity(&self) -> bool {
+        self.total_seconds > 0 || self.previous_total_seconds > 0
+    }
+
+    /// Last day of the reported week
+    pub fn week_end(&self) -> NaiveDate {
+        self.week_start + Duration::days(6)
+    }
+
+    /// Change against the previous week in whole percent, none when the previous week was empty
+    pub fn change_percent(&self) -> Option<i64> {
+        if self.previous_total_seconds <= 0 {
+            return None;
+        }
+
+        let change = (self.total_seconds - self.previous_total_seconds) as f64
+            / self.previous_total_seconds as f64
+            * 100.0;
+        Some(change.round() as i64)
+    }
+
+    /// One line comparison with the previous week
+    pub fn comparison(&self) -> String {
+        match self.change_percent() {
+            None if self.total_seconds > 0 => "No coding time the week before".to_string(),
+            None => "No coding time in either week".to_string(),
+            Some(0) => format!(
+                "Same as the week before ({})",
+                format_hour_minute(self.previous_total_seconds)
+            ),
+            Some(change) => format!(
+                "{}{}% compared to the week before ({})",
+                if change > 0 { "+" } else { "" },
+                change,
+                format_hour_minute(self.previous_total_seconds)
+            ),
+        }
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "Your rustytime week: {} of coding",
+            format_hour_minute(self.total_seconds)
+        )
+    }
+
+    pub fn render(&self, site_url: &str) -> RenderedDigest {
+        RenderedDigest {
+            subject: self.subject(),
+            text: self.render_text(site_url),
+            html: self.render_html(site_url),
+        }
+    }
+
+    fn period(&self) -> String {
+        format!(
+            "{} to {}",
+            self.week_start.format("%b %-d"),
+            self.week_end().format("%b %-d, %Y")
+        )
+    }
+
+    pub fn render_text(&self, site_url: &str) -> String {
+        let mut out = format!(
+            "Hi {},\n\nHere is your coding summary for {}.\n\nTotal: {}\n{}\n",
+            self.username,
+            self.period(),
+            format_hour_minute(self.total_seconds),
+            self.comparison()
+        );
+
+        for (title, stats) in [
+            ("Top projects", &self.projects),
+            ("Top languages", &self.languages),
+        ] {
+            if stats.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{title}:\n"));
+            for stat in stats.iter().take(MAX_DIGEST_ITEMS) {
+                out.push_str(&format!(
+                    "  - {}: {}\n",
+                    stat.name,
+                    format_hour_minute(stat.total_seconds)
+                ));
+            }
+        }
+
+        out.push_str(&format!(
+            "\nSee more on your dashboard: {site_url}/dashboard\nYou can turn off these emails in your settings: {site_url}/settings\n"
+        ));
+        out
+    }
+
+    pub fn render_html(&self, site_url: &str) -> String {
+        let site_url = escape_html(site_url);
+        let mut sections = String::new();
+
+        for (title, stats) in [
+            ("Top projects", &self.projects),
+            ("Top languages", &self.languages),
+        ] {
+            if stats.is_empty() {
+                continue;
+            }
+            sections.push_str(&format!(
+                r#"<h3 style="margin:20px 0 8px">{title}</h3><table style="border-collapse:collapse;width:100%">"#
+            ));
+            for stat in stats.iter().take(MAX_DIGEST_ITEMS) {
+                sections.push_str(&format!(
+                    r#"<tr><td style="padding:4px 0">{}</td><td style="padding:4px 0;text-align:right">{}</td></tr>"#,
+                    escape_html(&stat.name),
+                    format_hour_minute(stat.total_seconds)
+                ));
+            }
+            sections.push_str("</table>");
+        }
+
+        format!(
+            r#"<!DOCTYPE html><html><body style="font-family:sans-serif;color:#1e1e2e;max-width:560px;margin:0 auto;padding:16px"><p>Hi {username},</p><p>Here is your coding summary for {period}.</p><p style="font-size:28px;font-weight:bold;margin:8px 0">{total}</p><p style="color:#5c5f77;margin:0">{comparison}</p>{sections}<p style="margin-top:24px"><a href="{site_url}/dashboard">Open your dashboard</a></p><p style="color:#8c8fa1;font-size:12px">You can turn off these emails in your <a href="{site_url}/settings">settings</a>.</p></body></html>"#,
+            username = escape_html(&self.username),
+            period = self.period(),
+            total = format_hour_minute(self.total_seconds),
+            comparison = escape_html(&self.comparison()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests;
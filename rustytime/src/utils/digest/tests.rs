@@ -0,0 +1,86 @@
+use super::*;
+
+fn stat(name: &str, total_seconds: i64) -> DigestStat {
+    DigestStat {
+        name: name.to_string(),
+        total_seconds,
+    }
+}
+
+fn sample_digest(total_seconds: i64, previous_total_seconds: i64) -> WeeklyDigest {
+    WeeklyDigest {
+        username: "alice".to_string(),
+        week_start: NaiveDate::from_ymd_opt(2026, 3, 16).unwrap(),
+        total_seconds,
+        previous_total_seconds,
+        projects: vec![stat("rustytime", 30_000), stat("<script>", 6_000)],
+        languages: vec![stat("Rust", 28_800), stat("Python", 7_200)],
+    }
+}
+
+// ==================== comparison tests ====================
+
+#[test]
+fn change_percent_compares_with_previous_week() {
+    assert_eq!(sample_digest(36_000, 24_000).change_percent(), Some(50));
+    assert_eq!(sample_digest(12_000, 24_000).change_percent(), Some(-50));
+    assert_eq!(sample_digest(36_000, 0).change_percent(), None);
+}
+
+#[test]
+fn comparison_describes_change() {
+    assert_eq!(
+        sample_digest(36_000, 24_000).comparison(),
+        "+50% compared to the week before (6h 40m)"
+    );
+    assert_eq!(
+        sample_digest(12_000, 24_000).comparison(),
+        "-50% compared to the week before (6h 40m)"
+    );
+    assert_eq!(
+        sample_digest(24_000, 24_000).comparison(),
+        "Same as the week before (6h 40m)"
+    );
+    assert_eq!(
+        sample_digest(3_600, 0).comparison(),
+        "No coding time the week before"
+    );
+}
+
+#[test]
+fn has_activity_requires_time_in_either_week() {
+    assert!(sample_digest(60, 0).has_activity());
+    assert!(sample_digest(0, 60).has_activity());
+    assert!(!sample_digest(0, 0).has_activity());
+}
+
+// ==================== render tests ====================
+
+#[test]
+fn render_text_lists_week_and_top_items() {
+    let text = sample_digest(36_000, 24_000).render_text("https://rustytime.example");
+    assert!(text.contains("Hi alice,"));
+    assert!(text.contains("Mar 16 to Mar 22, 2026"));
+    assert!(text.contains("Total: 10h 0m"));
+    assert!(text.contains("  - rustytime: 8h 20m"));
+    assert!(text.contains("  - Rust: 8h 0m"));
+    assert!(text.contains("https://rustytime.example/settings"));
+}
+
+#[test]
+fn render_html_escapes_names() {
+    let html = sample_digest(36_000, 24_000).render_html("https://rustytime.example");
+    assert!(html.contains("&lt;script&gt;"));
+    assert!(!html.contains("<script>"));
+}
+
+#[test]
+fn render_skips_empty_sections() {
+    let mut digest = sample_digest(36_000, 24_000);
+    digest.projects.clear();
+    let rendered = digest.render("https://rustytime.example");
+    assert!(!rendered.text.contains("Top projects"));
+    assert!(!rendered.html.contains("Top projects"));
+    assert!(rendered.text.contains("Top languages"));
+    assert_eq!(rendered.subject, "Your rustytime week: 10h 0m of coding");
+}
@@ -31,3 +31,34 @@ pub fn use_cloudflare_headers() -> bool {
             .unwrap_or(false)
     })
 }
+
+/// Whether webhooks may target local and private addresses, always allowed outside production
+pub fn allow_private_webhook_urls() -> bool {
+    if !is_production_env() {
+        return true;
+    }
+
+    std::env::var("WEBHOOK_ALLOW_PRIVATE_URLS")
+        .map(|value| {
+            matches!(
+                value.trim().to_ascii_lowercase().as_str(),
+                "true" | "1" | "yes" | "on"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Origins allowed to make cookie authenticated writes, the frontend and `CSRF_TRUSTED_ORIGINS`
+pub fn trusted_origins() -> &'static [String] {
+    static TRUSTED_ORIGINS: OnceCell<Vec<String>> = OnceCell::new();
+    TRUSTED_ORIGINS.get_or_init(|| {
+        let frontend_url =
+            std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+        let extra = std::env::var("CSRF_TRUSTED_ORIGINS").unwrap_or_default();
+
+        std::iter::once(frontend_url.as_str())
+            .chain(extra.split(','))
+            .filter_map(|value| crate::utils::http::url_origin(value.trim()))
+            .collect()
+    })
+}
@@ -1,14 +1,23 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
 use axum::Extension;
-use axum::extract::FromRequestParts;
-use axum::http::StatusCode;
+use axum::extract::{ConnectInfo, FromRequestParts};
 use axum::http::request::Parts;
+use axum::http::{StatusCode, header};
 
 use diesel::PgConnection;
 use diesel::r2d2::ConnectionManager;
 use diesel::r2d2::PooledConnection;
 
+use crate::models::role::Role;
 use crate::models::user::User;
 use crate::state::AppState;
+use crate::utils::env::use_cloudflare_headers;
+use crate::utils::http::{
+    describe_device, extract_client_ip_cloudflare, extract_client_ip_from_headers,
+};
+use crate::utils::session::SessionData;
 
 /// Custom extractor for authenticated users
 pub struct AuthenticatedUser(pub User);
@@ -30,6 +39,27 @@ where
     }
 }
 
+/// Extractor for the role the request acts with: the impersonating admin's role while
+/// impersonating, otherwise the authenticated user's
+pub struct CurrentRole(pub Role);
+
+impl<S> FromRequestParts<S> for CurrentRole
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Extension::<Role>::from_request_parts(parts, state)
+            .await
+            .map(|Extension(role)| CurrentRole(role))
+            .map_err(|e| {
+                tracing::error!("❌ Failed to extract current role: {:?}", e);
+                StatusCode::UNAUTHORIZED
+            })
+    }
+}
+
 /// Extractor for database connections
 pub struct DbConnection(pub PooledConnection<ConnectionManager<PgConnection>>);
 
@@ -49,3 +79,55 @@ impl FromRequestParts<AppState> for DbConnection {
         })
     }
 }
+
+/// Extractor for the session the request was authenticated with
+pub struct CurrentSession(pub SessionData);
+
+impl<S> FromRequestParts<S> for CurrentSession
+where
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Extension::<SessionData>::from_request_parts(parts, state)
+            .await
+            .map(|Extension(session)| CurrentSession(session))
+            .map_err(|e| {
+                tracing::error!("❌ Failed to extract current session: {:?}", e);
+                StatusCode::UNAUTHORIZED
+            })
+    }
+}
+
+/// Device and IP address of the client, recorded on new sessions
+pub struct ClientInfo {
+    pub device: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let device = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(describe_device);
+
+        let ip_address = match parts.extensions.get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(addr)) => Some(extract_client_ip_from_headers(&parts.headers, *addr)),
+            None if use_cloudflare_headers() => extract_client_ip_cloudflare(&parts.headers),
+            None => None,
+        };
+
+        Ok(ClientInfo {
+            device,
+            ip_address: ip_address.map(|ip| ip.to_string()),
+        })
+    }
+}
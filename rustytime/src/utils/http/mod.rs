@@ -1,5 +1,5 @@
 use axum::extract::ConnectInfo;
-use axum::http::HeaderMap;
+use axum::http::{HeaderMap, Method, header};
 use axum::{body::Body, extract::Request};
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -60,6 +60,49 @@ pub fn parse_user_agent(ua: String) -> Result<(Option<String>, Option<String>),
     Err("failed to parse user agent string".to_string())
 }
 
+/// Describe the browser and OS of a user agent, like `Firefox on Linux`
+pub fn describe_device(ua: &str) -> Option<String> {
+    let result = Parser::new().parse(ua)?;
+    let known = |value: &str| !value.is_empty() && value != "UNKNOWN";
+
+    if !known(result.name) {
+        return None;
+    }
+    if !known(result.os) {
+        return Some(result.name.to_string());
+    }
+    Some(format!("{} on {}", result.name, result.os))
+}
+
+/// Parse a `name/version` plugin token into its name and version
+pub fn parse_plugin(value: &str) -> Option<(String, Option<String>)> {
+    let token = value.split_whitespace().last()?;
+    let (name, version) = match token.split_once('/') {
+        Some((name, version)) => (name, Some(version)),
+        None => (token, None),
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+
+    let version = version
+        .map(|v| v.strip_prefix('v').unwrap_or(v))
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+
+    Some((name.to_ascii_lowercase(), version))
+}
+
+/// Parse the plugin name and version from a wakatime client user agent
+pub fn parse_plugin_from_user_agent(ua: &str) -> Option<(String, Option<String>)> {
+    let groups = USER_AGENT_PATTERN.captures(ua)?;
+
+    // the plugin is whatever trails the editor token
+    let plugin = ua[groups.get(2)?.start()..].split_whitespace().nth(1)?;
+    parse_plugin(plugin)
+}
+
 /// Extract client IP from request headers or connection info
 #[inline(always)]
 pub fn extract_client_ip(request: &Request<Body>) -> IpAddr {
@@ -105,6 +148,57 @@ pub fn extract_client_ip_cloudflare(headers: &HeaderMap) -> Option<IpAddr> {
     None
 }
 
+/// Serialized origin of a URL, like `https://example.com:8080`
+pub fn url_origin(value: &str) -> Option<String> {
+    let origin = url::Url::parse(value).ok()?.origin();
+    origin.is_tuple().then(|| origin.ascii_serialization())
+}
+
+/// Whether a request comes from the same site, checked before cookie authenticated writes.
+/// Uses `Sec-Fetch-Site` when the browser sends it and falls back to `Origin` and `Referer`,
+/// requests without any of them are not from a browser and cannot be forged cross-site
+pub fn is_same_origin_request(
+    method: &Method,
+    headers: &HeaderMap,
+    trusted_origins: &[String],
+) -> bool {
+    if matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    ) {
+        return true;
+    }
+
+    let header_value = |name| headers.get(name).and_then(|value| value.to_str().ok());
+
+    let fetch_site = header_value(header::HeaderName::from_static("sec-fetch-site"));
+    if matches!(fetch_site, Some("same-origin" | "none")) {
+        return true;
+    }
+
+    let origin = match header_value(header::ORIGIN) {
+        Some(origin) => url_origin(origin),
+        None => match header_value(header::REFERER) {
+            Some(referer) => url_origin(referer),
+            None => return fetch_site.is_none(),
+        },
+    };
+    let Some(origin) = origin else {
+        return false;
+    };
+
+    if trusted_origins.contains(&origin) {
+        return true;
+    }
+
+    // same origin as the API itself, like the docs page
+    header_value(header::HOST).is_some_and(|host| {
+        origin
+            .split_once("://")
+            .is_some_and(|(_, authority)| authority.eq_ignore_ascii_case(host))
+    })
+}
+
 #[derive(Clone, Copy)]
 pub struct CloudflareAwareKeyExtractor {
     use_cloudflare: bool,
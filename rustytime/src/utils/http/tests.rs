@@ -129,6 +129,59 @@ fn handles_very_long_user_agent_string() {
     assert_eq!(editor, Some("vscode".to_string()));
 }
 
+// ==================== describe_device tests ====================
+
+#[test]
+fn describes_browser_and_os() {
+    let ua = "Mozilla/5.0 (X11; Linux x86_64; rv:144.0) Gecko/20100101 Firefox/144.0";
+    assert_eq!(describe_device(ua), Some("Firefox on Linux".to_string()));
+}
+
+#[test]
+fn describes_chrome_on_windows() {
+    let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert_eq!(
+        describe_device(ua),
+        Some("Chrome on Windows 10".to_string())
+    );
+}
+
+#[test]
+fn describe_device_returns_none_for_unknown_agents() {
+    assert_eq!(describe_device("completely-invalid-garbage-string"), None);
+    assert_eq!(describe_device(""), None);
+}
+
+// ==================== parse_plugin tests ====================
+
+#[test]
+fn parses_plugin_from_wakatime_user_agent() {
+    let user_agent =
+        "wakatime/v1.131.0 (darwin-24.6.0-arm64) go1.24.4 zsh/5.9 terminal-wakatime/v1.1.5";
+    let (name, version) = parse_plugin_from_user_agent(user_agent).unwrap();
+    assert_eq!(name, "terminal-wakatime");
+    assert_eq!(version, Some("1.1.5".to_string()));
+}
+
+#[test]
+fn returns_none_for_user_agent_without_plugin() {
+    let user_agent = "wakatime/v1.115.2 (linux-6.14.1) go1.24.2 vscode/1.100.0";
+    assert!(parse_plugin_from_user_agent(user_agent).is_none());
+}
+
+#[test]
+fn returns_none_for_browser_user_agent_plugin() {
+    let browser_ua = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36";
+    assert!(parse_plugin_from_user_agent(browser_ua).is_none());
+}
+
+#[test]
+fn parses_plugin_field_without_version() {
+    let (name, version) = parse_plugin("Chrome-WakaTime").unwrap();
+    assert_eq!(name, "chrome-wakatime");
+    assert!(version.is_none());
+}
+
 // ==================== extract_client_ip_cloudflare tests ====================
 
 #[test]
@@ -240,6 +293,101 @@ fn falls_back_to_socket_addr_when_cf_header_missing() {
     assert_eq!(ip, "192.168.1.100".parse::<IpAddr>().unwrap());
 }
 
+// ==================== is_same_origin_request tests ====================
+
+fn origin_headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(*name, value.parse().unwrap());
+    }
+    headers
+}
+
+fn trusted() -> Vec<String> {
+    vec!["https://rustytime.example.com".to_string()]
+}
+
+#[test]
+fn safe_methods_skip_the_origin_check() {
+    let headers = origin_headers(&[("origin", "https://evil.example")]);
+    assert!(is_same_origin_request(&Method::GET, &headers, &trusted()));
+    assert!(is_same_origin_request(&Method::HEAD, &headers, &trusted()));
+}
+
+#[test]
+fn sec_fetch_site_same_origin_is_allowed() {
+    let headers = origin_headers(&[("sec-fetch-site", "same-origin")]);
+    assert!(is_same_origin_request(&Method::POST, &headers, &trusted()));
+}
+
+#[test]
+fn trusted_origin_is_allowed() {
+    let headers = origin_headers(&[
+        ("sec-fetch-site", "same-site"),
+        ("origin", "https://rustytime.example.com"),
+    ]);
+    assert!(is_same_origin_request(&Method::PUT, &headers, &trusted()));
+}
+
+#[test]
+fn cross_site_origin_is_rejected() {
+    let headers = origin_headers(&[
+        ("sec-fetch-site", "cross-site"),
+        ("origin", "https://evil.example"),
+    ]);
+    assert!(!is_same_origin_request(&Method::POST, &headers, &trusted()));
+
+    let headers = origin_headers(&[("origin", "null")]);
+    assert!(!is_same_origin_request(
+        &Method::DELETE,
+        &headers,
+        &trusted()
+    ));
+}
+
+#[test]
+fn referer_is_used_without_origin() {
+    let headers = origin_headers(&[("referer", "https://rustytime.example.com/settings")]);
+    assert!(is_same_origin_request(&Method::POST, &headers, &trusted()));
+
+    let headers = origin_headers(&[("referer", "https://evil.example/page")]);
+    assert!(!is_same_origin_request(&Method::POST, &headers, &trusted()));
+}
+
+#[test]
+fn origin_matching_host_is_allowed() {
+    let headers = origin_headers(&[
+        ("origin", "https://api.rustytime.example.com"),
+        ("host", "api.rustytime.example.com"),
+    ]);
+    assert!(is_same_origin_request(&Method::POST, &headers, &trusted()));
+}
+
+#[test]
+fn requests_without_browser_headers_are_allowed() {
+    assert!(is_same_origin_request(
+        &Method::POST,
+        &HeaderMap::new(),
+        &trusted()
+    ));
+
+    let headers = origin_headers(&[("sec-fetch-site", "cross-site")]);
+    assert!(!is_same_origin_request(&Method::POST, &headers, &trusted()));
+}
+
+#[test]
+fn url_origin_drops_path_and_default_port() {
+    assert_eq!(
+        url_origin("https://example.com:443/some/path?q=1"),
+        Some("https://example.com".to_string())
+    );
+    assert_eq!(
+        url_origin("http://localhost:5173"),
+        Some("http://localhost:5173".to_string())
+    );
+    assert_eq!(url_origin("null"), None);
+}
+
 // ==================== CloudflareAwareKeyExtractor tests ====================
 
 #[test]
@@ -0,0 +1,146 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// Canonical language names and the spellings plugins are known to send for them
+const CANONICAL_LANGUAGES: &[(&str, &[&str])] = &[
+    ("Assembly", &["asm", "nasm", "gas"]),
+    ("Astro", &[]),
+    (
+        "Bash",
+        &["sh", "shell", "shellscript", "shell script", "zsh"],
+    ),
+    ("C", &[]),
+    ("C#", &["csharp", "c sharp", "cs"]),
+    ("C++", &["cpp", "cplusplus", "c plus plus", "cxx"]),
+    ("Clojure", &["clj"]),
+    ("CMake", &[]),
+    ("CSS", &[]),
+    ("CSV", &[]),
+    ("Dart", &[]),
+    ("Docker", &["dockerfile"]),
+    ("Elixir", &["ex", "exs"]),
+    ("Erlang", &["erl"]),
+    ("F#", &["fsharp"]),
+    ("Fish", &[]),
+    ("GDScript", &["gdscript3"]),
+    (
+        "Git",
+        &[
+            "git commit",
+            "git-commit",
+            "git config",
+            "git rebase",
+            "gitignore",
+        ],
+    ),
+    ("GLSL", &[]),
+    ("Go", &["golang", "go module", "go.mod"]),
+    ("GraphQL", &["gql"]),
+    ("Groovy", &[]),
+    ("Haskell", &["hs"]),
+    ("HCL", &["terraform", "tf"]),
+    ("HTML", &["htm", "xhtml"]),
+    ("INI", &["properties"]),
+    ("Java", &[]),
+    (
+        "JavaScript",
+        &[
+            "js",
+            "jsx",
+            "javascriptreact",
+            "javascript react",
+            "mjs",
+            "cjs",
+        ],
+    ),
+    ("JSON", &["jsonc", "json with comments", "json5"]),
+    ("Julia", &["jl"]),
+    ("Jupyter", &["ipynb", "jupyter notebook"]),
+    ("Kotlin", &["kt", "kts"]),
+    ("LaTeX", &["tex"]),
+    ("Less", &[]),
+    ("Lua", &["luau"]),
+    ("Makefile", &["make"]),
+    ("Markdown", &["md", "mdx"]),
+    ("Nix", &[]),
+    ("Objective-C", &["objc", "objective c", "objectivec"]),
+    ("OCaml", &["ml"]),
+    ("Perl", &["pl"]),
+    ("PHP", &[]),
+    ("PowerShell", &["ps1", "pwsh"]),
+    ("Prisma", &[]),
+    ("Protocol Buffer", &["proto", "protobuf", "proto3"]),
+    ("Python", &["py", "python3"]),
+    ("R", &[]),
+    ("Ruby", &["rb"]),
+    ("Rust", &["rs"]),
+    ("Sass", &[]),
+    ("Scala", &[]),
+    ("SCSS", &[]),
+    ("SQL", &["plsql", "pgsql", "postgres", "mysql", "sqlite"]),
+    ("Svelte", &[]),
+    ("Swift", &[]),
+    ("Text", &["plaintext", "plain text", "txt"]),
+    ("TOML", &[]),
+    (
+        "TypeScript",
+        &[
+            "ts",
+            "tsx",
+            "typescriptreact",
+            "typescript react",
+            "mts",
+            "cts",
+        ],
+    ),
+    ("Vue.js", &["vue"]),
+    ("XML", &[]),
+    ("YAML", &["yml"]),
+    ("Zig", &[]),
+];
+
+static LANGUAGE_LOOKUP: Lazy<HashMap<String, &'static str>> = Lazy::new(|| {
+    let mut lookup = HashMap::new();
+    for (canonical, aliases) in CANONICAL_LANGUAGES {
+        lookup.insert(canonical.to_lowercase(), *canonical);
+        for alias in *aliases {
+            lookup.insert(alias.to_string(), *canonical);
+        }
+    }
+    lookup
+});
+
+/// Canonical name of a language, if it is in the table
+pub fn canonical_language(language: &str) -> Option<&'static str> {
+    LANGUAGE_LOOKUP
+        .get(&language.trim().to_lowercase())
+        .copied()
+}
+
+/// Normalize a language reported by a plugin, keeping unknown names as sent
+pub fn normalize_language(language: Option<String>) -> Option<String> {
+    let language = language?;
+    let trimmed = language.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    match canonical_language(trimmed) {
+        Some(canonical) => Some(canonical.to_string()),
+        None if trimmed.len() == language.len() => Some(language),
+        None => Some(trimmed.to_string()),
+    }
+}
+
+/// Lowercased extension of an entity path, without the leading dot
+pub fn file_extension(entity: &str) -> Option<String> {
+    let name = entity.rsplit(['/', '\\']).next()?;
+    let (stem, extension) = name.rsplit_once('.')?;
+    if stem.is_empty() || extension.is_empty() {
+        return None;
+    }
+    Some(extension.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests;
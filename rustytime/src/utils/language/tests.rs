@@ -0,0 +1,83 @@
+use super::*;
+
+// ==================== canonical_language tests ====================
+
+#[test]
+fn canonical_language_merges_case_variants() {
+    assert_eq!(canonical_language("typescript"), Some("TypeScript"));
+    assert_eq!(canonical_language("TypeScript"), Some("TypeScript"));
+    assert_eq!(canonical_language("rust"), Some("Rust"));
+    assert_eq!(canonical_language("  Rust "), Some("Rust"));
+}
+
+#[test]
+fn canonical_language_maps_known_aliases() {
+    assert_eq!(canonical_language("TSX"), Some("TypeScript"));
+    assert_eq!(canonical_language("typescriptreact"), Some("TypeScript"));
+    assert_eq!(canonical_language("JSX"), Some("JavaScript"));
+    assert_eq!(canonical_language("golang"), Some("Go"));
+    assert_eq!(canonical_language("cpp"), Some("C++"));
+    assert_eq!(canonical_language("plaintext"), Some("Text"));
+}
+
+#[test]
+fn canonical_language_returns_none_for_unknown() {
+    assert_eq!(canonical_language("Brainfuck"), None);
+    assert_eq!(canonical_language(""), None);
+}
+
+#[test]
+fn canonical_aliases_do_not_collide() {
+    let mut seen = HashMap::new();
+    for (canonical, aliases) in CANONICAL_LANGUAGES {
+        for name in std::iter::once(&canonical.to_lowercase().as_str()).chain(aliases.iter()) {
+            if let Some(previous) = seen.insert(name.to_string(), *canonical) {
+                panic!("{name} maps to both {previous} and {canonical}");
+            }
+        }
+    }
+}
+
+// ==================== normalize_language tests ====================
+
+#[test]
+fn normalize_language_uses_canonical_names() {
+    assert_eq!(
+        normalize_language(Some("tsx".to_string())),
+        Some("TypeScript".to_string())
+    );
+}
+
+#[test]
+fn normalize_language_keeps_unknown_names() {
+    assert_eq!(
+        normalize_language(Some("Brainfuck".to_string())),
+        Some("Brainfuck".to_string())
+    );
+    assert_eq!(
+        normalize_language(Some(" Brainfuck ".to_string())),
+        Some("Brainfuck".to_string())
+    );
+}
+
+#[test]
+fn normalize_language_drops_blank_values() {
+    assert_eq!(normalize_language(Some("   ".to_string())), None);
+    assert_eq!(normalize_language(None), None);
+}
+
+// ==================== file_extension tests ====================
+
+#[test]
+fn file_extension_reads_last_segment() {
+    assert_eq!(file_extension("/src/main.rs"), Some("rs".to_string()));
+    assert_eq!(file_extension("C:\\code\\App.TSX"), Some("tsx".to_string()));
+    assert_eq!(file_extension("archive.tar.gz"), Some("gz".to_string()));
+}
+
+#[test]
+fn file_extension_ignores_dotfiles_and_bare_names() {
+    assert_eq!(file_extension("/home/me/.bashrc"), None);
+    assert_eq!(file_extension("Makefile"), None);
+    assert_eq!(file_extension("/src.d/Makefile"), None);
+}
@@ -0,0 +1,195 @@
+use std::sync::LazyLock;
+
+use argon2::password_hash::{PasswordHash, SaltString, rand_core::OsRng};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use tracing::{info, warn};
+
+use crate::db::connection::DbPool;
+use crate::models::user::User;
+use crate::models::user_identity::UserIdentity;
+
+/// Provider id of local accounts in `user_identities`
+pub const LOCAL_PROVIDER: &str = "local";
+
+pub const MIN_USERNAME_LENGTH: usize = 2;
+pub const MAX_USERNAME_LENGTH: usize = 39;
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+pub const MAX_EMAIL_LENGTH: usize = 255;
+
+/// Verified against for unknown usernames so they take as long as wrong passwords
+static DUMMY_HASH: LazyLock<String> =
+    LazyLock::new(|| hash_password("rustytime-dummy-password").unwrap_or_default());
+
+/// Owner account provisioned from the environment in single-user mode
+#[derive(Clone)]
+pub struct SingleUser {
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for SingleUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SingleUser")
+            .field("username", &self.username)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Username and password login settings
+#[derive(Debug, Clone, Default)]
+pub struct LocalAuth {
+    /// Whether local accounts can log in
+    pub enabled: bool,
+    /// Whether anyone can create a local account
+    pub registration: bool,
+    pub single_user: Option<SingleUser>,
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    std::env::var(name).ok().map(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "true" | "1" | "yes" | "on"
+        )
+    })
+}
+
+impl LocalAuth {
+    /// Read `LOCAL_AUTH`, `LOCAL_REGISTRATION` and the `SINGLE_USER_*` variables
+    pub fn from_env() -> Self {
+        let single_user = match (
+            std::env::var("SINGLE_USER_NAME").ok(),
+            std::env::var("SINGLE_USER_PASSWORD").ok(),
+        ) {
+            (Some(username), Some(password)) => {
+                let username = username.trim().to_string();
+                match validate_username(&username).and(validate_password(&password)) {
+                    Ok(()) => Some(SingleUser { username, password }),
+                    Err(e) => {
+                        warn!(error = e, "Invalid single-user configuration, ignoring it");
+                        None
+                    }
+                }
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                warn!("Single-user mode needs both SINGLE_USER_NAME and SINGLE_USER_PASSWORD");
+                None
+            }
+            (None, None) => None,
+        };
+
+        if let Some(single_user) = &single_user {
+            info!(username = %single_user.username, "Single-user mode enabled");
+            return Self {
+                enabled: true,
+                registration: false,
+                single_user: Some(single_user.clone()),
+            };
+        }
+
+        let enabled = env_flag("LOCAL_AUTH").unwrap_or(false);
+        Self {
+            enabled,
+            registration: enabled && env_flag("LOCAL_REGISTRATION").unwrap_or(true),
+            single_user: None,
+        }
+    }
+
+    pub fn is_single_user(&self) -> bool {
+        self.single_user.is_some()
+    }
+
+    /// Create or update the owner account in single-user mode, the configured password always wins
+    pub async fn provision_single_user(
+        &self,
+        pool: &DbPool,
+    ) -> Result<Option<User>, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(SingleUser { username, password }) = self.single_user.clone() else {
+            return Ok(None);
+        };
+
+        let password_hash = tokio::task::spawn_blocking(move || hash_password(&password))
+            .await?
+            .map_err(|e| e.to_string())?;
+
+        let mut conn = pool.get()?;
+        let user = UserIdentity::provision_local_owner(&mut conn, &username, &password_hash)?;
+        Ok(Some(user))
+    }
+}
+
+/// Check a username for local accounts, same rules as GitHub logins
+pub fn validate_username(username: &str) -> Result<(), &'static str> {
+    if username.len() < MIN_USERNAME_LENGTH || username.len() > MAX_USERNAME_LENGTH {
+        return Err("Username must be between 2 and 39 characters");
+    }
+    if !username
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err("Username can only contain letters, numbers, dashes and underscores");
+    }
+    if username.starts_with('-') || username.ends_with('-') {
+        return Err("Username cannot start or end with a dash");
+    }
+    Ok(())
+}
+
+pub fn validate_password(password: &str) -> Result<(), &'static str> {
+    let length = password.chars().count();
+    if length < MIN_PASSWORD_LENGTH {
+        return Err("Password must be at least 8 characters");
+    }
+    if length > MAX_PASSWORD_LENGTH {
+        return Err("Password must be at most 128 characters");
+    }
+    Ok(())
+}
+
+/// Check an email address set by hand, local accounts have no provider to take one from
+pub fn validate_email(email: &str) -> Result<(), &'static str> {
+    if email.len() > MAX_EMAIL_LENGTH {
+        return Err("Email address must be at most 255 characters");
+    }
+    let valid = email.split_once('@').is_some_and(|(local, domain)| {
+        !local.is_empty() && domain.contains('.') && !domain.contains('@')
+    }) && !email.chars().any(char::is_whitespace);
+    if !valid {
+        return Err("Invalid email address");
+    }
+    Ok(())
+}
+
+/// Subject of a local identity, usernames are case insensitive
+pub fn local_subject(username: &str) -> String {
+    username.to_ascii_lowercase()
+}
+
+/// Hash a password with argon2id and a random salt
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Check a password against a stored hash, a missing hash never matches
+pub fn verify_password(password: &str, hash: Option<&str>) -> bool {
+    let (hash, known) = match hash {
+        Some(hash) => (hash, true),
+        None => (DUMMY_HASH.as_str(), false),
+    };
+
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return false;
+    };
+
+    Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok()
+        && known
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+use super::*;
+
+// ==================== validation tests ====================
+
+#[test]
+fn validate_username_accepts_github_style_names() {
+    assert!(validate_username("octocat").is_ok());
+    assert!(validate_username("shy_mike-2").is_ok());
+}
+
+#[test]
+fn validate_username_rejects_invalid_names() {
+    assert!(validate_username("a").is_err());
+    assert!(validate_username(&"a".repeat(40)).is_err());
+    assert!(validate_username("has space").is_err());
+    assert!(validate_username("-dash").is_err());
+    assert!(validate_username("dash-").is_err());
+    assert!(validate_username("ünicode").is_err());
+}
+
+#[test]
+fn validate_password_checks_length() {
+    assert!(validate_password("short").is_err());
+    assert!(validate_password("long enough").is_ok());
+    assert!(validate_password(&"x".repeat(129)).is_err());
+}
+
+#[test]
+fn validate_email_checks_the_shape() {
+    assert!(validate_email("mike@example.com").is_ok());
+    assert!(validate_email("mike").is_err());
+    assert!(validate_email("@example.com").is_err());
+    assert!(validate_email("mike@localhost").is_err());
+    assert!(validate_email("mi ke@example.com").is_err());
+    assert!(validate_email("a@b@example.com").is_err());
+    assert!(validate_email(&format!("{}@example.com", "x".repeat(250))).is_err());
+}
+
+#[test]
+fn local_subject_is_case_insensitive() {
+    assert_eq!(local_subject("ShyMike"), local_subject("shymike"));
+}
+
+// ==================== hashing tests ====================
+
+#[test]
+fn hash_password_round_trips() {
+    let hash = hash_password("correct horse battery").unwrap();
+    assert!(hash.starts_with("$argon2id$"));
+    assert!(verify_password("correct horse battery", Some(&hash)));
+    assert!(!verify_password("wrong horse battery", Some(&hash)));
+}
+
+#[test]
+fn hash_password_uses_random_salts() {
+    assert_ne!(
+        hash_password("same password").unwrap(),
+        hash_password("same password").unwrap()
+    );
+}
+
+#[test]
+fn verify_password_rejects_missing_and_invalid_hashes() {
+    assert!(!verify_password("rustytime-dummy-password", None));
+    assert!(!verify_password("anything", Some("not a hash")));
+}
@@ -1,5 +1,9 @@
+use crate::models::audit_log::{AuditAction, AuditLogEntry, NewAuditLogEntry};
+use crate::models::role::Permission;
 use crate::state::AppState;
-use crate::utils::session::{ImpersonationContext, SessionManager};
+use crate::utils::env::trusted_origins;
+use crate::utils::http::is_same_origin_request;
+use crate::utils::session::{ImpersonationContext, ResolvedSession, SessionManager};
 use axum::{
     extract::{Request, State},
     http::{HeaderValue, StatusCode},
@@ -10,24 +14,141 @@ use reqwest::Method;
 use tower_cookies::Cookies;
 use tower_http::cors::{AllowOrigin, CorsLayer};
 
+/// Run the request as the session's user, recording writes made while impersonating.
+/// Impersonated writes are refused unless `read_only` is off or write mode was enabled.
+async fn run_authenticated(
+    app_state: &AppState,
+    resolved: ResolvedSession,
+    read_only: bool,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let impersonation =
+        resolved
+            .impersonator
+            .clone()
+            .map(|(admin, admin_role)| ImpersonationContext {
+                write: resolved.session.impersonation_write
+                    && admin_role.has(Permission::ImpersonateWrite),
+                admin,
+                admin_role,
+            });
+
+    // handlers check permissions against the role of whoever is acting, the admin when impersonating
+    let acting_role = impersonation
+        .as_ref()
+        .map_or(&resolved.role, |ctx| &ctx.admin_role);
+
+    let extensions = request.extensions_mut();
+    extensions.insert(resolved.user.clone());
+    extensions.insert(acting_role.clone());
+    extensions.insert(resolved.session.clone());
+    if let Some(ctx) = impersonation.clone() {
+        extensions.insert(ctx);
+    }
+
+    let Some(ctx) = impersonation else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let mut response = if read_only && !ctx.allows(&method) {
+        (StatusCode::FORBIDDEN, "Impersonation is read-only").into_response()
+    } else if read_only
+        && !matches!(method, Method::GET | Method::HEAD | Method::OPTIONS)
+        && let Err(e) =
+            record_impersonated_request(app_state, &ctx, &resolved, &method, &path).await
+    {
+        // routes that skip the read-only check record their own audit entries, any other
+        // write is recorded before it runs and refused when that fails
+        tracing::error!(error = %e, "Failed to record impersonated request");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to record impersonated request",
+        )
+            .into_response()
+    } else {
+        next.run(request).await
+    };
+
+    response.headers_mut().insert(
+        ImpersonationContext::HEADER,
+        HeaderValue::from_static(ctx.mode()),
+    );
+    response
+}
+
+/// Write the audit entry of a request made while impersonating
+async fn record_impersonated_request(
+    app_state: &AppState,
+    ctx: &ImpersonationContext,
+    resolved: &ResolvedSession,
+    method: &Method,
+    path: &str,
+) -> Result<(), String> {
+    let entry = NewAuditLogEntry::new(
+        ctx.admin.id,
+        resolved.user.id,
+        AuditAction::ImpersonatedRequest,
+    )
+    .target_user(resolved.user.id)
+    .metadata(serde_json::json!({
+        "method": method.as_str(),
+        "path": path,
+        "write_mode": ctx.write,
+    }));
+
+    let pool = app_state.db_pool.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        AuditLogEntry::record(&mut conn, &entry).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map(|_| ())
+}
+
+/// Resolve the session and let it through when the acting role grants `permission`,
+/// which is the impersonating admin's role when impersonating
+async fn permission_session(
+    app_state: &AppState,
+    cookies: &Cookies,
+    permission: Permission,
+    read_only: bool,
+    request: Request,
+    next: Next,
+) -> Response {
+    match SessionManager::resolve_session(cookies, &app_state.db_pool).await {
+        Ok(Some(resolved)) => {
+            let acting_role = resolved
+                .impersonator
+                .as_ref()
+                .map_or(&resolved.role, |(_, admin_role)| admin_role);
+
+            if !acting_role.has(permission) {
+                return (StatusCode::FORBIDDEN, "Missing permission").into_response();
+            }
+
+            run_authenticated(app_state, resolved, read_only, request, next).await
+        }
+        Ok(None) => {
+            // user is not authenticated
+            (StatusCode::UNAUTHORIZED, "Authentication required").into_response()
+        }
+        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response(),
+    }
+}
+
 /// Middleware to require authentication
 pub async fn require_auth(
     State(app_state): State<AppState>,
     cookies: Cookies,
-    mut request: Request,
+    request: Request,
     next: Next,
 ) -> Response {
     match SessionManager::resolve_session(&cookies, &app_state.db_pool).await {
-        Ok(Some(resolved)) => {
-            {
-                let extensions = request.extensions_mut();
-                extensions.insert(resolved.user.clone());
-                if let Some(admin) = resolved.impersonator.clone() {
-                    extensions.insert(ImpersonationContext { admin });
-                }
-            }
-            next.run(request).await
-        }
+        Ok(Some(resolved)) => run_authenticated(&app_state, resolved, true, request, next).await,
         Ok(None) => (StatusCode::UNAUTHORIZED, "Authentication required").into_response(),
         Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response(),
     }
@@ -48,80 +169,48 @@ pub async fn require_auth(
 //     next.run(request).await
 // }
 
-/// Middleware to require admin privileges
-pub async fn require_admin(
-    State(app_state): State<AppState>,
+/// Middleware to require a permission, layered with `from_fn_with_state((state, permission), ..)`
+pub async fn require_permission(
+    State((app_state, permission)): State<(AppState, Permission)>,
     cookies: Cookies,
-    mut request: Request,
+    request: Request,
     next: Next,
 ) -> Response {
-    match SessionManager::resolve_session(&cookies, &app_state.db_pool).await {
-        Ok(Some(resolved)) => {
-            let is_admin = resolved.user.is_admin()
-                || resolved
-                    .impersonator
-                    .as_ref()
-                    .map(|admin| admin.is_admin())
-                    .unwrap_or(false);
-
-            if !is_admin {
-                return (StatusCode::FORBIDDEN, "Admin access required").into_response();
-            }
-
-            {
-                let extensions = request.extensions_mut();
-                extensions.insert(resolved.user.clone());
-                if let Some(admin) = resolved.impersonator.clone() {
-                    extensions.insert(ImpersonationContext { admin });
-                }
-            }
-
-            next.run(request).await
-        }
-        Ok(None) => {
-            // user is not authenticated
-            (StatusCode::UNAUTHORIZED, "Authentication required").into_response()
-        }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response(),
-    }
+    permission_session(&app_state, &cookies, permission, true, request, next).await
 }
 
-/// Middleware to require owner privileges
-pub async fn require_owner(
+/// Middleware for the impersonation route, which stays writable so impersonation can be ended
+pub async fn require_impersonate(
     State(app_state): State<AppState>,
     cookies: Cookies,
-    mut request: Request,
+    request: Request,
     next: Next,
 ) -> Response {
-    match SessionManager::resolve_session(&cookies, &app_state.db_pool).await {
-        Ok(Some(resolved)) => {
-            let is_owner = resolved.user.is_owner()
-                || resolved
-                    .impersonator
-                    .as_ref()
-                    .map(|admin| admin.is_owner())
-                    .unwrap_or(false);
-
-            if !is_owner {
-                return (StatusCode::FORBIDDEN, "Owner access required").into_response();
-            }
+    permission_session(
+        &app_state,
+        &cookies,
+        Permission::Impersonate,
+        false,
+        request,
+        next,
+    )
+    .await
+}
 
-            {
-                let extensions = request.extensions_mut();
-                extensions.insert(resolved.user.clone());
-                if let Some(admin) = resolved.impersonator.clone() {
-                    extensions.insert(ImpersonationContext { admin });
-                }
-            }
+/// Routes that start a session from posted credentials, checked even without a session cookie
+/// so a cross-site form can't sign the victim into another account
+const SESSION_STARTING_PATHS: [&str; 2] = ["/auth/local/login", "/auth/local/register"];
 
-            next.run(request).await
-        }
-        Ok(None) => {
-            // user is not authenticated
-            (StatusCode::UNAUTHORIZED, "Authentication required").into_response()
-        }
-        Err(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error").into_response(),
+/// Middleware to reject cross-site writes authenticated by the session cookie
+pub async fn csrf_protection(cookies: Cookies, request: Request, next: Next) -> Response {
+    let checked = SessionManager::get_session_from_cookies(&cookies).is_some()
+        || SESSION_STARTING_PATHS.contains(&request.uri().path());
+
+    if checked && !is_same_origin_request(request.method(), request.headers(), trusted_origins()) {
+        return (StatusCode::FORBIDDEN, "Cross-site request blocked").into_response();
     }
+
+    next.run(request).await
 }
 
 /// Middleware to track request metrics
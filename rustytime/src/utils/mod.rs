@@ -1,12 +1,19 @@
 pub mod auth;
+pub mod badge;
 pub mod cache;
+pub mod card;
+pub mod digest;
 pub mod env;
 pub mod extractors;
 pub mod http;
 pub mod instrumented;
+pub mod language;
+pub mod local_auth;
 pub mod macros;
+pub mod mailer;
 pub mod metrics;
 pub mod middleware;
+pub mod oauth;
 pub mod session;
 pub mod time;
 pub mod tracing;
@@ -0,0 +1,439 @@
+use std::sync::Arc;
+
+use oauth2::{
+    AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointNotSet, EndpointSet,
+    RedirectUrl, Scope, TokenResponse, TokenUrl, basic::BasicClient, reqwest as oauth2_reqwest,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{error, info, warn};
+
+pub type OAuthClient =
+    BasicClient<EndpointSet, EndpointNotSet, EndpointNotSet, EndpointNotSet, EndpointSet>;
+
+const DEFAULT_REDIRECT_URL: &str = "http://localhost:3000/auth/github/callback";
+const DEFAULT_GITLAB_URL: &str = "https://gitlab.com";
+const USER_AGENT: &str = "rustytime-oauth";
+
+/// Kinds of supported login providers, each enabled by its own environment variables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    Github,
+    Gitlab,
+    /// Gitea and Forgejo share the same API
+    Gitea,
+    /// Any OpenID Connect issuer, configured through discovery
+    Oidc,
+}
+
+impl ProviderKind {
+    pub const ALL: [ProviderKind; 4] = [
+        ProviderKind::Github,
+        ProviderKind::Gitlab,
+        ProviderKind::Gitea,
+        ProviderKind::Oidc,
+    ];
+
+    /// Identifier used in routes and stored with identities
+    pub fn id(&self) -> &'static str {
+        match self {
+            ProviderKind::Github => "github",
+            ProviderKind::Gitlab => "gitlab",
+            ProviderKind::Gitea => "gitea",
+            ProviderKind::Oidc => "oidc",
+        }
+    }
+
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            ProviderKind::Github => "GITHUB",
+            ProviderKind::Gitlab => "GITLAB",
+            ProviderKind::Gitea => "GITEA",
+            ProviderKind::Oidc => "OIDC",
+        }
+    }
+
+    fn default_name(&self) -> &'static str {
+        match self {
+            ProviderKind::Github => "GitHub",
+            ProviderKind::Gitlab => "GitLab",
+            ProviderKind::Gitea => "Gitea",
+            ProviderKind::Oidc => "OpenID Connect",
+        }
+    }
+
+    fn scopes(&self) -> &'static [&'static str] {
+        match self {
+            ProviderKind::Github => &["read:user", "user:email"],
+            ProviderKind::Gitlab => &["read_user"],
+            ProviderKind::Gitea => &["read:user"],
+            ProviderKind::Oidc => &["openid", "profile", "email"],
+        }
+    }
+}
+
+/// Account details reported by a provider
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderUser {
+    /// Stable account id at the provider
+    pub subject: String,
+    pub username: String,
+    pub avatar_url: String,
+    pub email: Option<String>,
+}
+
+fn string_field(value: &Value, key: &str) -> Option<String> {
+    match value.get(key)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+/// Read the account details from a provider's user endpoint response
+pub fn parse_user(kind: ProviderKind, value: &Value) -> Option<ProviderUser> {
+    let (subject, username, avatar_url, email) = match kind {
+        ProviderKind::Github | ProviderKind::Gitea => (
+            string_field(value, "id")?,
+            string_field(value, "login")?,
+            string_field(value, "avatar_url"),
+            string_field(value, "email"),
+        ),
+        ProviderKind::Gitlab => (
+            string_field(value, "id")?,
+            string_field(value, "username")?,
+            string_field(value, "avatar_url"),
+            string_field(value, "email").or_else(|| string_field(value, "public_email")),
+        ),
+        ProviderKind::Oidc => {
+            let subject = string_field(value, "sub")?;
+            let email = string_field(value, "email")
+                .filter(|_| value.get("email_verified") != Some(&Value::Bool(false)));
+            let username = string_field(value, "preferred_username")
+                .or_else(|| string_field(value, "nickname"))
+                .or_else(|| string_field(value, "name"))
+                .or_else(|| {
+                    email
+                        .as_deref()
+                        .and_then(|e| e.split('@').next())
+                        .map(str::to_string)
+                })
+                .unwrap_or_else(|| format!("user-{}", subject.chars().take(8).collect::<String>()));
+            (subject, username, string_field(value, "picture"), email)
+        }
+    };
+
+    Some(ProviderUser {
+        subject,
+        username: username.chars().take(100).collect(),
+        avatar_url: avatar_url
+            .filter(|url| url.len() <= 200)
+            .unwrap_or_default(),
+        email: email.filter(|e| e.len() <= 255 && e.contains('@')),
+    })
+}
+
+/// Callback URL of a provider, derived from the GitHub `REDIRECT_URL` so one setting covers all
+pub fn callback_url(redirect_url: &str, provider_id: &str) -> String {
+    let base = redirect_url.trim_end_matches('/');
+    let base = base.strip_suffix("/github/callback").unwrap_or(base);
+    format!("{base}/{provider_id}/callback")
+}
+
+/// OAuth endpoints of a provider
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderEndpoints {
+    #[serde(rename = "authorization_endpoint")]
+    pub auth_url: String,
+    #[serde(rename = "token_endpoint")]
+    pub token_url: String,
+    #[serde(rename = "userinfo_endpoint")]
+    pub userinfo_url: String,
+}
+
+impl ProviderEndpoints {
+    fn under(base: &str, auth: &str, token: &str, userinfo: &str) -> Self {
+        let base = base.trim_end_matches('/');
+        Self {
+            auth_url: format!("{base}{auth}"),
+            token_url: format!("{base}{token}"),
+            userinfo_url: format!("{base}{userinfo}"),
+        }
+    }
+}
+
+/// A configured login provider
+#[derive(Clone)]
+pub struct AuthProvider {
+    pub kind: ProviderKind,
+    /// Display name shown on login buttons
+    pub name: String,
+    client: OAuthClient,
+    userinfo_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ProviderInfo {
+    pub id: &'static str,
+    pub kind: ProviderKind,
+    pub name: String,
+}
+
+impl AuthProvider {
+    pub fn new(
+        kind: ProviderKind,
+        name: String,
+        client_id: String,
+        client_secret: String,
+        endpoints: ProviderEndpoints,
+        redirect_url: String,
+    ) -> Result<Self, String> {
+        let auth_url =
+            AuthUrl::new(endpoints.auth_url).map_err(|e| format!("invalid auth URL: {e}"))?;
+        let token_url =
+            TokenUrl::new(endpoints.token_url).map_err(|e| format!("invalid token URL: {e}"))?;
+        let redirect_url =
+            RedirectUrl::new(redirect_url).map_err(|e| format!("invalid redirect URL: {e}"))?;
+
+        let client = BasicClient::new(ClientId::new(client_id))
+            .set_client_secret(ClientSecret::new(client_secret))
+            .set_auth_uri(auth_url)
+            .set_token_uri(token_url)
+            .set_redirect_uri(redirect_url);
+
+        Ok(Self {
+            kind,
+            name,
+            client,
+            userinfo_url: endpoints.userinfo_url,
+        })
+    }
+
+    pub fn id(&self) -> &'static str {
+        self.kind.id()
+    }
+
+    pub fn info(&self) -> ProviderInfo {
+        ProviderInfo {
+            id: self.id(),
+            kind: self.kind,
+            name: self.name.clone(),
+        }
+    }
+
+    /// URL to send the user to, along with the CSRF state to check in the callback
+    pub fn authorize_url(&self) -> (String, String) {
+        let (url, csrf_token) = self
+            .kind
+            .scopes()
+            .iter()
+            .fold(
+                self.client.authorize_url(CsrfToken::new_random),
+                |request, scope| request.add_scope(Scope::new(scope.to_string())),
+            )
+            .url();
+
+        (url.to_string(), csrf_token.secret().clone())
+    }
+
+    /// Exchange an authorization code for an access token
+    pub async fn exchange_code(&self, code: String) -> Result<String, String> {
+        // i hate this so much but oauth2 crate is stuck on reqwest 0.12
+        let client = oauth2_reqwest::Client::builder()
+            .redirect(oauth2_reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let token = self
+            .client
+            .exchange_code(AuthorizationCode::new(code))
+            .request_async(&client)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(token.access_token().secret().clone())
+    }
+
+    /// Fetch the account details of the token's owner
+    pub async fn fetch_user(
+        &self,
+        http: &reqwest::Client,
+        token: &str,
+    ) -> Result<ProviderUser, String> {
+        let value: Value = http
+            .get(&self.userinfo_url)
+            .bearer_auth(token)
+            .header("User-Agent", USER_AGENT)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut user = parse_user(self.kind, &value)
+            .ok_or_else(|| format!("unexpected user response from {}", self.name))?;
+
+        // private GitHub emails are only listed by the emails endpoint
+        if self.kind == ProviderKind::Github && user.email.is_none() {
+            match fetch_github_primary_email(http, token).await {
+                Ok(email) => user.email = email,
+                Err(e) => error!("Failed to fetch user emails from GitHub: {}", e),
+            }
+        }
+
+        Ok(user)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+/// Fetch the primary verified email of the GitHub user
+async fn fetch_github_primary_email(
+    http: &reqwest::Client,
+    token: &str,
+) -> Result<Option<String>, reqwest::Error> {
+    let emails: Vec<GitHubEmail> = http
+        .get("https://api.github.com/user/emails")
+        .bearer_auth(token)
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    Ok(emails
+        .into_iter()
+        .find(|e| e.primary && e.verified)
+        .map(|e| e.email))
+}
+
+fn env_var(kind: ProviderKind, name: &str) -> Option<String> {
+    std::env::var(format!("{}_{name}", kind.env_prefix()))
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Build a provider from its `<PREFIX>_*` environment variables, none when it is not configured
+async fn provider_from_env(
+    kind: ProviderKind,
+    http: &reqwest::Client,
+) -> Result<Option<AuthProvider>, String> {
+    let (Some(client_id), Some(client_secret)) =
+        (env_var(kind, "CLIENT_ID"), env_var(kind, "CLIENT_SECRET"))
+    else {
+        return Ok(None);
+    };
+
+    let redirect_url = env_var(kind, "REDIRECT_URL").unwrap_or_else(|| {
+        let github_redirect =
+            std::env::var("REDIRECT_URL").unwrap_or_else(|_| DEFAULT_REDIRECT_URL.to_string());
+        callback_url(&github_redirect, kind.id())
+    });
+    let name = env_var(kind, "NAME").unwrap_or_else(|| kind.default_name().to_string());
+
+    let endpoints = match kind {
+        ProviderKind::Github => ProviderEndpoints {
+            auth_url: "https://github.com/login/oauth/authorize".to_string(),
+            token_url: "https://github.com/login/oauth/access_token".to_string(),
+            userinfo_url: "https://api.github.com/user".to_string(),
+        },
+        ProviderKind::Gitlab => ProviderEndpoints::under(
+            &env_var(kind, "URL").unwrap_or_else(|| DEFAULT_GITLAB_URL.to_string()),
+            "/oauth/authorize",
+            "/oauth/token",
+            "/api/v4/user",
+        ),
+        ProviderKind::Gitea => ProviderEndpoints::under(
+            &env_var(kind, "URL").ok_or("GITEA_URL is required")?,
+            "/login/oauth/authorize",
+            "/login/oauth/access_token",
+            "/api/v1/user",
+        ),
+        ProviderKind::Oidc => {
+            let issuer = env_var(kind, "ISSUER_URL").ok_or("OIDC_ISSUER_URL is required")?;
+            let discovery_url = format!(
+                "{}/.well-known/openid-configuration",
+                issuer.trim_end_matches('/')
+            );
+            http.get(&discovery_url)
+                .header("User-Agent", USER_AGENT)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("discovery failed: {e}"))?
+                .json::<ProviderEndpoints>()
+                .await
+                .map_err(|e| format!("invalid discovery document: {e}"))?
+        }
+    };
+
+    AuthProvider::new(
+        kind,
+        name,
+        client_id,
+        client_secret,
+        endpoints,
+        redirect_url,
+    )
+    .map(Some)
+}
+
+/// All configured login providers
+#[derive(Clone, Default)]
+pub struct AuthProviders {
+    providers: Arc<Vec<AuthProvider>>,
+}
+
+impl AuthProviders {
+    pub fn new(providers: Vec<AuthProvider>) -> Self {
+        Self {
+            providers: Arc::new(providers),
+        }
+    }
+
+    /// Load every provider configured in the environment, skipping misconfigured ones
+    pub async fn from_env(http: &reqwest::Client) -> Self {
+        let mut providers = Vec::new();
+
+        for kind in ProviderKind::ALL {
+            match provider_from_env(kind, http).await {
+                Ok(Some(provider)) => {
+                    info!(provider = kind.id(), "Login provider enabled");
+                    providers.push(provider);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    error!(provider = kind.id(), error = %e, "Invalid login provider configuration")
+                }
+            }
+        }
+
+        if providers.is_empty() {
+            warn!("No login providers are configured");
+        }
+
+        Self::new(providers)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&AuthProvider> {
+        self.providers.iter().find(|p| p.id() == id)
+    }
+
+    pub fn list(&self) -> Vec<ProviderInfo> {
+        self.providers.iter().map(AuthProvider::info).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,159 @@
+use super::*;
+use serde_json::json;
+
+// ==================== user parsing tests ====================
+
+#[test]
+fn parse_user_reads_github_and_gitea_users() {
+    let value = json!({
+        "id": 583231,
+        "login": "octocat",
+        "avatar_url": "https://avatars.githubusercontent.com/u/583231",
+        "email": null
+    });
+
+    for kind in [ProviderKind::Github, ProviderKind::Gitea] {
+        assert_eq!(
+            parse_user(kind, &value),
+            Some(ProviderUser {
+                subject: "583231".to_string(),
+                username: "octocat".to_string(),
+                avatar_url: "https://avatars.githubusercontent.com/u/583231".to_string(),
+                email: None,
+            })
+        );
+    }
+}
+
+#[test]
+fn parse_user_reads_gitlab_users() {
+    let value = json!({
+        "id": 42,
+        "username": "tanuki",
+        "avatar_url": "https://gitlab.com/uploads/tanuki.png",
+        "email": "",
+        "public_email": "tanuki@example.com"
+    });
+
+    let user = parse_user(ProviderKind::Gitlab, &value).unwrap();
+    assert_eq!(user.subject, "42");
+    assert_eq!(user.username, "tanuki");
+    assert_eq!(user.email.as_deref(), Some("tanuki@example.com"));
+}
+
+#[test]
+fn parse_user_reads_oidc_claims() {
+    let value = json!({
+        "sub": "0b7c1e5e-2f1a-4d3c",
+        "preferred_username": "jdoe",
+        "picture": "https://idp.example.com/jdoe.png",
+        "email": "jdoe@example.com",
+        "email_verified": true
+    });
+
+    let user = parse_user(ProviderKind::Oidc, &value).unwrap();
+    assert_eq!(user.subject, "0b7c1e5e-2f1a-4d3c");
+    assert_eq!(user.username, "jdoe");
+    assert_eq!(user.email.as_deref(), Some("jdoe@example.com"));
+}
+
+#[test]
+fn parse_user_falls_back_for_sparse_oidc_claims() {
+    let value = json!({ "sub": "abc123", "email": "jane@example.com" });
+    let user = parse_user(ProviderKind::Oidc, &value).unwrap();
+    assert_eq!(user.username, "jane");
+    assert_eq!(user.avatar_url, "");
+
+    let unverified =
+        json!({ "sub": "abc123", "email": "jane@example.com", "email_verified": false });
+    let user = parse_user(ProviderKind::Oidc, &unverified).unwrap();
+    assert_eq!(user.email, None);
+    assert_eq!(user.username, "user-abc123");
+}
+
+#[test]
+fn parse_user_requires_subject_and_username() {
+    assert_eq!(
+        parse_user(ProviderKind::Github, &json!({ "login": "x" })),
+        None
+    );
+    assert_eq!(parse_user(ProviderKind::Gitlab, &json!({ "id": 1 })), None);
+    assert_eq!(
+        parse_user(ProviderKind::Oidc, &json!({ "name": "x" })),
+        None
+    );
+}
+
+#[test]
+fn parse_user_drops_oversized_fields() {
+    let value = json!({
+        "id": 1,
+        "login": "a".repeat(150),
+        "avatar_url": format!("https://example.com/{}", "a".repeat(200)),
+        "email": "not-an-email"
+    });
+
+    let user = parse_user(ProviderKind::Github, &value).unwrap();
+    assert_eq!(user.username.len(), 100);
+    assert_eq!(user.avatar_url, "");
+    assert_eq!(user.email, None);
+}
+
+// ==================== callback URL tests ====================
+
+#[test]
+fn callback_url_follows_github_redirect_url() {
+    assert_eq!(
+        callback_url("http://localhost:3000/auth/github/callback", "gitlab"),
+        "http://localhost:3000/auth/gitlab/callback"
+    );
+    assert_eq!(
+        callback_url("https://api.example.com/auth/github/callback/", "github"),
+        "https://api.example.com/auth/github/callback"
+    );
+    assert_eq!(
+        callback_url("https://api.example.com/auth", "oidc"),
+        "https://api.example.com/auth/oidc/callback"
+    );
+}
+
+// ==================== provider tests ====================
+
+fn provider(kind: ProviderKind) -> AuthProvider {
+    AuthProvider::new(
+        kind,
+        "Forgejo".to_string(),
+        "client".to_string(),
+        "secret".to_string(),
+        ProviderEndpoints::under(
+            "https://git.example.com/",
+            "/login/oauth/authorize",
+            "/login/oauth/access_token",
+            "/api/v1/user",
+        ),
+        "http://localhost:3000/auth/gitea/callback".to_string(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn authorize_url_includes_state_and_scopes() {
+    let (url, state) = provider(ProviderKind::Gitea).authorize_url();
+    assert!(url.starts_with("https://git.example.com/login/oauth/authorize?"));
+    assert!(url.contains(&format!("state={state}")));
+    assert!(url.contains("scope=read%3Auser"));
+    assert!(url.contains("redirect_uri=http%3A%2F%2Flocalhost%3A3000%2Fauth%2Fgitea%2Fcallback"));
+}
+
+#[test]
+fn providers_are_found_by_id() {
+    let providers = AuthProviders::new(vec![provider(ProviderKind::Gitea)]);
+    assert!(providers.get("gitea").is_some());
+    assert!(providers.get("github").is_none());
+
+    let list = providers.list();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].id, "gitea");
+    assert_eq!(list[0].name, "Forgejo");
+    assert!(AuthProviders::default().list().is_empty());
+}
@@ -1,7 +1,9 @@
 use crate::db::connection::DbPool;
-use crate::models::session::Session;
+use crate::models::role::Role;
+use crate::models::session::{LAST_SEEN_INTERVAL_MINUTES, Session};
 use crate::models::user::User;
 use crate::utils::env::is_production_env;
+use axum::http::Method;
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -9,7 +11,7 @@ use tower_cookies::cookie::SameSite;
 use tower_cookies::{Cookie, Cookies};
 use uuid::Uuid;
 
-use crate::schema::{sessions, users};
+use crate::schema::{roles, sessions, users};
 use crate::utils::instrumented;
 
 pub const SESSION_COOKIE_NAME: &str = "rustytime_session";
@@ -17,24 +19,50 @@ pub const SESSION_COOKIE_NAME: &str = "rustytime_session";
 pub struct SessionData {
     pub id: Uuid,
     pub user_id: i32,
-    pub github_user_id: i64,
     pub expires_at: DateTime<Utc>,
     pub impersonated_by: Option<i32>,
+    pub last_seen_at: DateTime<Utc>,
+    pub impersonation_write: bool,
+}
+
+impl SessionData {
+    /// The user who signed in with this session, the admin when impersonating
+    pub fn owner_id(&self) -> i32 {
+        self.impersonated_by.unwrap_or(self.user_id)
+    }
 }
 
 pub struct SessionManager;
 
 #[derive(Debug, Clone)]
 pub struct ResolvedSession {
-    #[allow(dead_code)]
     pub session: SessionData,
     pub user: User,
-    pub impersonator: Option<User>,
+    pub role: Role,
+    /// The admin behind an impersonated session, with their own role
+    pub impersonator: Option<(User, Role)>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ImpersonationContext {
     pub admin: User,
+    pub admin_role: Role,
+    /// Writes are only let through in the owner's explicit write mode
+    pub write: bool,
+}
+
+impl ImpersonationContext {
+    /// Header set on every response served to an impersonating admin
+    pub const HEADER: &'static str = "x-rustytime-impersonation";
+
+    /// Reads are always allowed, anything else only in write mode
+    pub fn allows(&self, method: &Method) -> bool {
+        self.write || matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+    }
+
+    pub fn mode(&self) -> &'static str {
+        if self.write { "write" } else { "read-only" }
+    }
 }
 
 impl SessionManager {
@@ -94,9 +122,10 @@ impl SessionManager {
         Ok(session.map(|s| SessionData {
             id: s.id,
             user_id: s.user_id,
-            github_user_id: s.github_user_id,
             expires_at: s.expires_at,
             impersonated_by: s.impersonated_by,
+            last_seen_at: s.last_seen_at,
+            impersonation_write: s.impersonation_write,
         }))
     }
 
@@ -148,20 +177,32 @@ impl SessionManager {
             .get()
             .map_err(|_| diesel::result::Error::BrokenTransactionManager)?;
 
+        if session_data.last_seen_at
+            < Utc::now() - chrono::Duration::minutes(LAST_SEEN_INTERVAL_MINUTES)
+        {
+            Session::touch(&mut conn, session_data.id)?;
+        }
+
         let user = instrumented::first("Session::resolve_user", || {
             users::table
-                .find(session_data.user_id)
-                .first::<User>(&mut conn)
+                .inner_join(roles::table)
+                .filter(users::id.eq(session_data.user_id))
+                .select((User::as_select(), Role::as_select()))
+                .first::<(User, Role)>(&mut conn)
         })
         .optional()?;
 
-        let Some(user) = user else {
+        let Some((user, role)) = user else {
             return Ok(None);
         };
 
         let impersonator = if let Some(admin_id) = session_data.impersonated_by {
             instrumented::first("Session::resolve_impersonator", || {
-                users::table.find(admin_id).first::<User>(&mut conn)
+                users::table
+                    .inner_join(roles::table)
+                    .filter(users::id.eq(admin_id))
+                    .select((User::as_select(), Role::as_select()))
+                    .first::<(User, Role)>(&mut conn)
             })
             .optional()?
         } else {
@@ -171,6 +212,7 @@ impl SessionManager {
         Ok(Some(ResolvedSession {
             session: session_data,
             user,
+            role,
             impersonator,
         }))
     }
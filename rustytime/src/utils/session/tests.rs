@@ -111,3 +111,19 @@ fn remove_session_cookie_has_correct_path() {
     let cookie = SessionManager::remove_session_cookie();
     assert_eq!(cookie.path(), Some("/"));
 }
+
+#[test]
+fn session_owner_is_the_impersonating_admin() {
+    let mut session = SessionData {
+        id: Uuid::new_v4(),
+        user_id: 7,
+        expires_at: Utc::now(),
+        impersonated_by: None,
+        last_seen_at: Utc::now(),
+        impersonation_write: false,
+    };
+    assert_eq!(session.owner_id(), 7);
+
+    session.impersonated_by = Some(1);
+    assert_eq!(session.owner_id(), 1);
+}
@@ -32,6 +32,11 @@ pub enum TimeFormat {
     Full,
 }
 
+/// Short duration like `3h 12m`, as shown on badges, cards and notifications
+pub fn format_hour_minute(seconds: i64) -> String {
+    human_readable_duration(seconds, TimeFormat::HourMinute).human_readable
+}
+
 pub fn human_readable_duration(seconds: i64, format: TimeFormat) -> Time {
     let minutes = seconds / 60;
     let hours = minutes / 60;
@@ -21,6 +21,12 @@ fn formats_duration_for_hour_minute_display() {
     assert_eq!(time_obj.human_readable, "25h 1m");
 }
 
+#[test]
+fn formats_hour_minute_shorthand() {
+    assert_eq!(format_hour_minute(SECONDS), "25h 1m");
+    assert_eq!(format_hour_minute(45), "45s");
+}
+
 #[test]
 fn formats_duration_zero_seconds() {
     let time_obj = human_readable_duration(0, TimeFormat::Full);
@@ -5,14 +5,16 @@ use axum::extract::connect_info::IntoMakeServiceWithConnectInfo;
 use axum_prometheus::metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use axum_test::{TestServer, TestServerConfig};
 use diesel::prelude::*;
-use oauth2::{AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl, basic::BasicClient};
 use rustytime_server::{
     db::connection::DbPool,
+    models::role::Role,
     models::user::{NewUser, User},
     routes::create_app_router,
     state::AppState,
     utils::cache::AppCache,
+    utils::local_auth::LocalAuth,
     utils::metrics::MetricsTracker,
+    utils::oauth::{AuthProvider, AuthProviders, ProviderEndpoints, ProviderKind},
 };
 use std::sync::{Arc, OnceLock};
 use tokio::sync::RwLock;
@@ -108,28 +110,23 @@ pub fn create_test_pool(database_url: &str) -> DbPool {
         .expect("Failed to create test database pool")
 }
 
-/// Create a mock GitHub OAuth client for testing
-pub fn create_mock_github_client() -> BasicClient<
-    oauth2::EndpointSet,
-    oauth2::EndpointNotSet,
-    oauth2::EndpointNotSet,
-    oauth2::EndpointNotSet,
-    oauth2::EndpointSet,
-> {
-    let client_id = ClientId::new("test-client-id".to_string());
-    let client_secret = ClientSecret::new("test-client-secret".to_string());
-    let auth_url = AuthUrl::new("https://github.com/login/oauth/authorize".to_string())
-        .expect("Invalid authorization endpoint URL");
-    let token_url = TokenUrl::new("https://github.com/login/oauth/access_token".to_string())
-        .expect("Invalid token endpoint URL");
-    let redirect_url = RedirectUrl::new("http://localhost:3000/auth/github/callback".to_string())
-        .expect("Invalid redirect URL");
-
-    BasicClient::new(client_id)
-        .set_client_secret(client_secret)
-        .set_auth_uri(auth_url)
-        .set_token_uri(token_url)
-        .set_redirect_uri(redirect_url)
+/// Create mock login providers for testing
+pub fn create_mock_auth_providers() -> AuthProviders {
+    let github = AuthProvider::new(
+        ProviderKind::Github,
+        "GitHub".to_string(),
+        "test-client-id".to_string(),
+        "test-client-secret".to_string(),
+        ProviderEndpoints {
+            auth_url: "https://github.com/login/oauth/authorize".to_string(),
+            token_url: "https://github.com/login/oauth/access_token".to_string(),
+            userinfo_url: "https://api.github.com/user".to_string(),
+        },
+        "http://localhost:3000/auth/github/callback".to_string(),
+    )
+    .expect("Invalid mock provider");
+
+    AuthProviders::new(vec![github])
 }
 
 /// Test server wrapper
@@ -177,12 +174,13 @@ impl TestApp {
         let github_id = 999_000_000 + (timestamp % 1_000_000_000);
 
         let new_user = NewUser {
-            github_id,
+            github_id: Some(github_id),
             name: name.to_string(),
             avatar_url: format!("https://example.com/avatar/{}", github_id),
-            admin_level: 0,
+            role: Role::USER.to_string(),
             is_banned: false,
             timezone: "UTC".to_string(),
+            email: None,
         };
 
         diesel::insert_into(users::table)
@@ -191,6 +189,19 @@ impl TestApp {
             .expect("Failed to create test user")
     }
 
+    /// Register a local account and keep its session cookie for the following requests
+    pub async fn sign_up(&self, username: &str, password: &str) -> i32 {
+        let response = self
+            .server
+            .post("/auth/local/register")
+            .json(&serde_json::json!({ "username": username, "password": password }))
+            .await;
+        response.assert_status_ok();
+        response.json::<serde_json::Value>()["id"]
+            .as_i64()
+            .expect("Response should contain the user id") as i32
+    }
+
     /// Delete a test user and all their data
     pub fn cleanup_test_user(&self, user_id: i32) {
         use rustytime_server::schema::{heartbeats, projects, users};
@@ -217,14 +228,19 @@ fn create_test_router_full(
 ) -> IntoMakeServiceWithConnectInfo<Router, std::net::SocketAddr> {
     use axum::Extension;
 
-    let github_client = create_mock_github_client();
-
     let app_state = AppState {
         db_pool,
-        github_client,
+        auth_providers: create_mock_auth_providers(),
+        local_auth: LocalAuth {
+            enabled: true,
+            registration: true,
+            single_user: None,
+        },
         http_client: reqwest::Client::new(),
         metrics: MetricsTracker::new(),
         import_store: Arc::new(RwLock::new(None)),
+        history_store: Arc::new(RwLock::new(None)),
+        webhook_store: Arc::new(RwLock::new(None)),
         cache: AppCache::new(),
     };
 
@@ -49,10 +49,671 @@ mod auth_tests {
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/auth/github/verify").await;
+        let response = app.server.get("/auth/verify").await;
 
         response.assert_status(StatusCode::BAD_REQUEST);
     }
+
+    #[tokio::test]
+    async fn test_auth_providers_lists_configured_providers() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/auth/providers").await;
+
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["providers"][0]["id"], "github");
+        assert_eq!(body["providers"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_login_with_unknown_provider_returns_not_found() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/auth/gitlab/login").await;
+
+        response.assert_status(StatusCode::NOT_FOUND);
+    }
+}
+
+#[cfg(test)]
+mod local_auth_tests {
+    use super::*;
+    use rustytime_server::models::role::Role;
+    use rustytime_server::utils::local_auth::{LocalAuth, SingleUser};
+
+    fn unique_username(prefix: &str) -> String {
+        format!(
+            "{prefix}_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        )
+    }
+
+    #[tokio::test]
+    async fn test_register_starts_a_session() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("local");
+        let user_id = app.sign_up(&username, "hunter2hunter2").await;
+
+        let response = app.server.get("/auth/verify").await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["valid"], true);
+        assert_eq!(body["user"]["id"], user_id);
+        assert_eq!(body["user"]["username"], username.as_str());
+
+        app.server.get("/page/settings").await.assert_status_ok();
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_login_checks_the_password() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("local");
+        let user_id = app.sign_up(&username, "hunter2hunter2").await;
+        app.server.post("/auth/logout").await.assert_status_ok();
+
+        app.server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({ "username": username, "password": "wrong-password" }))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        app.server
+            .get("/page/settings")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        // usernames are case insensitive
+        let response = app
+            .server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({
+                "username": username.to_uppercase(),
+                "password": "hunter2hunter2"
+            }))
+            .await;
+        response.assert_status_ok();
+        app.server.get("/page/settings").await.assert_status_ok();
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_taken_and_invalid_usernames() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("local");
+        let user_id = app.sign_up(&username, "hunter2hunter2").await;
+
+        app.server
+            .post("/auth/local/register")
+            .json(&serde_json::json!({ "username": username, "password": "another-password" }))
+            .await
+            .assert_status(StatusCode::CONFLICT);
+        app.server
+            .post("/auth/local/register")
+            .json(&serde_json::json!({ "username": "bad name", "password": "hunter2hunter2" }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        app.server
+            .post("/auth/local/register")
+            .json(&serde_json::json!({ "username": unique_username("local"), "password": "short" }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_single_user_owner_is_provisioned_once() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("owner");
+        let local_auth = |password: &str| LocalAuth {
+            enabled: true,
+            registration: false,
+            single_user: Some(SingleUser {
+                username: username.clone(),
+                password: password.to_string(),
+            }),
+        };
+
+        let owner = local_auth("first-password")
+            .provision_single_user(&app.db_pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.role, Role::OWNER);
+
+        // provisioning again keeps the account and takes the new password
+        let again = local_auth("second-password")
+            .provision_single_user(&app.db_pool)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(again.id, owner.id);
+
+        app.server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({ "username": username, "password": "first-password" }))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        app.server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({ "username": username, "password": "second-password" }))
+            .await
+            .assert_status_ok();
+
+        app.cleanup_test_user(owner.id);
+    }
+
+    #[tokio::test]
+    async fn test_local_accounts_set_their_own_email() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user_id = app
+            .sign_up(&unique_username("local"), "hunter2hunter2")
+            .await;
+
+        let settings: serde_json::Value = app.server.get("/page/settings").await.json();
+        assert_eq!(settings["email"], serde_json::Value::Null);
+        assert_eq!(settings["email_editable"], true);
+
+        app.server
+            .put("/data/settings")
+            .json(&serde_json::json!({ "weekly_digest": true }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        app.server
+            .put("/data/settings")
+            .json(&serde_json::json!({ "email": "not an email" }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        app.server
+            .put("/data/settings")
+            .json(&serde_json::json!({ "email": "me@example.com", "weekly_digest": true }))
+            .await
+            .assert_status_ok();
+
+        let settings: serde_json::Value = app.server.get("/page/settings").await.json();
+        assert_eq!(settings["email"], "me@example.com");
+        assert_eq!(settings["weekly_digest"], true);
+
+        app.cleanup_test_user(user_id);
+    }
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+    use rustytime_server::models::user::User;
+
+    fn unique_username(prefix: &str) -> String {
+        format!(
+            "{prefix}_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        )
+    }
+
+    async fn list_sessions(app: &TestApp) -> Vec<serde_json::Value> {
+        let response = app.server.get("/data/sessions").await;
+        response.assert_status_ok();
+        response.json::<serde_json::Value>()["sessions"]
+            .as_array()
+            .expect("Response should contain sessions")
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_independent_and_revocable() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let laptop = TestApp::new().await;
+        let phone = TestApp::new().await;
+        let username = unique_username("sessions");
+        let user_id = laptop.sign_up(&username, "hunter2hunter2").await;
+        phone
+            .server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({ "username": username, "password": "hunter2hunter2" }))
+            .await
+            .assert_status_ok();
+
+        // logging in on a second device keeps the first one signed in
+        let sessions = list_sessions(&laptop).await;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions.iter().filter(|s| s["current"] == true).count(), 1);
+        assert!(sessions.iter().all(|s| s["impersonating"].is_null()));
+
+        let phone_session = list_sessions(&phone)
+            .await
+            .into_iter()
+            .find(|s| s["current"] == true)
+            .expect("Phone should see its own session");
+        laptop
+            .server
+            .delete(&format!(
+                "/data/sessions/{}",
+                phone_session["id"].as_str().unwrap()
+            ))
+            .await
+            .assert_status_ok();
+        phone
+            .server
+            .get("/data/sessions")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(list_sessions(&laptop).await.len(), 1);
+
+        // revoking an unknown session is a 404
+        laptop
+            .server
+            .delete(&format!("/data/sessions/{}", uuid::Uuid::new_v4()))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+
+        laptop.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_revoke_others_keeps_the_current_session() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let laptop = TestApp::new().await;
+        let phone = TestApp::new().await;
+        let username = unique_username("sessions");
+        let user_id = laptop.sign_up(&username, "hunter2hunter2").await;
+        phone
+            .server
+            .post("/auth/local/login")
+            .json(&serde_json::json!({ "username": username, "password": "hunter2hunter2" }))
+            .await
+            .assert_status_ok();
+
+        let response = laptop.server.post("/data/sessions/revoke-others").await;
+        response.assert_status_ok();
+        assert_eq!(response.json::<serde_json::Value>()["revoked"], 1);
+
+        laptop.server.get("/page/settings").await.assert_status_ok();
+        phone
+            .server
+            .get("/page/settings")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        laptop.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_sessions_belong_to_the_admin() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let admin_app = TestApp::new().await;
+        let target_app = TestApp::new().await;
+        let admin_id = admin_app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target_name = unique_username("target");
+        let target_id = target_app.sign_up(&target_name, "hunter2hunter2").await;
+
+        let mut conn = admin_app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+
+        admin_app
+            .server
+            .post(&format!("/admin/impersonate/{target_id}"))
+            .await
+            .assert_status_ok();
+
+        let admin_sessions = list_sessions(&admin_app).await;
+        assert_eq!(admin_sessions.len(), 1);
+        assert_eq!(admin_sessions[0]["current"], true);
+        assert_eq!(admin_sessions[0]["impersonating"], target_name.as_str());
+
+        // the target only sees the sessions they signed in with
+        let target_sessions = list_sessions(&target_app).await;
+        assert_eq!(target_sessions.len(), 1);
+        assert!(target_sessions[0]["impersonating"].is_null());
+
+        admin_app.cleanup_test_user(target_id);
+        admin_app.cleanup_test_user(admin_id);
+    }
+
+    #[tokio::test]
+    async fn test_name_taken_matches_wildcards_literally() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("name_taken");
+        let user = app.create_test_user(&username);
+        let mut conn = app.db_pool.get().unwrap();
+
+        assert!(User::name_taken(&mut conn, &username.to_uppercase(), None).unwrap());
+        assert!(!User::name_taken(&mut conn, &username, Some(user.id)).unwrap());
+
+        // an underscore only clashes with an underscore
+        let similar = username.replacen('k', "_", 1);
+        assert!(!User::name_taken(&mut conn, &similar, None).unwrap());
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_public_profiles_are_found_by_exact_name() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("Profile_Lookup");
+        let user = app.create_test_user(&username);
+        let mut conn = app.db_pool.get().unwrap();
+
+        let found = User::find_public_by_name(&mut conn, &username.to_lowercase()).unwrap();
+        assert_eq!(found.map(|u| u.id), Some(user.id));
+
+        // wildcards in the requested name are matched literally
+        let wildcard = format!("{}%", &username[..username.len() - 2]);
+        assert!(
+            User::find_public_by_name(&mut conn, &wildcard)
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            User::find_public_by_name(&mut conn, &username.replacen('L', "_", 1))
+                .unwrap()
+                .is_none()
+        );
+
+        app.cleanup_test_user(user.id);
+    }
+}
+
+#[cfg(test)]
+mod csrf_tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use rustytime_server::models::user::User;
+
+    fn unique_username(prefix: &str) -> String {
+        format!(
+            "{prefix}_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cross_site_writes_with_the_session_cookie_are_blocked() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user_id = app
+            .sign_up(&unique_username("csrf"), "hunter2hunter2")
+            .await;
+
+        app.server
+            .post("/auth/logout")
+            .add_header("origin", "https://evil.example")
+            .add_header("sec-fetch-site", "cross-site")
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        app.server
+            .post("/auth/logout")
+            .add_header("referer", "https://evil.example/page")
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // the session survived both attempts
+        app.server.get("/page/settings").await.assert_status_ok();
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_cross_site_local_logins_are_blocked_without_a_cookie() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let username = unique_username("csrf");
+        let user_id = app.sign_up(&username, "hunter2hunter2").await;
+        app.server.post("/auth/logout").await.assert_status_ok();
+
+        let credentials = serde_json::json!({ "username": username, "password": "hunter2hunter2" });
+        app.server
+            .post("/auth/local/login")
+            .add_header("origin", "https://evil.example")
+            .add_header("sec-fetch-site", "cross-site")
+            .json(&credentials)
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        app.server
+            .post("/auth/local/register")
+            .add_header("origin", "https://evil.example")
+            .json(&serde_json::json!({
+                "username": unique_username("csrf"),
+                "password": "hunter2hunter2"
+            }))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // no session was started by either attempt
+        app.server
+            .get("/page/settings")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        app.server
+            .post("/auth/local/login")
+            .add_header("sec-fetch-site", "same-origin")
+            .json(&credentials)
+            .await
+            .assert_status_ok();
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_writes_from_the_frontend_are_allowed() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user_id = app
+            .sign_up(&unique_username("csrf"), "hunter2hunter2")
+            .await;
+        let frontend_url =
+            std::env::var("FRONTEND_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+
+        app.server
+            .post("/data/sessions/revoke-others")
+            .add_header("origin", frontend_url.as_str())
+            .add_header("sec-fetch-site", "same-site")
+            .await
+            .assert_status_ok();
+        app.server
+            .post("/data/sessions/revoke-others")
+            .add_header("sec-fetch-site", "same-origin")
+            .await
+            .assert_status_ok();
+
+        app.cleanup_test_user(user_id);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_requests_are_not_checked() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user(&unique_username("csrf"));
+
+        // no session cookie, so a cross-site origin does not matter
+        app.server
+            .post("/api/v1/users/current/heartbeats")
+            .add_header("origin", "https://evil.example")
+            .add_header("sec-fetch-site", "cross-site")
+            .add_header(
+                "authorization",
+                format!("Basic {}", STANDARD.encode(user.api_key.to_string())).as_str(),
+            )
+            .json(&mock_heartbeat_payload())
+            .await
+            .assert_status_success();
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_is_no_longer_a_get() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let admin_id = app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+
+        app.server
+            .get(&format!("/admin/impersonate/{}", target.id))
+            .await
+            .assert_status(StatusCode::METHOD_NOT_ALLOWED);
+        app.server
+            .post(&format!("/admin/impersonate/{}", target.id))
+            .add_header("origin", "https://evil.example")
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(admin_id);
+    }
+}
+
+#[cfg(test)]
+mod identity_tests {
+    use super::*;
+    use rustytime_server::models::user_identity::{SignInOutcome, UserIdentity};
+    use rustytime_server::utils::oauth::{ProviderKind, ProviderUser};
+
+    fn account(subject: &str, username: &str) -> ProviderUser {
+        ProviderUser {
+            subject: subject.to_string(),
+            username: username.to_string(),
+            avatar_url: String::new(),
+            email: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_in_creates_user_and_links_other_providers() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let mut conn = app.db_pool.get().unwrap();
+        let subject = uuid::Uuid::new_v4().to_string();
+
+        let SignInOutcome::SignedIn(user) = UserIdentity::sign_in(
+            &mut conn,
+            ProviderKind::Oidc,
+            &account(&subject, "identity_test_user"),
+            None,
+            true,
+        )
+        .unwrap() else {
+            panic!("new account should sign in");
+        };
+        assert_eq!(user.github_id, None);
+
+        // signing in again finds the same user
+        let SignInOutcome::SignedIn(again) = UserIdentity::sign_in(
+            &mut conn,
+            ProviderKind::Oidc,
+            &account(&subject, "identity_test_user"),
+            None,
+            true,
+        )
+        .unwrap() else {
+            panic!("known account should sign in");
+        };
+        assert_eq!(again.id, user.id);
+
+        // a second provider account is linked to the signed in user
+        let gitlab_subject = format!("gl-{subject}");
+        assert!(matches!(
+            UserIdentity::sign_in(
+                &mut conn,
+                ProviderKind::Gitlab,
+                &account(&gitlab_subject, "identity_test_user"),
+                Some(user.id),
+                true,
+            )
+            .unwrap(),
+            SignInOutcome::Linked
+        ));
+        assert_eq!(
+            UserIdentity::list_for_user(&mut conn, user.id)
+                .unwrap()
+                .len(),
+            2
+        );
+
+        // but it cannot be linked to anyone else
+        let other = app.create_test_user("identity_test_other");
+        assert!(matches!(
+            UserIdentity::sign_in(
+                &mut conn,
+                ProviderKind::Gitlab,
+                &account(&gitlab_subject, "identity_test_user"),
+                Some(other.id),
+                true,
+            )
+            .unwrap(),
+            SignInOutcome::AlreadyLinked
+        ));
+
+        // new accounts are refused when sign up is closed
+        assert!(matches!(
+            UserIdentity::sign_in(
+                &mut conn,
+                ProviderKind::Gitea,
+                &account(&subject, "identity_test_new"),
+                None,
+                false,
+            )
+            .unwrap(),
+            SignInOutcome::SignUpClosed
+        ));
+
+        app.cleanup_test_user(other.id);
+        app.cleanup_test_user(user.id);
+    }
 }
 
 #[cfg(test)]
@@ -66,174 +727,929 @@ mod api_v1_tests {
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let payload = mock_heartbeat_payload();
-
-        let response = app
-            .server
-            .post("/api/v1/users/current/heartbeats")
-            .json(&payload)
+        let payload = mock_heartbeat_payload();
+
+        let response = app
+            .server
+            .post("/api/v1/users/current/heartbeats")
+            .json(&payload)
+            .await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_with_invalid_api_key_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let payload = mock_heartbeat_payload();
+
+        let response = app
+            .server
+            .post("/api/v1/users/current/heartbeats")
+            .add_header(header::AUTHORIZATION, format!("Basic {}", INVALID_API_KEY))
+            .json(&payload)
+            .await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_statusbar_today_without_auth_returns_error() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+
+        let response = app
+            .server
+            .get("/api/v1/users/current/statusbar/today")
+            .await;
+
+        assert!(
+            response.status_code() == StatusCode::UNAUTHORIZED
+                || response.status_code() == StatusCode::BAD_REQUEST,
+            "Expected auth error, got {:?}",
+            response.status_code()
+        );
+    }
+}
+
+#[cfg(test)]
+mod protected_routes_tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dashboard_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/dashboard").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_settings_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/settings").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_projects_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/projects").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_project_merge_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app
+            .server
+            .post("/data/projects/1/merge")
+            .json(&serde_json::json!({ "target_id": 2 }))
+            .await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_goals_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/data/goals").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_leaderboard_without_auth_returns_authorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/leaderboard").await;
+
+        response.assert_status(StatusCode::OK);
+    }
+}
+
+#[cfg(test)]
+mod admin_routes_tests {
+    use super::*;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use rustytime_server::models::role::Role;
+    use rustytime_server::models::user::User;
+
+    fn unique_username(prefix: &str) -> String {
+        format!(
+            "{prefix}_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        )
+    }
+
+    #[tokio::test]
+    async fn test_admin_page_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/admin").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_admin_impersonate_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.post("/admin/impersonate/1").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_without_auth_returns_unauthorized() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let response = app.server.get("/page/audit_log").await;
+
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_is_recorded_in_the_audit_log() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let owner_name = unique_username("owner");
+        let owner_id = app.sign_up(&owner_name, "hunter2hunter2").await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, owner_id, Role::OWNER).unwrap();
+
+        app.server
+            .post(&format!("/admin/impersonate/{}?write=true", target.id))
+            .await
+            .assert_status_ok();
+        app.server
+            .post("/data/sessions/revoke-others")
+            .await
+            .assert_status_ok();
+
+        // admin pages check the impersonating owner's role, not the impersonated user's
+        app.server.get("/page/audit_log").await.assert_status_ok();
+
+        app.server
+            .post(&format!("/admin/impersonate/{owner_id}"))
+            .await
+            .assert_status_ok();
+
+        let response = app
+            .server
+            .get(&format!("/page/audit_log?actor_id={owner_id}&limit=100"))
+            .await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        let actions: Vec<&str> = body["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|entry| entry["action"].as_str().unwrap())
+            .collect();
+
+        assert_eq!(actions.first(), Some(&"impersonation.stop"));
+        assert!(actions.contains(&"impersonation.start"));
+        let request = body["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["metadata"]["path"] == "/data/sessions/revoke-others")
+            .expect("impersonated write was not recorded");
+        assert_eq!(request["action"], "impersonation.request");
+        assert_eq!(request["effective_user_id"], target.id);
+        assert_eq!(request["actor_name"], owner_name.as_str());
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(owner_id);
+    }
+
+    #[tokio::test]
+    async fn test_admin_actions_while_impersonating_name_the_admin() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let owner_id = app
+            .sign_up(&unique_username("owner"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("target"));
+        let member = app.create_test_user(&unique_username("member"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, owner_id, Role::OWNER).unwrap();
+
+        app.server
+            .post(&format!("/admin/impersonate/{}?write=true", target.id))
+            .await
+            .assert_status_ok();
+        app.server
+            .put(&format!("/admin/role/{}/admin", member.id))
+            .await
+            .assert_status_ok();
+        app.server.get("/page/imports").await.assert_status_ok();
+        app.server
+            .post(&format!("/admin/impersonate/{owner_id}"))
+            .await
+            .assert_status_ok();
+
+        let response = app
+            .server
+            .get(&format!(
+                "/page/audit_log?action=user.role&target=user:{}",
+                member.id
+            ))
+            .await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        let entry = &body["entries"][0];
+        assert_eq!(entry["actor_id"], owner_id);
+        assert_eq!(entry["effective_user_id"], target.id);
+        assert_eq!(entry["metadata"]["to"], "admin");
+
+        let response = app
+            .server
+            .get(&format!(
+                "/page/audit_log?action=admin.imports_viewed&effective_user_id={}",
+                target.id
+            ))
+            .await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        let entry = &body["entries"][0];
+        assert_eq!(entry["actor_id"], owner_id);
+        assert_eq!(entry["effective_user_id"], target.id);
+
+        app.cleanup_test_user(member.id);
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(owner_id);
+    }
+
+    #[tokio::test]
+    async fn test_impersonating_owner_cannot_create_global_webhooks() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let owner_id = app
+            .sign_up(&unique_username("owner"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, owner_id, Role::OWNER).unwrap();
+
+        app.server
+            .post(&format!("/admin/impersonate/{}?write=true", target.id))
+            .await
+            .assert_status_ok();
+
+        let response = app.server.get("/data/webhooks").await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["can_create_global"], false);
+
+        let response = app
+            .server
+            .post("/data/webhooks")
+            .json(&serde_json::json!({
+                "url": "https://example.com/hook",
+                "events": ["goal.reached"],
+                "global": true,
+            }))
+            .await;
+        response.assert_status(StatusCode::FORBIDDEN);
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(owner_id);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_is_read_only_by_default() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let admin_id = app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+
+        // write mode needs the impersonate_write permission
+        app.server
+            .post(&format!("/admin/impersonate/{}?write=true", target.id))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        app.server
+            .post(&format!("/admin/impersonate/{}", target.id))
+            .await
+            .assert_status_ok();
+
+        let response = app.server.get("/page/settings").await;
+        response.assert_status_ok();
+        assert_eq!(response.header("x-rustytime-impersonation"), "read-only");
+
+        // the route and the handler both check the admin's role
+        app.server.get("/page/admin").await.assert_status_ok();
+        app.server
+            .get(&format!("/page/admin/users/{}", target.id))
+            .await
+            .assert_status_ok();
+
+        app.server
+            .post("/data/sessions/revoke-others")
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // ending impersonation is still allowed
+        let response = app
+            .server
+            .post(&format!("/admin/impersonate/{admin_id}"))
+            .await;
+        response.assert_status_ok();
+        assert!(
+            app.server
+                .get("/page/settings")
+                .await
+                .maybe_header("x-rustytime-impersonation")
+                .is_none()
+        );
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(admin_id);
+    }
+
+    #[tokio::test]
+    async fn test_admin_routes_check_role_permissions() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let admin_id = app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+
+        let verify: serde_json::Value = app.server.get("/auth/verify").await.json();
+        assert_eq!(verify["user"]["role"], "admin");
+        assert!(
+            verify["user"]["permissions"]
+                .as_array()
+                .unwrap()
+                .contains(&serde_json::json!("view_users"))
+        );
+
+        let dashboard = app.server.get("/page/admin").await;
+        dashboard.assert_status_ok();
+        let dashboard: serde_json::Value = dashboard.json();
+        // admins can not see API keys
+        assert!(
+            dashboard["all_users"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(|user| user["api_key"].is_null())
+        );
+
+        for path in ["/page/imports", "/page/audit_log"] {
+            app.server
+                .get(path)
+                .await
+                .assert_status(StatusCode::FORBIDDEN);
+        }
+        app.server
+            .put(&format!("/admin/role/{}/admin", target.id))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        app.server
+            .put(&format!("/admin/ban/{}/true", target.id))
+            .await
+            .assert_status_ok();
+        assert!(
+            User::get_by_id(&mut conn, target.id)
+                .unwrap()
+                .unwrap()
+                .is_banned
+        );
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(admin_id);
+    }
+
+    #[tokio::test]
+    async fn test_owner_can_only_assign_lower_roles() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let owner_id = app
+            .sign_up(&unique_username("owner"), "hunter2hunter2")
             .await;
+        let target = app.create_test_user(&unique_username("target"));
+
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, owner_id, Role::OWNER).unwrap();
+
+        app.server
+            .put(&format!("/admin/role/{}/admin", target.id))
+            .await
+            .assert_status_ok();
+        assert_eq!(
+            User::get_by_id(&mut conn, target.id).unwrap().unwrap().role,
+            "admin"
+        );
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        app.server
+            .put(&format!("/admin/role/{}/owner", target.id))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        app.server
+            .put(&format!("/admin/role/{}/no_such_role", target.id))
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        app.server
+            .put(&format!("/admin/role/{owner_id}/user"))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(owner_id);
     }
 
     #[tokio::test]
-    async fn test_heartbeat_with_invalid_api_key_returns_unauthorized() {
+    async fn test_admin_can_search_and_filter_users() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let payload = mock_heartbeat_payload();
+        let admin_id = app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("search_target"));
 
-        let response = app
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+        User::set_banned(&mut conn, target.id, true).unwrap();
+
+        let ids = |response: serde_json::Value| -> Vec<i64> {
+            response["all_users"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|user| user["id"].as_i64().unwrap())
+                .collect()
+        };
+
+        let by_name: serde_json::Value = app
             .server
-            .post("/api/v1/users/current/heartbeats")
-            .add_header(header::AUTHORIZATION, format!("Basic {}", INVALID_API_KEY))
-            .json(&payload)
-            .await;
+            .get(&format!("/page/admin?search={}", target.name))
+            .await
+            .json();
+        assert_eq!(by_name["matching_users"], 1);
+        assert_eq!(ids(by_name), vec![target.id as i64]);
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        let by_github_id: serde_json::Value = app
+            .server
+            .get(&format!("/page/admin?search={}", target.github_id.unwrap()))
+            .await
+            .json();
+        assert_eq!(ids(by_github_id), vec![target.id as i64]);
+
+        // the target is banned, not an admin, and has never sent a heartbeat
+        for (query, found) in [
+            ("banned=true", true),
+            ("banned=false", false),
+            ("admin=true", false),
+            ("has_imports=false", true),
+            ("active_days=7", false),
+        ] {
+            let response: serde_json::Value = app
+                .server
+                .get(&format!("/page/admin?search={}&{query}", target.name))
+                .await
+                .json();
+            assert_eq!(
+                ids(response).contains(&(target.id as i64)),
+                found,
+                "{query}"
+            );
+        }
+
+        for sort in ["role", "newest", "oldest", "total_time"] {
+            app.server
+                .get(&format!("/page/admin?sort={sort}"))
+                .await
+                .assert_status_ok();
+        }
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(admin_id);
     }
 
     #[tokio::test]
-    async fn test_statusbar_today_without_auth_returns_error() {
+    async fn test_admin_user_detail() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
+        let admin_id = app
+            .sign_up(&unique_username("admin"), "hunter2hunter2")
+            .await;
+        let target = app.create_test_user(&unique_username("detail_target"));
+
+        // the first account on an empty database is the owner
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, admin_id, Role::USER).unwrap();
+        app.server
+            .get(&format!("/page/admin/users/{}", target.id))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        User::set_role(&mut conn, admin_id, "admin").unwrap();
+
+        app.server
+            .post("/api/v1/users/current/heartbeats")
+            .add_header(
+                "authorization",
+                format!("Basic {}", STANDARD.encode(target.api_key.to_string())).as_str(),
+            )
+            .json(&mock_heartbeat_payload())
+            .await
+            .assert_status_success();
 
         let response = app
             .server
-            .get("/api/v1/users/current/statusbar/today")
+            .get(&format!("/page/admin/users/{}", target.id))
             .await;
-
-        assert!(
-            response.status_code() == StatusCode::UNAUTHORIZED
-                || response.status_code() == StatusCode::BAD_REQUEST,
-            "Expected auth error, got {:?}",
-            response.status_code()
-        );
+        response.assert_status_ok();
+        let detail: serde_json::Value = response.json();
+        assert_eq!(detail["user"]["id"], target.id);
+        assert!(detail["user"]["api_key"].is_null());
+        assert_eq!(detail["heartbeats"]["all_time"], 1);
+        assert!(detail["last_heartbeat_at"].is_string());
+        assert_eq!(detail["source_types"][0]["count"], 1);
+        assert_eq!(detail["projects"][0]["name"], "test-project");
+        assert!(detail["import_jobs"].as_array().unwrap().is_empty());
+
+        app.server
+            .get("/page/admin/users/0")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+
+        app.cleanup_test_user(target.id);
+        app.cleanup_test_user(admin_id);
     }
 }
 
 #[cfg(test)]
-mod protected_routes_tests {
+mod docs_tests {
     use super::*;
 
     #[tokio::test]
-    async fn test_dashboard_without_auth_returns_unauthorized() {
+    async fn test_openapi_docs_endpoint() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/page/dashboard").await;
+        let response = app.server.get("/docs/private/api.json").await;
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert!(body.get("openapi").is_some(), "Should return OpenAPI spec");
     }
 
     #[tokio::test]
-    async fn test_settings_without_auth_returns_unauthorized() {
+    async fn test_scalar_docs_page() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/page/settings").await;
+        let response = app.server.get("/docs").await;
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        response.assert_status_ok();
     }
+}
+
+#[cfg(test)]
+mod not_found_tests {
+    use super::*;
 
     #[tokio::test]
-    async fn test_projects_without_auth_returns_unauthorized() {
+    async fn test_unknown_route_returns_not_found() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/page/projects").await;
+        let response = app.server.get("/this/route/does/not/exist").await;
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        response.assert_status(StatusCode::NOT_FOUND);
     }
+}
+
+#[cfg(test)]
+mod badge_tests {
+    use super::*;
+    use axum::http::header;
 
     #[tokio::test]
-    async fn test_leaderboard_without_auth_returns_authorized() {
+    async fn test_badge_for_unknown_user_renders_not_found() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/page/leaderboard").await;
+        let response = app.server.get("/badge/no_such_badge_user/time").await;
 
-        response.assert_status(StatusCode::OK);
+        response.assert_status_ok();
+        assert!(response.text().contains("not found"));
+        assert!(response.headers().contains_key(header::ETAG));
+        assert!(response.headers().contains_key(header::CACHE_CONTROL));
     }
-}
-
-#[cfg(test)]
-mod admin_routes_tests {
-    use super::*;
 
     #[tokio::test]
-    async fn test_admin_page_without_auth_returns_unauthorized() {
+    async fn test_badge_for_user_renders_time() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/page/admin").await;
+        let user = app.create_test_user("test_badge_user");
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        let response = app
+            .server
+            .get("/badge/test_badge_user/time?range=week&label=coded")
+            .await;
+
+        response.assert_status_ok();
+        let svg = response.text();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">coded</text>"));
+        assert!(!svg.contains("not found"));
+
+        app.cleanup_test_user(user.id);
     }
 
     #[tokio::test]
-    async fn test_admin_impersonate_without_auth_returns_unauthorized() {
+    async fn test_stats_card_for_user_renders_card() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/admin/impersonate/1").await;
+        let user = app.create_test_user("test_card_user");
 
-        response.assert_status(StatusCode::UNAUTHORIZED);
+        let response = app
+            .server
+            .get("/card/test_card_user?theme=latte&layout=compact&projects=true")
+            .await;
+
+        response.assert_status_ok();
+        let svg = response.text();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("coding stats"));
+        assert!(response.headers().contains_key(header::ETAG));
+
+        app.cleanup_test_user(user.id);
     }
 }
 
 #[cfg(test)]
-mod docs_tests {
+mod webhook_tests {
     use super::*;
+    use axum::body::Bytes;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use rustytime_server::jobs::webhooks::{build_client, send_webhook};
+    use rustytime_server::models::role::Role;
+    use rustytime_server::models::user::User;
+    use rustytime_server::models::webhook::{
+        NewWebhook, Webhook, WebhookEvent, generate_secret, sign_payload,
+    };
+    use tokio::sync::mpsc;
 
     #[tokio::test]
-    async fn test_openapi_docs_endpoint() {
+    async fn test_webhooks_without_auth_returns_unauthorized() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/docs/private/api.json").await;
+        let response = app.server.get("/data/webhooks").await;
 
-        response.assert_status_ok();
-        let body: serde_json::Value = response.json();
-        assert!(body.get("openapi").is_some(), "Should return OpenAPI spec");
+        response.assert_status(StatusCode::UNAUTHORIZED);
     }
 
     #[tokio::test]
-    async fn test_scalar_docs_page() {
+    async fn test_global_webhooks_stop_when_the_owner_loses_the_permission() {
         let config = TestConfig::default();
         fail_without_db!(config);
 
         let app = TestApp::new().await;
-        let response = app.server.get("/docs").await;
+        let user = app.create_test_user(&format!(
+            "hooks_{}",
+            &uuid::Uuid::new_v4().simple().to_string()[..8]
+        ));
+        let mut conn = app.db_pool.get().unwrap();
+        User::set_role(&mut conn, user.id, Role::OWNER).unwrap();
+        let webhook = Webhook::create(
+            &mut conn,
+            &NewWebhook {
+                user_id: user.id,
+                url: "https://example.com/hook".to_string(),
+                secret: generate_secret(),
+                events: vec![WebhookEvent::GoalReached.as_str().to_string()],
+                global: true,
+            },
+        )
+        .unwrap();
 
-        response.assert_status_ok();
+        let subscribed = |conn: &mut diesel::PgConnection| {
+            Webhook::subscribed_to(conn, WebhookEvent::GoalReached)
+                .unwrap()
+                .iter()
+                .any(|w| w.id == webhook.id)
+        };
+        assert!(subscribed(&mut conn));
+
+        User::set_role(&mut conn, user.id, Role::USER).unwrap();
+        assert!(!subscribed(&mut conn));
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_send_webhook_delivers_signed_payload_to_local_receiver() {
+        let (tx, mut rx) = mpsc::unbounded_channel::<(HeaderMap, Bytes)>();
+        let receiver = axum::Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: Bytes| {
+                let tx = tx.clone();
+                async move {
+                    let _ = tx.send((headers, body));
+                    StatusCode::NO_CONTENT
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, receiver).await.unwrap();
+        });
+
+        let secret = "whsec_integration";
+        let body = br#"{"event":"ping","data":{}}"#;
+        let status = send_webhook(
+            &build_client(),
+            &format!("http://{address}/hook"),
+            secret,
+            WebhookEvent::Ping,
+            "delivery-1",
+            body,
+        )
+        .await
+        .expect("delivery should succeed");
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let (headers, received) = rx.recv().await.expect("receiver should get the payload");
+        assert_eq!(received.as_ref(), body);
+        assert_eq!(headers["x-rustytime-event"], "ping");
+        assert_eq!(headers["x-rustytime-delivery"], "delivery-1");
+
+        let timestamp: i64 = headers["x-rustytime-timestamp"]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(
+            headers["x-rustytime-signature"],
+            sign_payload(secret, timestamp, &received).as_str()
+        );
     }
 }
 
 #[cfg(test)]
-mod not_found_tests {
-    use super::*;
+mod digest_tests {
+    use chrono::NaiveDate;
+    use rustytime_server::jobs::digest::send_digest;
+    use rustytime_server::utils::digest::{DigestStat, WeeklyDigest};
+    use rustytime_server::utils::mailer::Mailer;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    /// Accept one SMTP session and return the raw message data
+    async fn smtp_sink(listener: TcpListener) -> String {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+        let mut data = String::new();
+        let mut in_data = false;
+
+        writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+        while let Some(line) = lines.next_line().await.unwrap() {
+            if in_data {
+                if line == "." {
+                    in_data = false;
+                    writer.write_all(b"250 OK\r\n").await.unwrap();
+                } else {
+                    data.push_str(&line);
+                    data.push('\n');
+                }
+                continue;
+            }
 
-    #[tokio::test]
-    async fn test_unknown_route_returns_not_found() {
-        let config = TestConfig::default();
-        fail_without_db!(config);
+            let command = line.to_ascii_uppercase();
+            if command.starts_with("DATA") {
+                in_data = true;
+                writer.write_all(b"354 Go ahead\r\n").await.unwrap();
+            } else if command.starts_with("QUIT") {
+                writer.write_all(b"221 Bye\r\n").await.unwrap();
+                break;
+            } else {
+                writer.write_all(b"250 OK\r\n").await.unwrap();
+            }
+        }
 
-        let app = TestApp::new().await;
-        let response = app.server.get("/this/route/does/not/exist").await;
+        data
+    }
 
-        response.assert_status(StatusCode::NOT_FOUND);
+    #[tokio::test]
+    async fn test_send_digest_delivers_to_local_smtp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let sink = tokio::spawn(smtp_sink(listener));
+
+        let mailer = Mailer::new(
+            &format!("smtp://{address}"),
+            "rustytime <noreply@localhost>",
+        )
+        .expect("mailer should be created");
+        let digest = WeeklyDigest {
+            username: "digest_user".to_string(),
+            week_start: NaiveDate::from_ymd_opt(2026, 3, 16).unwrap(),
+            total_seconds: 36_000,
+            previous_total_seconds: 24_000,
+            projects: vec![DigestStat {
+                name: "rustytime".to_string(),
+                total_seconds: 30_000,
+            }],
+            languages: vec![DigestStat {
+                name: "Rust".to_string(),
+                total_seconds: 28_800,
+            }],
+        };
+
+        send_digest(
+            &mailer,
+            "user@example.com",
+            &digest,
+            "http://localhost:5173",
+        )
+        .await
+        .expect("digest should be sent");
+
+        let message = sink.await.unwrap();
+        assert!(message.contains("To: user@example.com"));
+        assert!(message.contains("Subject: Your rustytime week: 10h 0m of coding"));
+        assert!(message.contains("+50% compared to the week before"));
+        assert!(message.contains("rustytime: 8h 20m"));
     }
 }
 
@@ -249,7 +1665,7 @@ mod user_tests {
         let app = TestApp::new().await;
         let user = app.create_test_user("test_user_create");
 
-        assert!(user.github_id > 999_000_000);
+        assert!(user.github_id.is_some_and(|id| id > 999_000_000));
         assert_eq!(user.name, "test_user_create");
         assert!(!user.api_key.is_nil());
 
@@ -412,6 +1828,301 @@ mod heartbeat_tests {
         app.cleanup_test_user(user.id);
     }
 
+    #[tokio::test]
+    async fn test_imported_heartbeats_use_project_entity_privacy() {
+        use diesel::prelude::*;
+        use rustytime_server::handlers::api::user::store_heartbeats_in_db_count_only;
+        use rustytime_server::models::heartbeat::{
+            EntityPrivacy, HackatimeHeartbeat, IngestSettings,
+        };
+        use rustytime_server::models::project::{Project, get_or_create_project_id};
+        use rustytime_server::schema::heartbeats;
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_import_privacy_user");
+        let entity = "/home/me/secret-client/src/main.rs";
+
+        let settings = {
+            let mut conn = app.db_pool.get().unwrap();
+            let project_id =
+                get_or_create_project_id(&mut conn, user.id, "secret-client", None).unwrap();
+            Project::set_entity_privacy(
+                &mut conn,
+                project_id,
+                user.id,
+                Some(EntityPrivacy::Hashed as i16),
+            )
+            .unwrap();
+            IngestSettings::load(&mut conn, user.id).unwrap()
+        };
+
+        let imported: HackatimeHeartbeat = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "user_id": 1,
+            "entity": entity,
+            "type": "file",
+            "project": "secret-client",
+            "time": chrono::Utc::now().timestamp() as f64
+        }))
+        .unwrap();
+        let inserted = store_heartbeats_in_db_count_only(
+            &app.db_pool,
+            vec![imported.to_new_heartbeat(user.id, &settings)],
+        )
+        .await
+        .unwrap();
+        assert_eq!(inserted, 1);
+
+        let stored: String = heartbeats::table
+            .filter(heartbeats::user_id.eq(user.id))
+            .select(heartbeats::entity)
+            .first(&mut app.db_pool.get().unwrap())
+            .unwrap();
+        assert_eq!(
+            stored,
+            EntityPrivacy::Hashed.apply(entity.to_string(), &settings.entity_privacy.salt)
+        );
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_hidden_entities_are_left_out_of_project_stats() {
+        use diesel::prelude::*;
+        use rustytime_server::models::heartbeat::{EntityPrivacy, Heartbeat, TimeRange};
+        use rustytime_server::models::project::get_or_create_project_id;
+        use rustytime_server::schema::users;
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_hidden_entity_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        diesel::update(users::table.find(user.id))
+            .set(users::entity_privacy.eq(EntityPrivacy::Hidden as i16))
+            .execute(&mut app.db_pool.get().unwrap())
+            .unwrap();
+
+        let now = chrono::Utc::now().timestamp() as f64;
+        let payload: Vec<serde_json::Value> = [now - 60.0, now]
+            .iter()
+            .map(|time| {
+                serde_json::json!({
+                    "entity": "/home/me/hidden-project/src/main.rs",
+                    "type": "file",
+                    "time": time,
+                    "project": "hidden-project",
+                    "language": "Rust"
+                })
+            })
+            .collect();
+
+        app.server
+            .post("/api/v1/users/current/heartbeats.bulk")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .json(&payload)
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        let mut conn = app.db_pool.get().unwrap();
+        let project_id =
+            get_or_create_project_id(&mut conn, user.id, "hidden-project", None).unwrap();
+        let stats = Heartbeat::get_project_stats_by_range(
+            &mut conn,
+            user.id,
+            project_id,
+            TimeRange::All,
+            "UTC",
+        )
+        .unwrap();
+
+        assert!(stats.total_time > 0);
+        assert!(stats.top_entities.is_empty());
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_pending_language_normalization_rewrites_stored_languages() {
+        use diesel::prelude::*;
+        use rustytime_server::models::language_mapping::{
+            LanguageOverrides, clear_pending_normalization, pending_normalizations,
+        };
+        use rustytime_server::schema::{heartbeats, pending_language_normalizations};
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_pending_normalization_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        app.server
+            .post("/api/v1/users/current/heartbeats")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .json(&mock_heartbeat_payload())
+            .await
+            .assert_status(StatusCode::ACCEPTED);
+
+        let mut conn = app.db_pool.get().unwrap();
+        // a spelling stored before languages were normalized at ingest
+        diesel::update(heartbeats::table.filter(heartbeats::user_id.eq(user.id)))
+            .set(heartbeats::language.eq("typescriptreact"))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::insert_into(pending_language_normalizations::table)
+            .values(pending_language_normalizations::user_id.eq(user.id))
+            .execute(&mut conn)
+            .unwrap();
+
+        assert!(
+            pending_normalizations(&mut conn)
+                .unwrap()
+                .contains(&user.id)
+        );
+        let updated = LanguageOverrides::default()
+            .apply_to_history(&mut conn, user.id)
+            .unwrap();
+        assert_eq!(updated, 1);
+        assert_eq!(clear_pending_normalization(&mut conn, user.id).unwrap(), 1);
+        assert!(
+            !pending_normalizations(&mut conn)
+                .unwrap()
+                .contains(&user.id)
+        );
+
+        let language: Option<String> = heartbeats::table
+            .filter(heartbeats::user_id.eq(user.id))
+            .select(heartbeats::language)
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(language.as_deref(), Some("TypeScript"));
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_daily_totals_refresh_fills_the_rollup_for_active_users() {
+        use chrono::{Duration, Utc};
+        use diesel::prelude::*;
+        use rustytime_server::models::daily_total::DailyTotalRow;
+        use rustytime_server::schema::user_daily_totals;
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_daily_totals_refresh_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        app.server
+            .post("/api/v1/users/current/heartbeats")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .json(&mock_heartbeat_payload())
+            .await
+            .assert_status(StatusCode::ACCEPTED);
+
+        let mut conn = app.db_pool.get().unwrap();
+        let today = Utc::now().date_naive();
+        let stored = |conn: &mut diesel::PgConnection| -> i64 {
+            user_daily_totals::table
+                .filter(user_daily_totals::user_id.eq(user.id))
+                .count()
+                .get_result(conn)
+                .unwrap()
+        };
+
+        // reading the rollup never writes to it
+        let days = DailyTotalRow::last_year_for_user(&mut conn, user.id, today).unwrap();
+        assert!(days.iter().all(|d| d.total_seconds == 0));
+        assert_eq!(stored(&mut conn), 0);
+
+        let stale =
+            DailyTotalRow::stale_users(&mut conn, Utc::now() - Duration::minutes(10)).unwrap();
+        assert!(stale.iter().any(|u| u.user_id == user.id));
+
+        DailyTotalRow::refresh_recent(&mut conn, user.id, "UTC", today).unwrap();
+        assert!(stored(&mut conn) > 0);
+
+        // once up to date, the user is only picked up again with new heartbeats
+        let stale =
+            DailyTotalRow::stale_users(&mut conn, Utc::now() + Duration::minutes(1)).unwrap();
+        assert!(!stale.iter().any(|u| u.user_id == user.id));
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_renamed_and_merged_projects_are_not_recreated() {
+        use diesel::prelude::*;
+        use rustytime_server::models::project::{
+            Project, get_or_create_project_id, invalidate_project_id_cache,
+        };
+        use rustytime_server::schema::heartbeats;
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_rename_redirect_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        {
+            let mut conn = app.db_pool.get().unwrap();
+            let renamed = get_or_create_project_id(&mut conn, user.id, "old-name", None).unwrap();
+            let source =
+                get_or_create_project_id(&mut conn, user.id, "side-project", None).unwrap();
+            Project::rename(&mut conn, user.id, renamed, "new-name").unwrap();
+            Project::merge_into(&mut conn, user.id, source, renamed).unwrap();
+            invalidate_project_id_cache(user.id);
+        }
+
+        let now = chrono::Utc::now().timestamp() as f64;
+        let payload: Vec<serde_json::Value> = ["old-name", "side-project"]
+            .iter()
+            .enumerate()
+            .map(|(i, project)| {
+                serde_json::json!({
+                    "entity": "/path/to/main.rs",
+                    "type": "file",
+                    "time": now - i as f64,
+                    "project": project,
+                })
+            })
+            .collect();
+
+        app.server
+            .post("/api/v1/users/current/heartbeats.bulk")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .json(&payload)
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        let mut conn = app.db_pool.get().unwrap();
+        let projects: Vec<Option<String>> = heartbeats::table
+            .filter(heartbeats::user_id.eq(user.id))
+            .select(heartbeats::project)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(projects.len(), 2);
+        assert!(projects.iter().all(|p| p.as_deref() == Some("new-name")));
+
+        for old_name in ["old-name", "side-project"] {
+            assert!(
+                Project::find_by_name(&mut conn, user.id, old_name)
+                    .unwrap()
+                    .is_none()
+            );
+        }
+
+        app.cleanup_test_user(user.id);
+    }
+
     #[tokio::test]
     async fn test_heartbeat_with_invalid_api_key_fails() {
         let config = TestConfig::default();
@@ -470,6 +2181,120 @@ mod data_retrieval_tests {
         app.cleanup_test_user(user.id);
     }
 
+    #[tokio::test]
+    async fn test_goals_returns_empty_list() {
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_goals_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        let response = app
+            .server
+            .get("/api/v1/users/current/goals")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .await;
+
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"], serde_json::json!([]));
+        assert_eq!(body["total"], 0);
+
+        app.cleanup_test_user(user.id);
+    }
+
+    #[tokio::test]
+    async fn test_goal_progress_matches_per_period_durations() {
+        use rustytime_server::models::goal::{Goal, NewGoal};
+        use rustytime_server::models::heartbeat::{DurationInput, Heartbeat};
+        use rustytime_server::utils::time::{get_day_end_utc, get_day_start_utc};
+
+        let config = TestConfig::default();
+        fail_without_db!(config);
+
+        let app = TestApp::new().await;
+        let user = app.create_test_user("test_goal_periods_user");
+        let auth_value = format!("Basic {}", encode_api_key(&user.api_key));
+
+        // a few bursts spread over the last days, some crossing midnight
+        let now = chrono::Utc::now().timestamp() as f64;
+        let payload: Vec<serde_json::Value> = (0..4)
+            .flat_map(|day| {
+                (0..6).map(move |beat| {
+                    serde_json::json!({
+                        "entity": "/path/to/main.rs",
+                        "type": "file",
+                        "time": now - (day * 86_400 + 3_600) as f64 + (beat * 45) as f64,
+                        "project": "goal-project",
+                        "language": if beat % 2 == 0 { "Rust" } else { "Go" },
+                    })
+                })
+            })
+            .collect();
+
+        app.server
+            .post("/api/v1/users/current/heartbeats.bulk")
+            .add_header(header::AUTHORIZATION, auth_value)
+            .json(&payload)
+            .await
+            .assert_status(StatusCode::CREATED);
+
+        let mut conn = app.db_pool.get().unwrap();
+        for (period, project, language) in [
+            ("day", None, None),
+            ("day", Some("goal-project"), Some("Rust")),
+            ("week", Some("goal-project"), None),
+        ] {
+            Goal::create(
+                &mut conn,
+                &NewGoal {
+                    user_id: user.id,
+                    period: period.to_string(),
+                    target_seconds: 60,
+                    project: project.map(str::to_string),
+                    language: language.map(str::to_string),
+                    ignored_weekdays: 0,
+                },
+            )
+            .unwrap();
+        }
+
+        let progress = Goal::progress_for_user(&mut conn, user.id, "UTC").unwrap();
+        assert_eq!(progress.len(), 3);
+        assert!(
+            progress
+                .iter()
+                .any(|goal| goal.history.iter().any(|p| p.actual_seconds > 0))
+        );
+
+        let tz = chrono_tz::UTC;
+        for goal in progress {
+            for period in goal.history {
+                let expected = Heartbeat::get_user_duration_seconds(
+                    &mut conn,
+                    DurationInput {
+                        user_id: Some(user.id),
+                        start_date: Some(get_day_start_utc(period.start, tz)),
+                        end_date: Some(get_day_end_utc(period.end, tz)),
+                        project: goal.project.clone(),
+                        language: goal.language.clone(),
+                        entity: None,
+                        type_filter: None,
+                    },
+                )
+                .unwrap();
+                assert_eq!(
+                    period.actual_seconds, expected,
+                    "{} {}",
+                    goal.title, period.start
+                );
+            }
+        }
+
+        app.cleanup_test_user(user.id);
+    }
+
     #[tokio::test]
     async fn test_statusbar_today_with_heartbeats() {
         let config = TestConfig::default();